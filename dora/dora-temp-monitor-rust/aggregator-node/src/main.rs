@@ -0,0 +1,156 @@
+use dora_node_api::{arrow::array::StructArray, dora_core::config::DataId, DoraNode, Event};
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::Duration;
+use temp_common::config::{env_or, parse_duration};
+use temp_common::reading::{arrow_to_readings, Reading};
+use temp_common::stats::{stats_to_arrow, WindowStats};
+
+mod window;
+
+use window::{Window, WindowSpec};
+
+/// 按传感器 ID 为每种窗口维护各自的样本缓冲，新传感器首次出现时自动创建
+struct Aggregator {
+    specs: Vec<WindowSpec>,
+    sensors: HashMap<String, Vec<Window>>,
+}
+
+impl Aggregator {
+    fn new(specs: Vec<WindowSpec>) -> Self {
+        Self {
+            specs,
+            sensors: HashMap::new(),
+        }
+    }
+
+    /// 计入一批读数，返回因此结束的窗口统计；非有限值不参与统计
+    fn push_batch(&mut self, readings: &[Reading]) -> Vec<WindowStats> {
+        let mut out = Vec::new();
+        for reading in readings.iter().filter(|r| r.value.is_finite()) {
+            let windows = self
+                .sensors
+                .entry(reading.sensor_id.clone())
+                .or_insert_with(|| self.specs.iter().cloned().map(Window::new).collect());
+            for window in windows {
+                window.push(
+                    &reading.sensor_id,
+                    reading.timestamp_ns,
+                    reading.value,
+                    &mut out,
+                );
+            }
+        }
+        out
+    }
+
+    /// 按时钟关闭已经结束的窗口，传感器停止发送时也能输出最后的统计
+    fn flush(&mut self, now_ns: u64) -> Vec<WindowStats> {
+        let mut out = Vec::new();
+        for (sensor_id, windows) in &mut self.sensors {
+            for window in windows {
+                window.flush_until(sensor_id, now_ns, &mut out);
+            }
+        }
+        out
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    temp_common::config::load_file("aggregator")?;
+    let (mut node, mut events) = DoraNode::init_from_env()?;
+    let output_temp_stats = DataId::from(temp_common::TEMP_STATS.to_owned());
+
+    let specs = WindowSpec::from_env()?;
+    // 按时钟关闭窗口前等待迟到读数的时间
+    let grace: Duration = parse_duration(&env_or("AGG_GRACE", "2s".to_owned())?)?;
+    let names: Vec<String> = specs
+        .iter()
+        .map(|s| format!("{}({})", s.name, s.mode.as_str()))
+        .collect();
+    println!("📊 聚合节点启动 (窗口: {})", names.join(", "));
+
+    let mut aggregator = Aggregator::new(specs);
+
+    while let Some(event) = events.recv() {
+        if let Event::Input { id, metadata, data } = event {
+            let stats = match id.as_str() {
+                "reading" => {
+                    let array = data
+                        .as_any()
+                        .downcast_ref::<StructArray>()
+                        .ok_or("读数不是 StructArray")?;
+                    aggregator.push_batch(&arrow_to_readings(array)?)
+                }
+                "tick" => {
+                    aggregator.flush(temp_common::now_ns().saturating_sub(grace.as_nanos() as u64))
+                }
+                other => {
+                    eprintln!("Received input `{other}`");
+                    continue;
+                }
+            };
+            if !stats.is_empty() {
+                node.send_output(
+                    output_temp_stats.clone(),
+                    metadata.parameters,
+                    stats_to_arrow(&stats),
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use temp_common::stats::{arrow_to_stats, WindowMode};
+
+    const SEC: u64 = 1_000_000_000;
+
+    #[test]
+    fn sensors_and_windows_are_aggregated_independently() {
+        let specs = vec![
+            WindowSpec::tumbling("10s").unwrap(),
+            WindowSpec::sliding("10s/5s").unwrap(),
+        ];
+        let mut aggregator = Aggregator::new(specs);
+
+        let mut sent = Vec::new();
+        for s in 0..12u64 {
+            let batch = [
+                Reading::new(s * SEC, "a", 20.0),
+                Reading::new(s * SEC, "b", if s == 3 { f32::NAN } else { 30.0 }),
+            ];
+            let stats = aggregator.push_batch(&batch);
+            if !stats.is_empty() {
+                sent.push(stats_to_arrow(&stats));
+            }
+        }
+        let received: Vec<WindowStats> = sent
+            .iter()
+            .flat_map(|array| arrow_to_stats(array).unwrap())
+            .collect();
+
+        let find = |sensor: &str, mode, end: u64| {
+            received
+                .iter()
+                .find(|s| s.sensor_id == sensor && s.mode == mode && s.window_end_ns == end * SEC)
+                .unwrap()
+        };
+        let a = find("a", WindowMode::Tumbling, 10);
+        assert_eq!((a.count, a.mean, a.stddev), (10, 20.0, 0.0));
+        let b = find("b", WindowMode::Tumbling, 10);
+        assert_eq!((b.count, b.p99), (9, 30.0));
+        assert_eq!(find("b", WindowMode::Sliding, 5).count, 4);
+        assert_eq!(received.len(), 6);
+
+        // 时钟关闭剩余的滚动窗口 [10s, 20s)
+        let flushed = aggregator.flush(30 * SEC);
+        assert!(flushed
+            .iter()
+            .any(|s| s.mode == WindowMode::Tumbling && s.count == 2));
+    }
+}
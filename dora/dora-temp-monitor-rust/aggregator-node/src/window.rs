@@ -0,0 +1,238 @@
+use std::error::Error;
+use temp_common::config::{env_or, parse_duration};
+use temp_common::stats::{WindowMode, WindowStats};
+
+/// 一种窗口的配置：滚动窗口的步长等于窗口长度
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowSpec {
+    /// 配置中的窗口时长原文，例如 `5m`，作为输出的 `window` 列
+    pub name: String,
+    pub mode: WindowMode,
+    pub length_ns: u64,
+    pub step_ns: u64,
+}
+
+impl WindowSpec {
+    /// `1m` 形式的滚动窗口
+    pub fn tumbling(spec: &str) -> Result<Self, Box<dyn Error>> {
+        let length_ns = duration_ns(spec)?;
+        Ok(Self {
+            name: spec.trim().to_owned(),
+            mode: WindowMode::Tumbling,
+            length_ns,
+            step_ns: length_ns,
+        })
+    }
+
+    /// `1m/10s` 形式的滑动窗口：窗口长度/滑动步长
+    pub fn sliding(spec: &str) -> Result<Self, Box<dyn Error>> {
+        let (length, step) = spec
+            .split_once('/')
+            .ok_or_else(|| format!("滑动窗口 `{spec}` 应写成 `长度/步长`，例如 `1m/10s`"))?;
+        let (length_ns, step_ns) = (duration_ns(length)?, duration_ns(step)?);
+        if step_ns > length_ns {
+            return Err(format!("滑动窗口 `{spec}` 的步长不能大于窗口长度").into());
+        }
+        Ok(Self {
+            name: length.trim().to_owned(),
+            mode: WindowMode::Sliding,
+            length_ns,
+            step_ns,
+        })
+    }
+
+    /// 从 `AGG_TUMBLING` 和 `AGG_SLIDING` 读取窗口列表，逗号分隔，`off` 表示不使用该类窗口
+    pub fn from_env() -> Result<Vec<Self>, Box<dyn Error>> {
+        let tumbling = env_or("AGG_TUMBLING", "1m,5m,1h".to_owned())?;
+        let sliding = env_or("AGG_SLIDING", "1m/10s,5m/30s,1h/5m".to_owned())?;
+        let list = |text: &str| -> Vec<String> {
+            text.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty() && *s != "off")
+                .map(str::to_owned)
+                .collect()
+        };
+
+        let mut specs = Vec::new();
+        for spec in list(&tumbling) {
+            specs.push(Self::tumbling(&spec)?);
+        }
+        for spec in list(&sliding) {
+            specs.push(Self::sliding(&spec)?);
+        }
+        if specs.is_empty() {
+            return Err("AGG_TUMBLING 和 AGG_SLIDING 至少需要配置一个窗口".into());
+        }
+        Ok(specs)
+    }
+}
+
+fn duration_ns(text: &str) -> Result<u64, Box<dyn Error>> {
+    let ns = parse_duration(text)?.as_nanos() as u64;
+    if ns == 0 {
+        return Err(format!("窗口时长 `{}` 必须大于 0", text.trim()).into());
+    }
+    Ok(ns)
+}
+
+/// 单个传感器在一种窗口上的样本缓冲
+///
+/// 窗口边界按 Unix 纪元对齐到步长的整数倍，由读数时间戳推进；
+/// 晚于当前最早窗口起点的乱序读数仍然计入，更早的读数丢弃
+pub struct Window {
+    spec: WindowSpec,
+    samples: Vec<(u64, f32)>,
+    /// 下一个待输出窗口的结束时间
+    next_end: Option<u64>,
+}
+
+impl Window {
+    pub fn new(spec: WindowSpec) -> Self {
+        Self {
+            spec,
+            samples: Vec::new(),
+            next_end: None,
+        }
+    }
+
+    pub fn push(&mut self, sensor_id: &str, ts: u64, value: f32, out: &mut Vec<WindowStats>) {
+        let step = self.spec.step_ns;
+        let next_end = *self.next_end.get_or_insert((ts / step + 1) * step);
+        if ts >= next_end {
+            self.flush_until(sensor_id, ts, out);
+        }
+        let next_end = self.next_end.expect("window was initialised above");
+        if ts >= next_end.saturating_sub(self.spec.length_ns) {
+            self.samples.push((ts, value));
+        }
+    }
+
+    /// 输出所有在 `now_ns` 之前结束的窗口，没有样本的窗口不输出
+    pub fn flush_until(&mut self, sensor_id: &str, now_ns: u64, out: &mut Vec<WindowStats>) {
+        let step = self.spec.step_ns;
+        let Some(mut end) = self.next_end else {
+            return;
+        };
+        while end <= now_ns {
+            let start = end.saturating_sub(self.spec.length_ns);
+            let mut values: Vec<f32> = self
+                .samples
+                .iter()
+                .filter(|&&(ts, _)| ts >= start && ts < end)
+                .map(|&(_, value)| value)
+                .collect();
+            if !values.is_empty() {
+                out.push(self.summarize(sensor_id, start, end, &mut values));
+            }
+
+            end += step;
+            let cutoff = end.saturating_sub(self.spec.length_ns);
+            self.samples.retain(|&(ts, _)| ts >= cutoff);
+            // 长时间没有数据时直接跳到 `now_ns` 所在的窗口，不逐个遍历空窗口
+            if self.samples.is_empty() {
+                end = end.max((now_ns / step + 1) * step);
+            }
+        }
+        self.next_end = Some(end);
+    }
+
+    fn summarize(&self, sensor_id: &str, start: u64, end: u64, values: &mut [f32]) -> WindowStats {
+        values.sort_by(f32::total_cmp);
+        let n = values.len() as f64;
+        let mean = values.iter().map(|&v| v as f64).sum::<f64>() / n;
+        let variance = if values.len() > 1 {
+            values
+                .iter()
+                .map(|&v| (v as f64 - mean).powi(2))
+                .sum::<f64>()
+                / (n - 1.0)
+        } else {
+            0.0
+        };
+        WindowStats {
+            window_start_ns: start,
+            window_end_ns: end,
+            sensor_id: sensor_id.to_owned(),
+            window: self.spec.name.clone(),
+            mode: self.spec.mode,
+            count: values.len() as u64,
+            min: values[0],
+            max: values[values.len() - 1],
+            mean: mean as f32,
+            stddev: variance.sqrt() as f32,
+            p50: percentile(values, 0.50),
+            p95: percentile(values, 0.95),
+            p99: percentile(values, 0.99),
+        }
+    }
+}
+
+/// 已排序数据的分位数，相邻样本间线性插值
+fn percentile(sorted: &[f32], q: f64) -> f32 {
+    let rank = q * (sorted.len() - 1) as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    let frac = (rank - lo as f64) as f32;
+    sorted[lo] + (sorted[hi] - sorted[lo]) * frac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: u64 = 1_000_000_000;
+
+    #[test]
+    fn tumbling_windows_are_aligned_and_disjoint() {
+        let mut window = Window::new(WindowSpec::tumbling("10s").unwrap());
+        let mut out = Vec::new();
+        // 5s..25s 每秒一个读数，值等于秒数
+        for s in 5..25u64 {
+            window.push("a", s * SEC, s as f32, &mut out);
+        }
+
+        assert_eq!(out.len(), 2);
+        let stats = &out[0];
+        assert_eq!((stats.window_start_ns, stats.window_end_ns), (0, 10 * SEC));
+        assert_eq!(stats.count, 5);
+        assert_eq!((stats.min, stats.max, stats.mean), (5.0, 9.0, 7.0));
+        assert!((stats.stddev - 1.5811).abs() < 1e-4);
+        assert_eq!(stats.p50, 7.0);
+        assert!((stats.p95 - 8.8).abs() < 1e-5);
+        assert_eq!(out[1].count, 10);
+
+        // 读数中断后由时钟关闭最后一个窗口，之后的空窗口不输出
+        window.flush_until("a", 100 * SEC, &mut out);
+        assert_eq!(out.len(), 3);
+        assert_eq!(out[2].window_start_ns, 20 * SEC);
+        assert_eq!(out[2].count, 5);
+    }
+
+    #[test]
+    fn sliding_windows_overlap_by_step() {
+        let spec = WindowSpec::sliding("10s/5s").unwrap();
+        assert_eq!(
+            (spec.name.as_str(), spec.mode),
+            ("10s", WindowMode::Sliding)
+        );
+        let mut window = Window::new(spec);
+        let mut out = Vec::new();
+        for s in 0..20u64 {
+            window.push("a", s * SEC, s as f32, &mut out);
+        }
+
+        let ranges: Vec<(u64, u64, u64)> = out
+            .iter()
+            .map(|s| (s.window_start_ns / SEC, s.window_end_ns / SEC, s.count))
+            .collect();
+        // 第一个窗口只覆盖到首个读数之后的部分
+        assert_eq!(ranges, [(0, 5, 5), (0, 10, 10), (5, 15, 10)]);
+        assert_eq!(out[2].mean, 9.5);
+    }
+
+    #[test]
+    fn rejects_invalid_specs() {
+        assert!(WindowSpec::tumbling("0s").is_err());
+        assert!(WindowSpec::sliding("1m").is_err());
+        assert!(WindowSpec::sliding("10s/1m").is_err());
+    }
+}
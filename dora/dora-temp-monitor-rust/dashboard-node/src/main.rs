@@ -0,0 +1,126 @@
+use dora_node_api::{arrow::array::StructArray, DoraNode, Event};
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event as TermEvent, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::Terminal;
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::time::Duration;
+use temp_common::alert::arrow_to_alerts;
+use temp_common::config::env_or;
+use temp_common::forecast::arrow_to_forecasts;
+use temp_common::reading::arrow_to_readings;
+
+mod state;
+mod ui;
+
+use state::DashboardState;
+
+/// 直接在控制终端上绘制：dora 会接管节点的 stdout，所以不能写到标准输出
+struct Tui {
+    terminal: Terminal<CrosstermBackend<File>>,
+}
+
+impl Tui {
+    fn open(tty: &str) -> Result<Self, Box<dyn Error>> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(tty)
+            .map_err(|e| format!("无法打开终端 {tty}: {e}"))?;
+        enable_raw_mode()?;
+        execute!(file, EnterAlternateScreen)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(file))?;
+        terminal.clear()?;
+        Ok(Self { terminal })
+    }
+}
+
+impl Drop for Tui {
+    // 无论正常退出还是出错，都恢复终端状态
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen);
+        let _ = self.terminal.show_cursor();
+    }
+}
+
+/// 处理积压的按键，返回是否请求退出
+fn quit_requested() -> Result<bool, Box<dyn Error>> {
+    while event::poll(Duration::ZERO)? {
+        if let TermEvent::Key(key) = event::read()? {
+            let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers == KeyModifiers::CONTROL;
+            if key.kind == KeyEventKind::Press
+                && (ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc))
+            {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    temp_common::config::load_file("dashboard")?;
+    let (mut _node, mut events) = DoraNode::init_from_env()?;
+
+    let history = env_or("DASHBOARD_HISTORY", 300usize)?;
+    let alert_len = env_or("DASHBOARD_ALERTS", 50usize)?;
+    let tty = env_or("DASHBOARD_TTY", "/dev/tty".to_owned())?;
+    println!("📊 仪表盘节点启动 (终端 {tty})");
+
+    let mut state = DashboardState::new(history, alert_len);
+    let mut tui = Tui::open(&tty)?;
+
+    while let Some(event) = events.recv() {
+        if let Event::Input {
+            id,
+            metadata: _,
+            data,
+        } = event
+        {
+            match id.as_str() {
+                "reading" => {
+                    let array = data
+                        .as_any()
+                        .downcast_ref::<StructArray>()
+                        .ok_or("读数不是 StructArray")?;
+                    for reading in arrow_to_readings(array)? {
+                        state.push_reading(&reading);
+                    }
+                }
+                "alert" => {
+                    let array = data
+                        .as_any()
+                        .downcast_ref::<StructArray>()
+                        .ok_or("告警数据不是 StructArray")?;
+                    for alert in arrow_to_alerts(array)? {
+                        state.push_alert(alert);
+                    }
+                }
+                "forecast" => {
+                    let array = data
+                        .as_any()
+                        .downcast_ref::<StructArray>()
+                        .ok_or("预测数据不是 StructArray")?;
+                    for forecast in arrow_to_forecasts(array)? {
+                        state.push_forecast(forecast);
+                    }
+                }
+                // 按固定频率重绘，避免每条数据都刷新终端
+                "tick" => {
+                    if quit_requested()? {
+                        break;
+                    }
+                    tui.terminal.draw(|frame| ui::draw(frame, &state))?;
+                }
+                other => eprintln!("Received input `{other}`"),
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use temp_common::alert::{Alert, AlertKind, AlertState, Severity};
+use temp_common::forecast::Forecast;
+use temp_common::reading::Reading;
+
+/// 单个传感器的曲线历史和统计
+pub struct SensorSeries {
+    /// `(自首个读数起的秒数, 温度)`，只保留最近的若干点用于绘图
+    pub history: VecDeque<(f64, f64)>,
+    pub current: f32,
+    pub min: f32,
+    pub max: f32,
+    /// 处理器发来的最新预测，未启用预测时为 `None`
+    pub forecast: Option<Forecast>,
+    sum: f64,
+    count: u64,
+}
+
+impl SensorSeries {
+    fn new() -> Self {
+        Self {
+            history: VecDeque::new(),
+            current: f32::NAN,
+            min: f32::INFINITY,
+            max: f32::NEG_INFINITY,
+            forecast: None,
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    /// 仪表盘启动以来的平均温度
+    pub fn avg(&self) -> f32 {
+        if self.count == 0 {
+            return f32::NAN;
+        }
+        (self.sum / self.count as f64) as f32
+    }
+}
+
+/// 仪表盘显示的全部数据，与绘制逻辑分离以便测试
+pub struct DashboardState {
+    history_len: usize,
+    alert_len: usize,
+    start_ns: Option<u64>,
+    pub sensors: BTreeMap<String, SensorSeries>,
+    /// 最近的告警事件，最新的在前
+    pub alerts: VecDeque<Alert>,
+    /// 当前未解除的告警
+    active: HashMap<(String, AlertKind), Severity>,
+}
+
+impl DashboardState {
+    pub fn new(history_len: usize, alert_len: usize) -> Self {
+        Self {
+            history_len,
+            alert_len,
+            start_ns: None,
+            sensors: BTreeMap::new(),
+            alerts: VecDeque::new(),
+            active: HashMap::new(),
+        }
+    }
+
+    pub fn push_reading(&mut self, reading: &Reading) {
+        // 非有限值不参与统计和绘图
+        if !reading.value.is_finite() {
+            return;
+        }
+        let start_ns = *self.start_ns.get_or_insert(reading.timestamp_ns);
+        let x = reading.timestamp_ns.saturating_sub(start_ns) as f64 / 1e9;
+
+        let series = self
+            .sensors
+            .entry(reading.sensor_id.clone())
+            .or_insert_with(SensorSeries::new);
+        series.history.push_back((x, reading.value as f64));
+        if series.history.len() > self.history_len {
+            series.history.pop_front();
+        }
+        series.current = reading.value;
+        series.min = series.min.min(reading.value);
+        series.max = series.max.max(reading.value);
+        series.sum += reading.value as f64;
+        series.count += 1;
+    }
+
+    /// 只记录已有读数的传感器的预测
+    pub fn push_forecast(&mut self, forecast: Forecast) {
+        if let Some(series) = self.sensors.get_mut(&forecast.sensor_id) {
+            series.forecast = Some(forecast);
+        }
+    }
+
+    pub fn push_alert(&mut self, alert: Alert) {
+        let key = (alert.sensor_id.clone(), alert.kind);
+        match alert.state {
+            AlertState::Raised => {
+                self.active.insert(key, alert.severity);
+            }
+            AlertState::Cleared => {
+                self.active.remove(&key);
+            }
+        }
+        self.alerts.push_front(alert);
+        self.alerts.truncate(self.alert_len);
+    }
+
+    /// 当前未解除的告警数，以及其中的最高级别
+    pub fn active_alerts(&self) -> (usize, Option<Severity>) {
+        (self.active.len(), self.active.values().max().copied())
+    }
+
+    /// 横轴范围：所有曲线可见历史的时间跨度
+    pub fn x_bounds(&self) -> [f64; 2] {
+        let (lo, hi) = self
+            .sensors
+            .values()
+            .flat_map(|s| s.history.iter().map(|&(x, _)| x))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), x| {
+                (lo.min(x), hi.max(x))
+            });
+        if lo > hi {
+            return [0.0, 1.0];
+        }
+        [lo, hi.max(lo + 1.0)]
+    }
+
+    /// 纵轴范围：按可见历史的最值自动缩放，上下各留 10% 余量并取整到 1°C
+    pub fn y_bounds(&self) -> [f64; 2] {
+        let (lo, hi) = self
+            .sensors
+            .values()
+            .flat_map(|s| s.history.iter().map(|&(_, y)| y))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), y| {
+                (lo.min(y), hi.max(y))
+            });
+        if lo > hi {
+            return [0.0, 1.0];
+        }
+        let margin = ((hi - lo) * 0.1).max(0.5);
+        [(lo - margin).floor(), (hi + margin).ceil()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(secs: u64, sensor_id: &str, value: f32) -> Reading {
+        Reading::new(1_000_000_000_000 + secs * 1_000_000_000, sensor_id, value)
+    }
+
+    #[test]
+    fn tracks_stats_and_bounded_history_per_sensor() {
+        let mut state = DashboardState::new(3, 10);
+        for (i, value) in [20.0, 24.0, 22.0, 26.0].into_iter().enumerate() {
+            state.push_reading(&reading(i as u64, "a", value));
+        }
+        state.push_reading(&reading(4, "b", f32::NAN));
+
+        let a = &state.sensors["a"];
+        assert_eq!(a.history.len(), 3);
+        assert_eq!(a.history[0], (1.0, 24.0));
+        assert_eq!((a.current, a.min, a.max, a.avg()), (26.0, 20.0, 26.0, 23.0));
+        assert!(!state.sensors.contains_key("b"));
+
+        let forecast = |sensor_id: &str, value| Forecast {
+            timestamp_ns: 0,
+            sensor_id: sensor_id.to_owned(),
+            horizon_s: 30.0,
+            value,
+            lower: value - 1.0,
+            upper: value + 1.0,
+        };
+        state.push_forecast(forecast("a", 27.5));
+        state.push_forecast(forecast("b", 27.5));
+        assert_eq!(state.sensors["a"].forecast.as_ref().unwrap().value, 27.5);
+        assert!(!state.sensors.contains_key("b"));
+    }
+
+    #[test]
+    fn axes_scale_to_visible_data() {
+        let mut state = DashboardState::new(100, 10);
+        assert_eq!(state.y_bounds(), [0.0, 1.0]);
+
+        state.push_reading(&reading(0, "a", 21.2));
+        state.push_reading(&reading(10, "b", 48.6));
+        assert_eq!(state.x_bounds(), [0.0, 10.0]);
+        // 余量 (48.6 - 21.2) * 0.1 = 2.74
+        assert_eq!(state.y_bounds(), [18.0, 52.0]);
+
+        // 单个常数值时至少留 0.5°C 余量
+        let mut flat = DashboardState::new(100, 10);
+        flat.push_reading(&reading(0, "a", 25.0));
+        assert_eq!(flat.y_bounds(), [24.0, 26.0]);
+    }
+
+    #[test]
+    fn alert_panel_keeps_newest_and_tracks_active() {
+        let alert = |kind, state| Alert {
+            timestamp_ns: 0,
+            sensor_id: "a".to_owned(),
+            kind,
+            severity: Severity::Critical,
+            state,
+            value: 0.0,
+            baseline: 0.0,
+            deviation: 0.0,
+            message: String::new(),
+        };
+        let mut state = DashboardState::new(10, 2);
+        state.push_alert(alert(AlertKind::High, AlertState::Raised));
+        state.push_alert(alert(AlertKind::Deviation, AlertState::Raised));
+        state.push_alert(alert(AlertKind::High, AlertState::Cleared));
+
+        assert_eq!(state.alerts.len(), 2);
+        assert_eq!(state.alerts[0].state, AlertState::Cleared);
+        assert_eq!(state.active_alerts(), (1, Some(Severity::Critical)));
+    }
+}
@@ -0,0 +1,215 @@
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::Marker;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Axis, Block, Chart, Dataset, GraphType, List, ListItem, Sparkline};
+use ratatui::Frame;
+use temp_common::alert::{AlertState, Severity};
+
+use crate::state::{DashboardState, SensorSeries};
+
+/// 各传感器曲线依次使用的颜色
+const PALETTE: [Color; 6] = [
+    Color::Cyan,
+    Color::Yellow,
+    Color::Green,
+    Color::Magenta,
+    Color::LightBlue,
+    Color::LightRed,
+];
+
+fn severity_color(severity: Severity) -> Color {
+    match severity {
+        Severity::Critical => Color::Red,
+        Severity::Warning => Color::Yellow,
+        Severity::Info => Color::Gray,
+    }
+}
+
+/// 绘制整个仪表盘：上方温度曲线，中间各传感器统计和迷你图，下方告警列表
+pub fn draw(frame: &mut Frame, state: &DashboardState) {
+    let sensor_rows = state.sensors.len().max(1) as u16 + 2;
+    let [chart, sensors, alerts] = Layout::vertical([
+        Constraint::Min(10),
+        Constraint::Length(sensor_rows),
+        Constraint::Length(10),
+    ])
+    .areas(frame.area());
+
+    draw_chart(frame, chart, state);
+    draw_sensors(frame, sensors, state);
+    draw_alerts(frame, alerts, state);
+}
+
+fn draw_chart(frame: &mut Frame, area: Rect, state: &DashboardState) {
+    let points: Vec<Vec<(f64, f64)>> = state
+        .sensors
+        .values()
+        .map(|series| series.history.iter().copied().collect())
+        .collect();
+    let datasets = state
+        .sensors
+        .keys()
+        .zip(&points)
+        .enumerate()
+        .map(|(i, (sensor_id, points))| {
+            Dataset::default()
+                .name(sensor_id.as_str())
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(PALETTE[i % PALETTE.len()]))
+                .data(points)
+        })
+        .collect();
+
+    let [x_lo, x_hi] = state.x_bounds();
+    let [y_lo, y_hi] = state.y_bounds();
+    let chart = Chart::new(datasets)
+        .block(Block::bordered().title(" 🌡️ 实时温度 (按 q 退出) "))
+        .x_axis(
+            Axis::default()
+                .title("时间 (s)")
+                .bounds([x_lo, x_hi])
+                .labels([format!("{x_lo:.0}"), format!("{x_hi:.0}")]),
+        )
+        .y_axis(Axis::default().title("°C").bounds([y_lo, y_hi]).labels([
+            format!("{y_lo:.0}"),
+            format!("{:.0}", (y_lo + y_hi) / 2.0),
+            format!("{y_hi:.0}"),
+        ]));
+    frame.render_widget(chart, area);
+}
+
+fn draw_sensors(frame: &mut Frame, area: Rect, state: &DashboardState) {
+    let block = Block::bordered().title(" 传感器 (当前 / 最小 / 最大 / 平均 / 预测 °C) ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let rows = Layout::vertical(vec![Constraint::Length(1); state.sensors.len()]).split(inner);
+    for (i, ((sensor_id, series), row)) in state.sensors.iter().zip(rows.iter()).enumerate() {
+        let [stats, spark] =
+            Layout::horizontal([Constraint::Length(72), Constraint::Min(10)]).areas(*row);
+        let color = PALETTE[i % PALETTE.len()];
+        let line = Line::from(vec![
+            Span::styled(format!("{sensor_id:>14} "), Style::default().fg(color)),
+            Span::styled(
+                format!("{:6.1}", series.current),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!(
+                " {:6.1} {:6.1} {:6.1}",
+                series.min,
+                series.max,
+                series.avg()
+            )),
+            Span::styled(forecast_text(series), Style::default().fg(Color::DarkGray)),
+        ]);
+        frame.render_widget(line, stats);
+        frame.render_widget(sparkline(series, spark.width as usize, color), spark);
+    }
+}
+
+/// 例如 ` → 38.2±1.3 (30s)`，没有预测时为空
+fn forecast_text(series: &SensorSeries) -> String {
+    match &series.forecast {
+        Some(f) => format!(
+            " → {:.1}±{:.1} ({:.0}s)",
+            f.value,
+            (f.upper - f.lower) / 2.0,
+            f.horizon_s
+        ),
+        None => String::new(),
+    }
+}
+
+/// 迷你图只能显示非负整数，以可见窗口的最低温度为零点、0.1°C 为一格
+fn sparkline(series: &SensorSeries, width: usize, color: Color) -> Sparkline<'static> {
+    let recent: Vec<f64> = series
+        .history
+        .iter()
+        .rev()
+        .take(width)
+        .rev()
+        .map(|&(_, y)| y)
+        .collect();
+    let lo = recent.iter().copied().fold(f64::INFINITY, f64::min);
+    let data: Vec<u64> = recent
+        .iter()
+        .map(|y| ((y - lo) * 10.0).round() as u64 + 1)
+        .collect();
+    Sparkline::default()
+        .data(data)
+        .style(Style::default().fg(color))
+}
+
+fn draw_alerts(frame: &mut Frame, area: Rect, state: &DashboardState) {
+    let (active, worst) = state.active_alerts();
+    let title_style = worst.map_or(Style::default(), |s| Style::default().fg(severity_color(s)));
+    let block = Block::bordered().title(Span::styled(
+        format!(" 告警 (未解除 {active}) "),
+        title_style,
+    ));
+
+    let items: Vec<ListItem> = state
+        .alerts
+        .iter()
+        .map(|alert| {
+            let color = match alert.state {
+                AlertState::Cleared => Color::Green,
+                AlertState::Raised => severity_color(alert.severity),
+            };
+            ListItem::new(format!(
+                "{} [{}] {}",
+                temp_common::format_time(alert.timestamp_ns),
+                alert.sensor_id,
+                alert.message
+            ))
+            .style(Style::default().fg(color))
+        })
+        .collect();
+    frame.render_widget(List::new(items).block(block), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use temp_common::forecast::Forecast;
+    use temp_common::reading::Reading;
+
+    #[test]
+    fn draws_empty_and_populated_dashboard() {
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        let mut state = DashboardState::new(100, 10);
+        terminal.draw(|frame| draw(frame, &state)).unwrap();
+
+        for i in 0..50u64 {
+            state.push_reading(&Reading::new(
+                i * 100_000_000,
+                "sensor0",
+                25.0 + i as f32 * 0.1,
+            ));
+            state.push_reading(&Reading::new(i * 100_000_000, "sensor1", 30.0));
+        }
+        state.push_forecast(Forecast {
+            timestamp_ns: 4_900_000_000,
+            sensor_id: "sensor0".to_owned(),
+            horizon_s: 30.0,
+            value: 35.9,
+            lower: 34.6,
+            upper: 37.2,
+        });
+        terminal.draw(|frame| draw(frame, &state)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("sensor1"));
+        assert!(screen.contains("29.9"));
+        assert!(screen.contains("35.9±1.3 (30s)"));
+    }
+}
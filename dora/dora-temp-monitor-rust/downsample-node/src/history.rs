@@ -0,0 +1,112 @@
+use std::collections::{BTreeMap, VecDeque};
+use temp_common::downsample::lttb_indices;
+use temp_common::reading::Reading;
+
+/// 按传感器保存最近 `retention_ns` 内的读数，按时间范围取出降采样后的序列
+pub struct History {
+    retention_ns: u64,
+    /// 传感器 ID → 按时间升序的 `(时间戳, 温度)`；按 ID 排序使输出顺序稳定
+    sensors: BTreeMap<String, VecDeque<(u64, f32)>>,
+    /// 收到的最新读数时间，时间范围以它为终点，回放历史数据时同样适用
+    latest_ns: u64,
+}
+
+impl History {
+    pub fn new(retention_ns: u64) -> Self {
+        Self {
+            retention_ns,
+            sensors: BTreeMap::new(),
+            latest_ns: 0,
+        }
+    }
+
+    /// 计入一批读数；非有限值不计入，迟到的读数按时间插入
+    pub fn push(&mut self, readings: &[Reading]) {
+        for reading in readings.iter().filter(|r| r.value.is_finite()) {
+            let samples = self.sensors.entry(reading.sensor_id.clone()).or_default();
+            let sample = (reading.timestamp_ns, reading.value);
+            match samples.back() {
+                Some(&(last, _)) if reading.timestamp_ns < last => {
+                    let at = samples.partition_point(|&(t, _)| t <= reading.timestamp_ns);
+                    samples.insert(at, sample);
+                }
+                _ => samples.push_back(sample),
+            }
+            self.latest_ns = self.latest_ns.max(reading.timestamp_ns);
+        }
+        self.prune();
+    }
+
+    /// 丢弃超出保留时长的读数，所有读数都过期的传感器一并移除
+    fn prune(&mut self) {
+        let cutoff = self.latest_ns.saturating_sub(self.retention_ns);
+        self.sensors.retain(|_, samples| {
+            while samples.front().is_some_and(|&(t, _)| t < cutoff) {
+                samples.pop_front();
+            }
+            !samples.is_empty()
+        });
+    }
+
+    /// 最近 `range_ns` 内每个传感器至多 `points` 个点的 LTTB 降采样序列
+    pub fn series(&self, range_ns: u64, points: usize) -> Vec<Reading> {
+        let start = self.latest_ns.saturating_sub(range_ns);
+        let mut out = Vec::new();
+        for (sensor_id, samples) in &self.sensors {
+            let from = samples.partition_point(|&(t, _)| t < start);
+            let window: Vec<(u64, f32)> = samples.range(from..).copied().collect();
+            // 以范围起点为原点换算为秒，避免纳秒时间戳转为 f64 时丢失精度
+            let xy: Vec<(f64, f64)> = window
+                .iter()
+                .map(|&(t, v)| ((t - start) as f64 / 1e9, v as f64))
+                .collect();
+            out.extend(lttb_indices(&xy, points).into_iter().map(|i| {
+                let (t, v) = window[i];
+                Reading::new(t, sensor_id, v)
+            }));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: u64 = 1_000_000_000;
+
+    #[test]
+    fn series_covers_requested_range_within_retention() {
+        let mut history = History::new(600 * SEC);
+        // 两个传感器 10 Hz 采样 20 分钟，只保留最近 10 分钟
+        for i in 0..12_000u64 {
+            let ts = i * SEC / 10;
+            history.push(&[
+                Reading::new(ts, "b", 30.0 + (i % 100) as f32 / 10.0),
+                Reading::new(ts, "a", 20.0),
+            ]);
+        }
+        let latest = history.latest_ns;
+        assert_eq!(latest, 11_999 * SEC / 10);
+
+        let series = history.series(60 * SEC, 100);
+        assert_eq!(series.len(), 200);
+        assert_eq!(series[0].sensor_id, "a");
+        assert_eq!(series[99].timestamp_ns, latest);
+        assert!(series.iter().all(|r| r.timestamp_ns >= latest - 60 * SEC));
+
+        // 超出保留时长的范围只能取到保留的部分
+        let all = history.series(3600 * SEC, 100_000);
+        assert_eq!(all.len(), 2 * 6001);
+
+        // 迟到的读数按时间插入
+        history.push(&[Reading::new(latest - SEC / 20, "a", 21.0)]);
+        let tail = history.series(SEC / 10, 10);
+        let a: Vec<f32> = tail
+            .iter()
+            .filter(|r| r.sensor_id == "a")
+            .map(|r| r.value)
+            .collect();
+        assert_eq!(a, [20.0, 21.0, 20.0]);
+    }
+}
@@ -0,0 +1,219 @@
+use dora_node_api::arrow::array::{StringArray, StructArray};
+use dora_node_api::{ArrowData, MetadataParameters, Parameter};
+use node_harness::{NodeHandler, Outputs};
+use std::error::Error;
+use std::sync::Arc;
+use temp_common::config::{env_or, parse_duration};
+use temp_common::downsample::{POINTS_PARAM, RANGE_PARAM};
+use temp_common::reading::{arrow_to_readings, readings_to_arrow};
+
+mod history;
+
+use history::History;
+
+/// 按 tick 定期输出的一个时间范围
+#[derive(Debug, Clone, PartialEq)]
+struct Range {
+    /// 配置中的时长原文，例如 `1h`，作为输出的 `range` 参数
+    name: String,
+    length_ns: u64,
+}
+
+impl Range {
+    fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        let length_ns = parse_duration(text)?.as_nanos() as u64;
+        if length_ns == 0 {
+            return Err(format!("时间范围 `{}` 必须大于 0", text.trim()).into());
+        }
+        Ok(Self {
+            name: text.trim().to_owned(),
+            length_ns,
+        })
+    }
+
+    /// 逗号分隔的时间范围列表
+    fn parse_list(text: &str) -> Result<Vec<Self>, Box<dyn Error>> {
+        text.split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(Self::parse)
+            .collect()
+    }
+}
+
+/// 降采样节点：缓存平滑读数，`tick` 时输出各配置范围的降采样序列，
+/// `request` 输入按前端请求的时间范围即时输出
+struct Downsampler {
+    history: History,
+    ranges: Vec<Range>,
+    points: usize,
+    /// 上次按 tick 输出后是否收到过新读数
+    updated: bool,
+}
+
+impl Downsampler {
+    fn send_series(
+        &self,
+        range: &Range,
+        points: usize,
+        parameters: &MetadataParameters,
+        outputs: &mut dyn Outputs,
+    ) -> Result<(), Box<dyn Error>> {
+        let series = self.history.series(range.length_ns, points);
+        if series.is_empty() {
+            return Ok(());
+        }
+        let mut parameters = parameters.clone();
+        parameters.insert(
+            RANGE_PARAM.to_owned(),
+            Parameter::String(range.name.clone()),
+        );
+        parameters.insert(POINTS_PARAM.to_owned(), Parameter::Integer(points as i64));
+        outputs.send(
+            temp_common::TEMP_SERIES,
+            parameters,
+            Arc::new(readings_to_arrow(&series)),
+        )
+    }
+}
+
+impl NodeHandler for Downsampler {
+    fn on_input(
+        &mut self,
+        id: &str,
+        parameters: MetadataParameters,
+        data: ArrowData,
+        outputs: &mut dyn Outputs,
+    ) -> Result<(), Box<dyn Error>> {
+        match id {
+            "reading" => {
+                let array = data
+                    .as_any()
+                    .downcast_ref::<StructArray>()
+                    .ok_or("读数不是 StructArray")?;
+                self.history.push(&arrow_to_readings(array)?);
+                self.updated = true;
+            }
+            "tick" => {
+                if !self.updated {
+                    return Ok(());
+                }
+                for range in &self.ranges {
+                    self.send_series(range, self.points, &parameters, outputs)?;
+                }
+                self.updated = false;
+            }
+            // 数据为时长字符串列表，例如 `["30m"]`；可用 `points` 参数指定点数
+            "request" => {
+                let Some(array) = data.as_any().downcast_ref::<StringArray>() else {
+                    eprintln!("降采样请求不是字符串数组: {}", data.data_type());
+                    return Ok(());
+                };
+                let points = match parameters.get(POINTS_PARAM) {
+                    Some(Parameter::Integer(n)) if *n >= 3 => *n as usize,
+                    _ => self.points,
+                };
+                // 前端请求有误时只报告，不中止节点
+                for text in array.iter().flatten() {
+                    match Range::parse(text) {
+                        Ok(range) => self.send_series(&range, points, &parameters, outputs)?,
+                        Err(e) => eprintln!("无效的降采样请求 `{text}`: {e}"),
+                    }
+                }
+            }
+            other => eprintln!("Received input `{other}`"),
+        }
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    temp_common::config::load_file("downsample")?;
+    let ranges = Range::parse_list(&env_or("DOWNSAMPLE_RANGES", "10m,1h,6h".to_owned())?)?;
+    let points: usize = env_or("DOWNSAMPLE_POINTS", 500)?;
+    if points < 3 {
+        return Err("DOWNSAMPLE_POINTS 至少为 3".into());
+    }
+    // 默认保留到最长的输出范围，请求更长的范围时只能取到保留的部分
+    let longest = ranges.iter().map(|r| r.length_ns).max().unwrap_or_default();
+    let retention_ns = match env_or("DOWNSAMPLE_RETENTION", String::new())? {
+        text if text.is_empty() => longest,
+        text => (parse_duration(&text)?.as_nanos() as u64).max(longest),
+    };
+    if retention_ns == 0 {
+        return Err("DOWNSAMPLE_RANGES 和 DOWNSAMPLE_RETENTION 至少需要配置一个".into());
+    }
+
+    let names: Vec<&str> = ranges.iter().map(|r| r.name.as_str()).collect();
+    println!(
+        "📉 降采样节点启动 (范围: {}，每个传感器最多 {points} 点)",
+        names.join(", ")
+    );
+
+    node_harness::run(&mut Downsampler {
+        history: History::new(retention_ns),
+        ranges,
+        points,
+        updated: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use node_harness::{FakeNode, Input};
+    use temp_common::reading::Reading;
+
+    const SEC: u64 = 1_000_000_000;
+
+    #[test]
+    fn ticks_emit_configured_ranges_and_requests_are_answered() {
+        let mut downsampler = Downsampler {
+            history: History::new(3600 * SEC),
+            ranges: Range::parse_list("1m, 10m").unwrap(),
+            points: 50,
+            updated: false,
+        };
+        let mut node = FakeNode::with_outputs(&[temp_common::TEMP_SERIES]);
+
+        // 没有读数时 tick 不输出
+        node.push(Input::tick("tick"));
+        assert!(node.run(&mut downsampler).unwrap().is_empty());
+
+        // 10 Hz 采样 30 分钟
+        for minute in 0..30u64 {
+            let batch: Vec<Reading> = (0..600u64)
+                .map(|i| Reading::new((minute * 600 + i) * SEC / 10, "sensor0", 25.0))
+                .collect();
+            node.push(Input::new("reading", readings_to_arrow(&batch)));
+        }
+        node.push(Input::tick("tick")).push(Input::tick("tick"));
+        let sent = node.run(&mut downsampler).unwrap();
+        // 第二次 tick 之前没有新读数，不重复输出
+        assert_eq!(sent.len(), 2);
+        let ranges: Vec<&Parameter> = sent.iter().map(|o| &o.parameters[RANGE_PARAM]).collect();
+        assert_eq!(
+            ranges,
+            [
+                &Parameter::String("1m".into()),
+                &Parameter::String("10m".into())
+            ]
+        );
+        for output in &sent {
+            let series = arrow_to_readings(output.downcast::<StructArray>().unwrap()).unwrap();
+            assert_eq!(series.len(), 50);
+        }
+
+        // 前端请求任意范围和点数，无效的范围被忽略
+        node.push(
+            Input::new("request", StringArray::from(vec!["30m", "soon"]))
+                .with_parameter(POINTS_PARAM, Parameter::Integer(200)),
+        );
+        let sent = node.run(&mut downsampler).unwrap();
+        assert_eq!(sent.len(), 1);
+        let series = arrow_to_readings(sent[0].downcast::<StructArray>().unwrap()).unwrap();
+        assert_eq!(series.len(), 200);
+        assert_eq!(series[0].timestamp_ns, 0);
+        assert_eq!(sent[0].parameters[POINTS_PARAM], Parameter::Integer(200));
+    }
+}
@@ -1,40 +1,210 @@
-use dora_node_api::{
-    arrow::array::{Float32Array, StringArray},
-    DoraNode, Event,
-};
+use dora_node_api::{arrow::array::StructArray, DoraNode, Event};
+use std::collections::HashMap;
 use std::error::Error;
+use temp_common::alert::{arrow_to_alerts, Alert, AlertState, Severity};
+use temp_common::config::{self, env_or};
+use temp_common::format_time;
+use temp_common::health::{arrow_to_health, HealthEvent};
+use temp_common::reading::arrow_to_readings;
+use temp_common::stats::{arrow_to_stats, WindowMode, WindowStats};
+use temp_common::units::{source_units_param, Unit};
+
+mod sink;
+
+use sink::{RotatingSink, SinkConfig};
+
+/// 终端显示配置，修改配置文件后通过 `reload` 输入热加载
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DisplayConfig {
+    /// 终端显示单位，文件中始终保存摄氏度
+    unit: Unit,
+    /// 柱状图每摄氏度的字符数
+    bar_scale: f32,
+}
+
+impl DisplayConfig {
+    fn from_env() -> Result<Self, Box<dyn Error>> {
+        let unit = env_or("DISPLAY_UNIT", Unit::Celsius)?;
+        if unit == Unit::Raw {
+            return Err("DISPLAY_UNIT 只能是 celsius、fahrenheit 或 kelvin".into());
+        }
+        let bar_scale = env_or("BAR_SCALE", 2.0f32)?;
+        if !(bar_scale > 0.0 && bar_scale.is_finite()) {
+            return Err(format!("BAR_SCALE 必须为正数: {bar_scale}").into());
+        }
+        Ok(Self { unit, bar_scale })
+    }
+}
+
+/// 以显示单位格式化摄氏温度
+fn temp(unit: Unit, celsius: f32) -> String {
+    format!("{:.1}{}", unit.from_celsius(celsius as f64), unit.symbol())
+}
+
+/// 终端中一条告警的显示内容；告警文本中的温度为摄氏度，非摄氏显示时附上换算后的读数
+fn render_alert(alert: &Alert, unit: Unit) -> String {
+    let icon = match (alert.state, alert.severity) {
+        (AlertState::Cleared, _) => "✅",
+        (_, Severity::Critical) => "🚨",
+        (_, Severity::Warning) => "⚠️",
+        (_, Severity::Info) => "ℹ️",
+    };
+    let converted = match unit {
+        Unit::Celsius => String::new(),
+        unit => format!(" ({})", temp(unit, alert.value)),
+    };
+    format!(
+        "{icon} {} [{}] {}/{} {}{converted}",
+        format_time(alert.timestamp_ns),
+        alert.sensor_id,
+        alert.kind.as_str(),
+        alert.severity.as_str(),
+        alert.message
+    )
+}
+
+/// 终端中一条数据质量事件的显示内容
+fn render_health(event: &HealthEvent) -> String {
+    let icon = match event.state {
+        AlertState::Cleared => "✅",
+        AlertState::Raised => "🩺",
+    };
+    format!(
+        "{icon} {} [{}] {}/{} {}",
+        format_time(event.timestamp_ns),
+        event.sensor_id,
+        event.issue.as_str(),
+        event.severity.as_str(),
+        event.message
+    )
+}
+
+/// 终端中一条窗口统计的显示内容
+fn render_stats(stats: &WindowStats, unit: Unit) -> String {
+    let value = |celsius: f32| unit.from_celsius(celsius as f64);
+    let symbol = unit.symbol();
+    format!(
+        "📊 {} [{}] {} 窗口 n={} 均值 {:.2}±{:.2}{symbol} 范围 {:.1}~{:.1}{symbol} p50/p95/p99 {:.1}/{:.1}/{:.1}{symbol}",
+        format_time(stats.window_end_ns),
+        stats.sensor_id,
+        stats.window,
+        stats.count,
+        value(stats.mean),
+        unit.from_celsius_delta(stats.stddev as f64),
+        value(stats.min),
+        value(stats.max),
+        value(stats.p50),
+        value(stats.p95),
+        value(stats.p99)
+    )
+}
 
 fn main() -> Result<(), Box<dyn Error>> {
+    config::load_file("logger")?;
     let (mut _node, mut events) = DoraNode::init_from_env()?;
-    println!("日志节点启动");
+    let mut display = DisplayConfig::from_env()?;
+    println!("日志节点启动 (显示单位 {})", display.unit.symbol());
+    // 已经提示过原始单位的传感器
+    let mut source_units: HashMap<String, Unit> = HashMap::new();
+
+    // 可选的持久化：平滑数据、告警和数据质量事件分别写入各自的滚动文件
+    let (mut smoothed_sink, mut alert_sink, mut health_sink, mut stats_sink) =
+        match SinkConfig::from_env()? {
+            Some(config) => {
+                println!("💾 持久化到 {} ({:?})", config.dir.display(), config.format);
+                (
+                    Some(RotatingSink::new("smoothed", config.clone())?),
+                    Some(RotatingSink::new("alert", config.clone())?),
+                    Some(RotatingSink::new("health", config.clone())?),
+                    Some(RotatingSink::new("stats", config)?),
+                )
+            }
+            None => (None, None, None, None),
+        };
+
     while let Some(event) = events.recv() {
-        match event {
-            Event::Input {
-                id,
-                metadata: _,
-                data,
-            } => match id.as_str() {
+        if let Event::Input { id, metadata, data } = event {
+            match id.as_str() {
                 "smoothed" => {
                     let array = data
                         .as_any()
-                        .downcast_ref::<Float32Array>()
+                        .downcast_ref::<StructArray>()
                         .ok_or("转换失败")?;
-                    let temp = array.value(0);
 
-                    // 终端柱状图（M1终端性能强劲）
-                    let bar = "█".repeat((temp * 2.0) as usize);
-                    println!("\r[{:4.1}°C] {}", temp, bar);
+                    for (sensor_id, source) in source_units_param(&metadata.parameters)? {
+                        if source_units.insert(sensor_id.clone(), source) != Some(source) {
+                            println!("📐 {sensor_id} 原始单位为 {source}，已换算为摄氏度");
+                        }
+                    }
+
+                    // 终端柱状图（M1终端性能强劲），每个传感器一行
+                    for reading in arrow_to_readings(array)? {
+                        let bar = "█".repeat((reading.value * display.bar_scale).max(0.0) as usize);
+                        println!(
+                            "\r{:>12} [{:>7}] {}",
+                            reading.sensor_id,
+                            temp(display.unit, reading.value),
+                            bar
+                        );
+                    }
+                    if let Some(sink) = &mut smoothed_sink {
+                        sink.write(array, temp_common::now_ns())?;
+                    }
                 }
                 "alert" => {
                     let array = data
                         .as_any()
-                        .downcast_ref::<StringArray>()
-                        .ok_or("转换失败")?;
-                    println!("\n🚨 {}", array.value(0));
+                        .downcast_ref::<StructArray>()
+                        .ok_or("告警数据不是 StructArray")?;
+                    for alert in arrow_to_alerts(array)? {
+                        println!("\n{}", render_alert(&alert, display.unit));
+                    }
+                    if let Some(sink) = &mut alert_sink {
+                        sink.write(array, temp_common::now_ns())?;
+                    }
+                }
+                "health" => {
+                    let array = data
+                        .as_any()
+                        .downcast_ref::<StructArray>()
+                        .ok_or("数据质量事件不是 StructArray")?;
+                    for event in arrow_to_health(array)? {
+                        println!("\n{}", render_health(&event));
+                    }
+                    if let Some(sink) = &mut health_sink {
+                        sink.write(array, temp_common::now_ns())?;
+                    }
+                }
+                "stats" => {
+                    let array = data
+                        .as_any()
+                        .downcast_ref::<StructArray>()
+                        .ok_or("窗口统计不是 StructArray")?;
+                    // 滑动窗口更新频繁，终端只显示滚动窗口，文件中保留全部
+                    for stats in arrow_to_stats(array)?
+                        .iter()
+                        .filter(|s| s.mode == WindowMode::Tumbling)
+                    {
+                        println!("\n{}", render_stats(stats, display.unit));
+                    }
+                    if let Some(sink) = &mut stats_sink {
+                        sink.write(array, temp_common::now_ns())?;
+                    }
                 }
+                // 配置文件修改后热加载显示配置，持久化配置仍需重启
+                "reload" => match config::reload_if_changed() {
+                    Ok(true) => match DisplayConfig::from_env() {
+                        Ok(new) => {
+                            display = new;
+                            println!("🔄 已重新加载显示配置");
+                        }
+                        Err(e) => eprintln!("配置无效，继续使用之前的配置: {e}"),
+                    },
+                    Ok(false) => {}
+                    Err(e) => eprintln!("重新加载配置失败: {e}"),
+                },
                 other => eprintln!("Logger： Received input `{}`", other),
-            },
-            _ => {}
+            }
         }
     }
 
@@ -0,0 +1,358 @@
+//! 把收到的 Arrow 批次持久化到按大小/时间滚动的文件中
+
+use chrono::DateTime;
+use dora_node_api::arrow::array::{Array, RecordBatch, StructArray};
+use dora_node_api::arrow::{csv, json};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use temp_common::config::env_or;
+
+/// 持久化文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Csv,
+    Jsonl,
+    Parquet,
+}
+
+impl LogFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            LogFormat::Csv => "csv",
+            LogFormat::Jsonl => "jsonl",
+            LogFormat::Parquet => "parquet",
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(LogFormat::Csv),
+            "jsonl" => Ok(LogFormat::Jsonl),
+            "parquet" => Ok(LogFormat::Parquet),
+            other => Err(format!("unknown log format `{other}`")),
+        }
+    }
+}
+
+/// 持久化配置，由 logger 的 `env:` 读取
+#[derive(Debug, Clone, PartialEq)]
+pub struct SinkConfig {
+    pub format: LogFormat,
+    pub dir: PathBuf,
+    /// 单个文件超过该字节数后滚动，0 表示不按大小滚动
+    pub rotate_bytes: u64,
+    /// 单个文件写入超过该秒数后滚动，0 表示不按时间滚动
+    pub rotate_secs: u64,
+    /// 每类数据最多保留的文件数（含当前文件），0 表示全部保留
+    pub retain_files: usize,
+}
+
+impl SinkConfig {
+    /// 读取 `LOG_FORMAT`（`none` | `csv` | `jsonl` | `parquet`），`none`（默认）表示只在终端显示
+    pub fn from_env() -> Result<Option<Self>, Box<dyn Error>> {
+        let format = env_or("LOG_FORMAT", "none".to_owned())?;
+        if format == "none" {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            format: format.parse()?,
+            dir: PathBuf::from(env_or("LOG_DIR", "logs".to_owned())?),
+            rotate_bytes: env_or("LOG_ROTATE_BYTES", 10 * 1024 * 1024)?,
+            rotate_secs: env_or("LOG_ROTATE_SECS", 3600)?,
+            retain_files: env_or("LOG_RETAIN_FILES", 24)?,
+        }))
+    }
+}
+
+/// 当前正在写入的文件
+enum FileWriter {
+    Csv(Box<csv::Writer<File>>),
+    Jsonl(json::LineDelimitedWriter<File>),
+    Parquet(Box<ArrowWriter<File>>),
+}
+
+impl FileWriter {
+    fn create(format: LogFormat, path: &Path, batch: &RecordBatch) -> Result<Self, Box<dyn Error>> {
+        let file = File::create(path).map_err(|e| format!("创建 {} 失败: {e}", path.display()))?;
+        let writer = match format {
+            LogFormat::Csv => FileWriter::Csv(Box::new(csv::Writer::new(file))),
+            LogFormat::Jsonl => FileWriter::Jsonl(json::LineDelimitedWriter::new(file)),
+            LogFormat::Parquet => {
+                let props = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                FileWriter::Parquet(Box::new(ArrowWriter::try_new(
+                    file,
+                    batch.schema(),
+                    Some(props),
+                )?))
+            }
+        };
+        Ok(writer)
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<(), Box<dyn Error>> {
+        match self {
+            FileWriter::Csv(writer) => writer.write(batch)?,
+            FileWriter::Jsonl(writer) => writer.write(batch)?,
+            FileWriter::Parquet(writer) => {
+                writer.write(batch)?;
+                // 每批写成一个行组，节点异常退出时最多丢失页脚而不是缓冲中的数据
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn close(self) -> Result<(), Box<dyn Error>> {
+        match self {
+            FileWriter::Csv(_) => {}
+            FileWriter::Jsonl(mut writer) => writer.finish()?,
+            FileWriter::Parquet(writer) => {
+                writer.close()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct OpenFile {
+    path: PathBuf,
+    writer: FileWriter,
+    opened_ns: u64,
+}
+
+/// 一类数据（如 `smoothed`、`alert`）的滚动文件输出
+///
+/// 文件名为 `{stream}-{UTC 时间}-{序号}.{扩展名}`，按名称排序即按时间排序
+pub struct RotatingSink {
+    stream: String,
+    config: SinkConfig,
+    current: Option<OpenFile>,
+    sequence: u32,
+}
+
+impl RotatingSink {
+    pub fn new(stream: &str, config: SinkConfig) -> Result<Self, Box<dyn Error>> {
+        fs::create_dir_all(&config.dir)
+            .map_err(|e| format!("创建日志目录 {} 失败: {e}", config.dir.display()))?;
+        Ok(Self {
+            stream: stream.to_owned(),
+            config,
+            current: None,
+            sequence: 0,
+        })
+    }
+
+    /// 在 `now_ns` 时刻写入一批数据，必要时先滚动到新文件
+    pub fn write(&mut self, array: &StructArray, now_ns: u64) -> Result<(), Box<dyn Error>> {
+        if array.is_empty() {
+            return Ok(());
+        }
+        let batch = RecordBatch::from(array.clone());
+
+        if self.current.as_ref().is_some_and(|file| {
+            let age_secs = now_ns.saturating_sub(file.opened_ns) / 1_000_000_000;
+            self.config.rotate_secs > 0 && age_secs >= self.config.rotate_secs
+        }) {
+            self.close()?;
+        }
+
+        if self.current.is_none() {
+            self.current = Some(self.open(&batch, now_ns)?);
+        }
+        let file = self.current.as_mut().expect("file was just opened");
+        file.writer.write(&batch)?;
+
+        let size = fs::metadata(&file.path).map(|m| m.len()).unwrap_or(0);
+        if self.config.rotate_bytes > 0 && size >= self.config.rotate_bytes {
+            self.close()?;
+        }
+        Ok(())
+    }
+
+    /// 关闭当前文件（写入 Parquet 页脚等），下一次写入时再创建新文件
+    pub fn close(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(file) = self.current.take() {
+            file.writer.close()?;
+        }
+        Ok(())
+    }
+
+    fn open(&mut self, batch: &RecordBatch, now_ns: u64) -> Result<OpenFile, Box<dyn Error>> {
+        let time = DateTime::from_timestamp_nanos(now_ns as i64).format("%Y%m%dT%H%M%S");
+        let name = format!(
+            "{}-{time}-{:04}.{}",
+            self.stream,
+            self.sequence,
+            self.config.format.extension()
+        );
+        self.sequence = self.sequence.wrapping_add(1);
+
+        let path = self.config.dir.join(name);
+        let writer = FileWriter::create(self.config.format, &path, batch)?;
+        self.prune()?;
+        Ok(OpenFile {
+            path,
+            writer,
+            opened_ns: now_ns,
+        })
+    }
+
+    /// 删除超出保留数量的最旧文件（新文件已创建，计入保留数量）
+    fn prune(&self) -> Result<(), Box<dyn Error>> {
+        if self.config.retain_files == 0 {
+            return Ok(());
+        }
+        let prefix = format!("{}-", self.stream);
+        let suffix = format!(".{}", self.config.format.extension());
+        let mut files: Vec<PathBuf> = fs::read_dir(&self.config.dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(&suffix))
+            })
+            .collect();
+        files.sort();
+
+        let excess = files.len().saturating_sub(self.config.retain_files);
+        for path in &files[..excess] {
+            fs::remove_file(path).map_err(|e| format!("删除 {} 失败: {e}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RotatingSink {
+    fn drop(&mut self) {
+        if let Err(e) = self.close() {
+            eprintln!("关闭 {} 日志文件失败: {e}", self.stream);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use temp_common::reading::{arrow_to_readings, readings_to_arrow, Reading};
+
+    const SECOND: u64 = 1_000_000_000;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("logger-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn config(dir: &Path, format: LogFormat) -> SinkConfig {
+        SinkConfig {
+            format,
+            dir: dir.to_owned(),
+            rotate_bytes: 0,
+            rotate_secs: 0,
+            retain_files: 0,
+        }
+    }
+
+    fn batch(i: u64) -> StructArray {
+        readings_to_arrow(&[
+            Reading::new(i * SECOND, "sensor0", 20.0 + i as f32),
+            Reading::new(i * SECOND, "sensor1", 30.0),
+        ])
+    }
+
+    fn files(dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn csv_and_jsonl_write_one_line_per_row() {
+        let dir = test_dir("text");
+        let mut csv = RotatingSink::new("smoothed", config(&dir, LogFormat::Csv)).unwrap();
+        let mut jsonl = RotatingSink::new("smoothed", config(&dir, LogFormat::Jsonl)).unwrap();
+        for i in 0..3 {
+            csv.write(&batch(i), i * SECOND).unwrap();
+            jsonl.write(&batch(i), i * SECOND).unwrap();
+        }
+        csv.close().unwrap();
+        jsonl.close().unwrap();
+
+        let files = files(&dir);
+        assert_eq!(files.len(), 2);
+        let csv = fs::read_to_string(&files[0]).unwrap();
+        assert_eq!(csv.lines().next(), Some("timestamp_ns,sensor_id,value"));
+        assert_eq!(csv.lines().count(), 1 + 6);
+        let jsonl = fs::read_to_string(&files[1]).unwrap();
+        assert_eq!(jsonl.lines().count(), 6);
+        assert!(jsonl
+            .lines()
+            .last()
+            .unwrap()
+            .contains(r#""sensor_id":"sensor1""#));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parquet_file_reads_back_as_readings() {
+        let dir = test_dir("parquet");
+        let mut sink = RotatingSink::new("smoothed", config(&dir, LogFormat::Parquet)).unwrap();
+        for i in 0..3 {
+            sink.write(&batch(i), i * SECOND).unwrap();
+        }
+        sink.close().unwrap();
+
+        let file = File::open(&files(&dir)[0]).unwrap();
+        let readings: Vec<Reading> = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap()
+            .flat_map(|batch| arrow_to_readings(&StructArray::from(batch.unwrap())).unwrap())
+            .collect();
+        assert_eq!(readings.len(), 6);
+        assert_eq!(readings[4], Reading::new(2 * SECOND, "sensor0", 22.0));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rotates_by_time_and_size_and_keeps_newest_files() {
+        let dir = test_dir("rotate");
+        let mut by_time = config(&dir, LogFormat::Csv);
+        by_time.rotate_secs = 10;
+        by_time.retain_files = 3;
+        let mut sink = RotatingSink::new("alert", by_time).unwrap();
+        // 每 5 秒写一次，持续 60 秒：每个文件 2 批，共 6 个文件，只保留最新 3 个
+        for i in 0..12 {
+            sink.write(&batch(i), i * 5 * SECOND).unwrap();
+        }
+        sink.close().unwrap();
+        let kept = files(&dir);
+        assert_eq!(kept.len(), 3);
+        assert!(kept[2].to_str().unwrap().ends_with("-0005.csv"));
+
+        let mut by_size = config(&dir, LogFormat::Jsonl);
+        by_size.rotate_bytes = 1;
+        let mut sink = RotatingSink::new("smoothed", by_size).unwrap();
+        for i in 0..4 {
+            sink.write(&batch(i), 0).unwrap();
+        }
+        sink.close().unwrap();
+        assert_eq!(files(&dir).len(), 3 + 4);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
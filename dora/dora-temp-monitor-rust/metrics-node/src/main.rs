@@ -0,0 +1,48 @@
+use dora_node_api::{arrow::array::StructArray, DoraNode, Event};
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use temp_common::alert::arrow_to_alerts;
+use temp_common::config::env_or;
+use temp_common::health::arrow_to_health;
+use temp_common::reading::arrow_to_readings;
+
+mod metrics;
+mod server;
+
+use metrics::TempMetrics;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    temp_common::config::load_file("metrics")?;
+    let (mut _node, mut events) = DoraNode::init_from_env()?;
+
+    // 默认只监听本机，需要远程抓取时改为 0.0.0.0:9898
+    let addr = env_or("METRICS_ADDR", "127.0.0.1:9898".to_owned())?;
+    let metrics = Arc::new(Mutex::new(TempMetrics::new()?));
+    let (local_addr, _server) = server::serve(&addr, metrics.clone())?;
+    println!("📈 指标节点启动 (http://{local_addr}/metrics)");
+
+    while let Some(event) = events.recv() {
+        if let Event::Input { id, metadata, data } = event {
+            let received_ns = temp_common::now_ns();
+            let array = data
+                .as_any()
+                .downcast_ref::<StructArray>()
+                .ok_or("数据不是 StructArray")?;
+
+            let mut metrics = metrics.lock().map_err(|e| e.to_string())?;
+            metrics.observe_message(
+                id.as_str(),
+                temp_common::timestamp_ns(&metadata),
+                received_ns,
+            );
+            match id.as_str() {
+                "reading" => metrics.observe_readings(&arrow_to_readings(array)?),
+                "alert" => metrics.observe_alerts(&arrow_to_alerts(array)?),
+                "health" => metrics.observe_health(&arrow_to_health(array)?),
+                other => eprintln!("Received input `{other}`"),
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use prometheus::{
+    exponential_buckets, Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, Opts,
+    Registry, TextEncoder,
+};
+use std::collections::HashMap;
+use std::error::Error;
+use temp_common::alert::{Alert, AlertState};
+use temp_common::health::HealthEvent;
+use temp_common::reading::Reading;
+
+/// 温度数据流的 Prometheus 指标
+pub struct TempMetrics {
+    registry: Registry,
+    temperature: GaugeVec,
+    last_reading: GaugeVec,
+    alerts: IntCounterVec,
+    health_events: IntCounterVec,
+    health_active: GaugeVec,
+    latency: HistogramVec,
+    interval: HistogramVec,
+    /// 各输入上一条消息的接收时间，用于计算消息间隔
+    last_received: HashMap<String, u64>,
+}
+
+impl TempMetrics {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let registry = Registry::new();
+        let temperature = GaugeVec::new(
+            Opts::new("temp_celsius", "Latest smoothed temperature per sensor"),
+            &["sensor_id"],
+        )?;
+        let last_reading = GaugeVec::new(
+            Opts::new(
+                "temp_last_reading_timestamp_seconds",
+                "Unix time of the latest reading per sensor",
+            ),
+            &["sensor_id"],
+        )?;
+        let alerts = IntCounterVec::new(
+            Opts::new("temp_alerts_total", "Raised alerts by severity and kind"),
+            &["severity", "kind"],
+        )?;
+        let health_events = IntCounterVec::new(
+            Opts::new(
+                "temp_sensor_health_events_total",
+                "Raised data-quality issues by sensor and issue",
+            ),
+            &["sensor_id", "issue"],
+        )?;
+        let health_active = GaugeVec::new(
+            Opts::new(
+                "temp_sensor_health_issue_active",
+                "1 while a data-quality issue is active, 0 after it clears",
+            ),
+            &["sensor_id", "issue"],
+        )?;
+        // 1ms ~ 8s
+        let buckets = exponential_buckets(0.001, 2.0, 14)?;
+        let latency = HistogramVec::new(
+            HistogramOpts::new(
+                "temp_message_latency_seconds",
+                "Delay between a message being sent and received by this node",
+            )
+            .buckets(buckets.clone()),
+            &["input"],
+        )?;
+        let interval = HistogramVec::new(
+            HistogramOpts::new(
+                "temp_message_interval_seconds",
+                "Time between consecutive messages on an input",
+            )
+            .buckets(buckets),
+            &["input"],
+        )?;
+
+        registry.register(Box::new(temperature.clone()))?;
+        registry.register(Box::new(last_reading.clone()))?;
+        registry.register(Box::new(alerts.clone()))?;
+        registry.register(Box::new(health_events.clone()))?;
+        registry.register(Box::new(health_active.clone()))?;
+        registry.register(Box::new(latency.clone()))?;
+        registry.register(Box::new(interval.clone()))?;
+
+        Ok(Self {
+            registry,
+            temperature,
+            last_reading,
+            alerts,
+            health_events,
+            health_active,
+            latency,
+            interval,
+            last_received: HashMap::new(),
+        })
+    }
+
+    /// 记录 `input` 上的一条消息：`sent_ns` 为 dora 元数据中的发送时间，`received_ns` 为接收时间
+    pub fn observe_message(&mut self, input: &str, sent_ns: u64, received_ns: u64) {
+        let secs = |ns: u64| ns as f64 / 1e9;
+        self.latency
+            .with_label_values(&[input])
+            .observe(secs(received_ns.saturating_sub(sent_ns)));
+        if let Some(previous) = self.last_received.insert(input.to_owned(), received_ns) {
+            self.interval
+                .with_label_values(&[input])
+                .observe(secs(received_ns.saturating_sub(previous)));
+        }
+    }
+
+    pub fn observe_readings(&mut self, readings: &[Reading]) {
+        for reading in readings {
+            let labels = [reading.sensor_id.as_str()];
+            self.temperature
+                .with_label_values(&labels)
+                .set(reading.value as f64);
+            self.last_reading
+                .with_label_values(&labels)
+                .set(reading.timestamp_ns as f64 / 1e9);
+        }
+    }
+
+    /// 只统计触发事件，解除事件不计入告警次数
+    pub fn observe_alerts(&mut self, alerts: &[Alert]) {
+        for alert in alerts.iter().filter(|a| a.state == AlertState::Raised) {
+            self.alerts
+                .with_label_values(&[alert.severity.as_str(), alert.kind.as_str()])
+                .inc();
+        }
+    }
+
+    pub fn observe_health(&mut self, events: &[HealthEvent]) {
+        for event in events {
+            let labels = [event.sensor_id.as_str(), event.issue.as_str()];
+            let active = event.state == AlertState::Raised;
+            if active {
+                self.health_events.with_label_values(&labels).inc();
+            }
+            self.health_active
+                .with_label_values(&labels)
+                .set(if active { 1.0 } else { 0.0 });
+        }
+    }
+
+    /// Prometheus 文本格式
+    pub fn render(&self) -> Result<String, Box<dyn Error>> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use temp_common::alert::{AlertKind, Severity};
+    use temp_common::health::HealthIssue;
+
+    fn alert(severity: Severity, state: AlertState) -> Alert {
+        Alert {
+            timestamp_ns: 0,
+            sensor_id: "sensor0".to_owned(),
+            kind: AlertKind::High,
+            severity,
+            state,
+            value: 0.0,
+            baseline: 0.0,
+            deviation: 0.0,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn renders_gauges_counters_and_histograms() {
+        let mut metrics = TempMetrics::new().unwrap();
+        metrics.observe_readings(&[
+            Reading::new(2_000_000_000, "sensor0", 25.5),
+            Reading::new(2_000_000_000, "sensor1", 31.0),
+        ]);
+        metrics.observe_alerts(&[
+            alert(Severity::Warning, AlertState::Raised),
+            alert(Severity::Warning, AlertState::Cleared),
+            alert(Severity::Critical, AlertState::Raised),
+        ]);
+        let stuck = |state| HealthEvent {
+            timestamp_ns: 0,
+            sensor_id: "sensor0".to_owned(),
+            issue: HealthIssue::Stuck,
+            severity: Severity::Warning,
+            state,
+            value: 25.0,
+            message: String::new(),
+        };
+        metrics.observe_health(&[stuck(AlertState::Raised), stuck(AlertState::Cleared)]);
+        metrics.observe_message("reading", 1_000_000_000, 1_003_000_000);
+        metrics.observe_message("reading", 1_100_000_000, 1_103_000_000);
+
+        let text = metrics.render().unwrap();
+        assert!(text.contains(r#"temp_celsius{sensor_id="sensor1"} 31"#));
+        assert!(text.contains(r#"temp_last_reading_timestamp_seconds{sensor_id="sensor0"} 2"#));
+        assert!(text.contains(r#"temp_alerts_total{kind="high",severity="warning"} 1"#));
+        assert!(text.contains(r#"temp_alerts_total{kind="high",severity="critical"} 1"#));
+        assert!(text
+            .contains(r#"temp_sensor_health_events_total{issue="stuck",sensor_id="sensor0"} 1"#));
+        assert!(text
+            .contains(r#"temp_sensor_health_issue_active{issue="stuck",sensor_id="sensor0"} 0"#));
+        assert!(text.contains(r#"temp_message_latency_seconds_count{input="reading"} 2"#));
+        // 第一条消息没有间隔
+        assert!(text.contains(r#"temp_message_interval_seconds_count{input="reading"} 1"#));
+        assert!(
+            text.contains(r#"temp_message_interval_seconds_bucket{input="reading",le="0.128"} 1"#)
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::metrics::TempMetrics;
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// 在后台线程提供 `GET /metrics`，返回实际监听的地址（`addr` 端口为 0 时由系统分配）
+pub fn serve(
+    addr: &str,
+    metrics: Arc<Mutex<TempMetrics>>,
+) -> Result<(SocketAddr, JoinHandle<()>), Box<dyn Error>> {
+    let server = Server::http(addr).map_err(|e| format!("无法监听 {addr}: {e}"))?;
+    let local_addr = server
+        .server_addr()
+        .to_ip()
+        .ok_or("metrics 服务器未监听 TCP 地址")?;
+
+    let handle = thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let response = match (request.method(), request.url()) {
+                (Method::Get, "/metrics") => {
+                    let body = metrics
+                        .lock()
+                        .map_err(|e| e.to_string())
+                        .and_then(|m| m.render().map_err(|e| e.to_string()));
+                    match body {
+                        Ok(body) => Response::from_string(body).with_header(
+                            Header::from_bytes("Content-Type", CONTENT_TYPE)
+                                .expect("static header is valid"),
+                        ),
+                        Err(e) => Response::from_string(e).with_status_code(500),
+                    }
+                }
+                _ => Response::from_string("not found\n").with_status_code(404),
+            };
+            if let Err(e) = request.respond(response) {
+                eprintln!("metrics 响应失败: {e}");
+            }
+        }
+    });
+
+    Ok((local_addr, handle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use temp_common::reading::Reading;
+
+    fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn serves_metrics_over_http() {
+        let metrics = Arc::new(Mutex::new(TempMetrics::new().unwrap()));
+        let (addr, _handle) = serve("127.0.0.1:0", metrics.clone()).unwrap();
+
+        metrics
+            .lock()
+            .unwrap()
+            .observe_readings(&[Reading::new(0, "rack1/cpu0", 42.5)]);
+
+        let response = get(addr, "/metrics");
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("text/plain; version=0.0.4"));
+        assert!(response.contains(r#"temp_celsius{sensor_id="rack1/cpu0"} 42.5"#));
+
+        assert!(get(addr, "/").starts_with("HTTP/1.1 404"));
+    }
+}
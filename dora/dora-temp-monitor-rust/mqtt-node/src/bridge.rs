@@ -0,0 +1,201 @@
+use rumqttc::{Client, Event, LastWill, MqttOptions, Packet, QoS};
+use std::error::Error;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+use temp_common::config::{self, env_or};
+use temp_common::reading::Reading;
+use temp_common::units::Unit;
+
+use crate::payload::parse_reading;
+
+/// MQTT 桥接配置，全部来自 dataflow.yml 中的 `env:`
+#[derive(Debug, Clone)]
+pub struct BridgeConfig {
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub credentials: Option<(String, String)>,
+    pub qos: QoS,
+    /// 平滑温度的主题模板，`{sensor_id}` 替换为传感器 ID
+    pub reading_topic: String,
+    /// 告警的主题模板
+    pub alert_topic: String,
+    /// 平滑温度是否以保留消息发布，新订阅者立即收到最新值
+    pub retain_readings: bool,
+    pub retain_alerts: bool,
+    /// 在线状态主题：连接后发布 `online`，断开时由代理发布遗嘱 `offline`
+    pub status_topic: Option<String>,
+    /// 作为温度数据源订阅的主题过滤器
+    pub subscribe: Option<String>,
+}
+
+fn optional(key: &str) -> Option<String> {
+    config::var(key)
+        .map(|value| value.trim().to_owned())
+        .filter(|value| !value.is_empty())
+}
+
+fn parse_qos(level: u8) -> Result<QoS, Box<dyn Error>> {
+    match level {
+        0 => Ok(QoS::AtMostOnce),
+        1 => Ok(QoS::AtLeastOnce),
+        2 => Ok(QoS::ExactlyOnce),
+        other => Err(format!("MQTT_QOS={other} 无效，只能是 0、1 或 2").into()),
+    }
+}
+
+impl BridgeConfig {
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let credentials = match (optional("MQTT_USERNAME"), optional("MQTT_PASSWORD")) {
+            (Some(user), password) => Some((user, password.unwrap_or_default())),
+            (None, Some(_)) => return Err("设置 MQTT_PASSWORD 时需要同时设置 MQTT_USERNAME".into()),
+            (None, None) => None,
+        };
+        Ok(Self {
+            host: env_or("MQTT_HOST", "127.0.0.1".to_owned())?,
+            port: env_or("MQTT_PORT", 1883)?,
+            client_id: env_or("MQTT_CLIENT_ID", "dora-temp-monitor".to_owned())?,
+            credentials,
+            qos: parse_qos(env_or("MQTT_QOS", 1)?)?,
+            reading_topic: env_or(
+                "MQTT_TOPIC_READING",
+                "dora/temperature/{sensor_id}".to_owned(),
+            )?,
+            alert_topic: env_or(
+                "MQTT_TOPIC_ALERT",
+                "dora/temperature/{sensor_id}/alert".to_owned(),
+            )?,
+            retain_readings: env_or("MQTT_RETAIN", true)?,
+            retain_alerts: env_or("MQTT_RETAIN_ALERTS", false)?,
+            status_topic: optional("MQTT_STATUS_TOPIC"),
+            subscribe: optional("MQTT_SUBSCRIBE"),
+        })
+    }
+}
+
+/// 连接代理并在后台线程驱动 MQTT 事件循环
+///
+/// 断线后 rumqttc 会自动重连，每次连上都重新发布在线状态并重新订阅；
+/// 订阅收到的读数通过返回的通道交给主循环，解析失败的消息只打印不中断
+pub fn connect(config: &BridgeConfig) -> (Client, Receiver<(Reading, Unit)>) {
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options.set_keep_alive(Duration::from_secs(10));
+    if let Some((user, password)) = &config.credentials {
+        options.set_credentials(user, password);
+    }
+    if let Some(topic) = &config.status_topic {
+        options.set_last_will(LastWill::new(topic, "offline", config.qos, true));
+    }
+
+    let (client, mut connection) = Client::new(options, 256);
+    let (tx, rx) = mpsc::channel();
+    let config = config.clone();
+    let subscriber = client.clone();
+    thread::spawn(move || {
+        let mut connected = false;
+        for event in connection.iter() {
+            match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    println!("🔌 已连接 MQTT 代理 {}:{}", config.host, config.port);
+                    connected = true;
+                    on_connect(&subscriber, &config);
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    forward(&config, &publish.topic, &publish.payload, &tx);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    if connected {
+                        eprintln!("MQTT 连接断开: {e}");
+                        connected = false;
+                    }
+                    // 代理不可用时放慢重连节奏
+                    thread::sleep(Duration::from_secs(1));
+                }
+            }
+        }
+    });
+    (client, rx)
+}
+
+fn on_connect(client: &Client, config: &BridgeConfig) {
+    if let Some(topic) = &config.status_topic {
+        if let Err(e) = client.try_publish(topic, config.qos, true, "online") {
+            eprintln!("发布在线状态失败: {e}");
+        }
+    }
+    if let Some(filter) = &config.subscribe {
+        if let Err(e) = client.try_subscribe(filter, config.qos) {
+            eprintln!("订阅 {filter} 失败: {e}");
+        }
+    }
+}
+
+fn forward(config: &BridgeConfig, topic: &str, payload: &[u8], tx: &Sender<(Reading, Unit)>) {
+    let Some(filter) = &config.subscribe else {
+        return;
+    };
+    match parse_reading(filter, topic, payload, temp_common::now_ns()) {
+        Ok(reading) => {
+            // 主循环退出后发送失败，忽略即可
+            let _ = tx.send(reading);
+        }
+        Err(e) => eprintln!("忽略主题 {topic} 的消息: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload::{reading_json, topic_for};
+    use std::sync::mpsc::RecvTimeoutError;
+    use std::time::Instant;
+
+    /// 需要 127.0.0.1:1883 上的 mosquitto：`cargo test -p mqtt_node -- --ignored`
+    #[test]
+    #[ignore]
+    fn publishes_and_subscribes_through_local_broker() {
+        // 主题和客户端 ID 带上进程号，不与代理上的其他客户端冲突
+        let prefix = format!("dora-temp-test-{}", std::process::id());
+        let config = BridgeConfig {
+            host: "127.0.0.1".to_owned(),
+            port: 1883,
+            client_id: prefix.clone(),
+            credentials: None,
+            qos: QoS::AtLeastOnce,
+            reading_topic: format!("{prefix}/{{sensor_id}}/temperature"),
+            alert_topic: format!("{prefix}/{{sensor_id}}/alert"),
+            retain_readings: false,
+            retain_alerts: false,
+            status_topic: None,
+            subscribe: Some(format!("{prefix}/+/temperature")),
+        };
+        let (client, subscribed) = connect(&config);
+        let reading = Reading::new(1_700_000_000_000_000_000, "room1", 23.5);
+
+        // 连上代理并订阅之前发布的消息会丢失，重复发布直到收到
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let received = loop {
+            client
+                .try_publish(
+                    topic_for(&config.reading_topic, &reading.sensor_id),
+                    config.qos,
+                    false,
+                    reading_json(&reading),
+                )
+                .unwrap();
+            match subscribed.recv_timeout(Duration::from_millis(200)) {
+                Ok(received) => break received,
+                Err(RecvTimeoutError::Timeout) => {
+                    assert!(
+                        Instant::now() < deadline,
+                        "没有收到消息，本地 mosquitto 是否在运行？"
+                    )
+                }
+                Err(e) => panic!("{e}"),
+            }
+        };
+        assert_eq!(received, (reading, Unit::Celsius));
+    }
+}
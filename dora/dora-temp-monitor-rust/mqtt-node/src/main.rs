@@ -0,0 +1,131 @@
+use dora_node_api::{
+    arrow::array::StructArray, dora_core::config::DataId, DoraNode, Event, Parameter,
+};
+use rumqttc::Client;
+use std::error::Error;
+use temp_common::alert::arrow_to_alerts;
+use temp_common::reading::{arrow_to_readings, readings_to_arrow, Reading};
+use temp_common::units::{set_source_units, Unit, UNIT_PARAM};
+
+mod bridge;
+mod payload;
+
+use bridge::BridgeConfig;
+use payload::{alert_json, reading_json, topic_for};
+
+/// 非阻塞发布：代理不可用、请求队列已满时丢弃消息，不拖慢数据流
+struct Publisher {
+    client: Client,
+    config: BridgeConfig,
+    /// 当前这次中断期间丢弃的消息数
+    dropped: u64,
+}
+
+impl Publisher {
+    fn publish(&mut self, topic: String, retain: bool, payload: String) {
+        match self
+            .client
+            .try_publish(topic, self.config.qos, retain, payload)
+        {
+            Ok(()) if self.dropped > 0 => {
+                println!("📤 MQTT 发布恢复，期间丢弃 {} 条消息", self.dropped);
+                self.dropped = 0;
+            }
+            Ok(()) => {}
+            Err(e) => {
+                if self.dropped == 0 {
+                    eprintln!("MQTT 发布失败，暂时丢弃消息: {e}");
+                }
+                self.dropped += 1;
+            }
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    temp_common::config::load_file("mqtt")?;
+    let (mut node, mut events) = DoraNode::init_from_env()?;
+    let output_temp_raw = DataId::from(temp_common::TEMP_RAW.to_owned());
+
+    let config = BridgeConfig::from_env()?;
+    let (client, subscribed) = bridge::connect(&config);
+    println!(
+        "📡 MQTT 桥接节点启动 ({}:{} 发布到 {}{})",
+        config.host,
+        config.port,
+        config.reading_topic,
+        match &config.subscribe {
+            Some(filter) => format!("，订阅 {filter}"),
+            None => String::new(),
+        }
+    );
+    let mut publisher = Publisher {
+        client,
+        config: config.clone(),
+        dropped: 0,
+    };
+    // 订阅数据源中出现过的非摄氏度传感器
+    let mut source_units: Vec<(String, Unit)> = Vec::new();
+
+    while let Some(event) = events.recv() {
+        if let Event::Input { id, metadata, data } = event {
+            match id.as_str() {
+                "smoothed" => {
+                    let array = data
+                        .as_any()
+                        .downcast_ref::<StructArray>()
+                        .ok_or("平滑数据不是 StructArray")?;
+                    for reading in arrow_to_readings(array)? {
+                        publisher.publish(
+                            topic_for(&config.reading_topic, &reading.sensor_id),
+                            config.retain_readings,
+                            reading_json(&reading),
+                        );
+                    }
+                }
+                "alert" => {
+                    let array = data
+                        .as_any()
+                        .downcast_ref::<StructArray>()
+                        .ok_or("告警数据不是 StructArray")?;
+                    for alert in arrow_to_alerts(array)? {
+                        publisher.publish(
+                            topic_for(&config.alert_topic, &alert.sensor_id),
+                            config.retain_alerts,
+                            alert_json(&alert),
+                        );
+                    }
+                }
+                // 把两次 tick 之间订阅到的读数合成一批作为 `temp_raw` 输出
+                "tick" => {
+                    let mut readings: Vec<Reading> = Vec::new();
+                    for (reading, unit) in subscribed.try_iter() {
+                        if unit != Unit::Celsius
+                            && !source_units.iter().any(|(id, _)| *id == reading.sensor_id)
+                        {
+                            source_units.push((reading.sensor_id.clone(), unit));
+                        }
+                        readings.push(reading);
+                    }
+                    if readings.is_empty() {
+                        continue;
+                    }
+                    let mut parameters = metadata.parameters;
+                    parameters.insert(
+                        UNIT_PARAM.to_owned(),
+                        Parameter::String(Unit::Celsius.as_str().to_owned()),
+                    );
+                    set_source_units(&mut parameters, &source_units);
+                    node.send_output(
+                        output_temp_raw.clone(),
+                        parameters,
+                        readings_to_arrow(&readings),
+                    )?;
+                }
+                other => eprintln!("Received input `{other}`"),
+            }
+        }
+    }
+
+    Ok(())
+}
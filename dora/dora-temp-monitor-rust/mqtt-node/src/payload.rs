@@ -0,0 +1,146 @@
+use serde_json::{json, Value};
+use temp_common::alert::Alert;
+use temp_common::reading::Reading;
+use temp_common::units::Unit;
+
+/// 把主题模板中的 `{sensor_id}` 替换为传感器 ID；ID 中的 MQTT 通配符替换为 `_`
+pub fn topic_for(template: &str, sensor_id: &str) -> String {
+    let sensor_id = sensor_id.replace(['+', '#'], "_");
+    template.replace("{sensor_id}", &sensor_id)
+}
+
+pub fn reading_json(reading: &Reading) -> String {
+    json!({
+        "timestamp_ns": reading.timestamp_ns,
+        "sensor_id": reading.sensor_id,
+        "value": reading.value,
+        "unit": Unit::Celsius.as_str(),
+    })
+    .to_string()
+}
+
+pub fn alert_json(alert: &Alert) -> String {
+    json!({
+        "timestamp_ns": alert.timestamp_ns,
+        "sensor_id": alert.sensor_id,
+        "kind": alert.kind.as_str(),
+        "severity": alert.severity.as_str(),
+        "state": alert.state.as_str(),
+        "value": alert.value,
+        "baseline": alert.baseline,
+        "deviation": alert.deviation,
+        "message": alert.message,
+    })
+    .to_string()
+}
+
+/// 订阅的主题过滤器中第一个 `+` 对应的主题层级；过滤器没有 `+` 时返回整个主题
+fn sensor_id_from_topic(filter: &str, topic: &str) -> String {
+    filter
+        .split('/')
+        .zip(topic.split('/'))
+        .find(|(pattern, _)| *pattern == "+")
+        .map(|(_, level)| level.to_owned())
+        .unwrap_or_else(|| topic.to_owned())
+}
+
+/// 解析订阅收到的一条消息，返回换算为摄氏度的读数及其原始单位
+///
+/// 负载可以是纯数字（摄氏度），也可以是 JSON 对象：
+/// `{"value": 72.5, "unit": "fahrenheit", "sensor_id": "room1", "timestamp_ns": 1700000000000000000}`，
+/// 其中只有 `value` 必填；缺省的传感器 ID 取自主题，缺省的时间戳为接收时刻
+pub fn parse_reading(
+    filter: &str,
+    topic: &str,
+    payload: &[u8],
+    received_ns: u64,
+) -> Result<(Reading, Unit), String> {
+    let text = std::str::from_utf8(payload).map_err(|e| format!("负载不是 UTF-8: {e}"))?;
+    let mut sensor_id = sensor_id_from_topic(filter, topic);
+    let mut timestamp_ns = received_ns;
+    let mut unit = Unit::Celsius;
+
+    let value = match serde_json::from_str::<Value>(text.trim()) {
+        Ok(Value::Number(number)) => number.as_f64(),
+        Ok(Value::Object(object)) => {
+            if let Some(id) = object.get("sensor_id") {
+                sensor_id = id.as_str().ok_or("`sensor_id` 应为字符串")?.to_owned();
+            }
+            if let Some(ts) = object.get("timestamp_ns") {
+                timestamp_ns = ts.as_u64().ok_or("`timestamp_ns` 应为非负整数")?;
+            }
+            if let Some(u) = object.get("unit") {
+                unit = u.as_str().ok_or("`unit` 应为字符串")?.parse()?;
+            }
+            object.get("value").and_then(Value::as_f64)
+        }
+        _ => None,
+    }
+    .ok_or_else(|| format!("负载中没有数值温度: `{text}`"))?;
+
+    let celsius = unit
+        .to_celsius(value)
+        .ok_or("原始计数无法换算为温度，请先在发布端标定")?;
+    Ok((Reading::new(timestamp_ns, &sensor_id, celsius as f32), unit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use temp_common::alert::{AlertKind, AlertState, Severity};
+
+    #[test]
+    fn readings_and_alerts_serialise_to_json() {
+        assert_eq!(topic_for("bms/{sensor_id}/temp", "a+b"), "bms/a_b/temp");
+
+        let reading: Value =
+            serde_json::from_str(&reading_json(&Reading::new(5, "sensor0", 21.5))).unwrap();
+        assert_eq!(reading["sensor_id"], "sensor0");
+        assert_eq!(reading["value"], 21.5);
+        assert_eq!(reading["unit"], "celsius");
+
+        let alert = Alert {
+            timestamp_ns: 7,
+            sensor_id: "sensor1".into(),
+            kind: AlertKind::High,
+            severity: Severity::Critical,
+            state: AlertState::Raised,
+            value: 41.0,
+            baseline: 40.0,
+            deviation: 1.0,
+            message: "温度过高".into(),
+        };
+        let alert: Value = serde_json::from_str(&alert_json(&alert)).unwrap();
+        assert_eq!(alert["kind"], "high");
+        assert_eq!(alert["severity"], "critical");
+        assert_eq!(alert["state"], "raised");
+        assert_eq!(alert["message"], "温度过高");
+    }
+
+    #[test]
+    fn parses_plain_and_json_payloads() {
+        let filter = "sensors/+/temperature";
+        let (reading, unit) =
+            parse_reading(filter, "sensors/room1/temperature", b"23.5", 9).unwrap();
+        assert_eq!(reading, Reading::new(9, "room1", 23.5));
+        assert_eq!(unit, Unit::Celsius);
+
+        let payload =
+            br#"{"value": 212, "unit": "fahrenheit", "sensor_id": "boiler", "timestamp_ns": 42}"#;
+        let (reading, unit) = parse_reading(filter, "sensors/x/temperature", payload, 9).unwrap();
+        assert_eq!(reading, Reading::new(42, "boiler", 100.0));
+        assert_eq!(unit, Unit::Fahrenheit);
+
+        let (reading, _) = parse_reading("lab/temp", "lab/temp", b"{\"value\": 20}", 1).unwrap();
+        assert_eq!(reading.sensor_id, "lab/temp");
+
+        assert!(parse_reading(filter, "sensors/a/temperature", b"hot", 0).is_err());
+        assert!(parse_reading(
+            filter,
+            "sensors/a/temperature",
+            b"{\"value\": 512, \"unit\": \"raw\"}",
+            0
+        )
+        .is_err());
+    }
+}
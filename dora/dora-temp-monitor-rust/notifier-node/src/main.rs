@@ -0,0 +1,128 @@
+use dora_node_api::{arrow::array::StructArray, DoraNode, Event};
+use std::collections::HashSet;
+use std::error::Error;
+use std::sync::mpsc::Sender;
+use temp_common::alert::{arrow_to_alerts, Alert, AlertKind, AlertState, Severity};
+use temp_common::config::env_or;
+
+mod policy;
+mod sink;
+
+use policy::{Dispatcher, Notification, Policy};
+use sink::Sink;
+
+/// 决定哪些告警需要通知：触发事件按最低级别过滤，
+/// 解除事件只在对应的触发事件通知过时发送
+struct AlertFilter {
+    min_severity: Severity,
+    notify_cleared: bool,
+    /// 已经通知过、尚未解除的告警
+    active: HashSet<(String, AlertKind)>,
+}
+
+impl AlertFilter {
+    fn accepts(&mut self, alert: &Alert) -> bool {
+        let key = (alert.sensor_id.clone(), alert.kind);
+        match alert.state {
+            AlertState::Raised if alert.severity >= self.min_severity => {
+                self.active.insert(key);
+                true
+            }
+            AlertState::Raised => false,
+            AlertState::Cleared => self.active.remove(&key) && self.notify_cleared,
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    temp_common::config::load_file("notifier")?;
+    let (mut _node, mut events) = DoraNode::init_from_env()?;
+
+    let mut filter = AlertFilter {
+        min_severity: env_or("NOTIFY_MIN_SEVERITY", Severity::Warning)?,
+        notify_cleared: env_or("NOTIFY_CLEARED", true)?,
+        active: HashSet::new(),
+    };
+    let mut channels: Vec<(Dispatcher, Sender<Notification>)> = Vec::new();
+    let mut names = Vec::new();
+    for sink in Sink::from_env()? {
+        let policy = Policy::from_env(sink.name())?;
+        names.push(sink.name());
+        channels.push((Dispatcher::new(policy), sink.spawn()));
+    }
+    println!("📣 通知节点启动 (通道: {})", names.join(", "));
+
+    while let Some(event) = events.recv() {
+        if let Event::Input {
+            id,
+            metadata: _,
+            data,
+        } = event
+        {
+            let now_ns = temp_common::now_ns();
+            match id.as_str() {
+                "alert" => {
+                    let array = data
+                        .as_any()
+                        .downcast_ref::<StructArray>()
+                        .ok_or("告警数据不是 StructArray")?;
+                    for alert in arrow_to_alerts(array)? {
+                        if filter.accepts(&alert) {
+                            for (dispatcher, _) in &mut channels {
+                                dispatcher.push(&alert, now_ns);
+                            }
+                        }
+                    }
+                }
+                // 合并窗口和限流按时钟推进
+                "tick" => {}
+                other => {
+                    eprintln!("Received input `{other}`");
+                    continue;
+                }
+            }
+            for (dispatcher, sender) in &mut channels {
+                if let Some(notification) = dispatcher.poll(now_ns) {
+                    sender.send(notification)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(kind: AlertKind, severity: Severity, state: AlertState) -> Alert {
+        Alert {
+            timestamp_ns: 0,
+            sensor_id: "sensor0".to_owned(),
+            kind,
+            severity,
+            state,
+            value: 0.0,
+            baseline: 0.0,
+            deviation: 0.0,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn cleared_events_follow_notified_alerts_only() {
+        let mut filter = AlertFilter {
+            min_severity: Severity::Critical,
+            notify_cleared: true,
+            active: HashSet::new(),
+        };
+        use AlertKind::{High, Low};
+        use AlertState::{Cleared, Raised};
+        assert!(!filter.accepts(&alert(High, Severity::Warning, Raised)));
+        assert!(!filter.accepts(&alert(High, Severity::Info, Cleared)));
+        assert!(filter.accepts(&alert(Low, Severity::Critical, Raised)));
+        assert!(filter.accepts(&alert(Low, Severity::Info, Cleared)));
+        assert!(!filter.accepts(&alert(Low, Severity::Info, Cleared)));
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use temp_common::alert::{Alert, AlertKind, AlertState, Severity};
+use temp_common::config::{self, parse_duration};
+
+/// 一个摘要最多列出的告警数，超出部分只计数
+const MAX_PENDING: usize = 100;
+
+fn duration_ns(text: &str) -> Result<u64, Box<dyn Error>> {
+    Ok(parse_duration(text)?.as_nanos() as u64)
+}
+
+/// 单个通知通道的限流、去重和合并策略
+#[derive(Debug, Clone, PartialEq)]
+pub struct Policy {
+    /// 每个周期最多发送的通知数：`(次数, 周期)`，`None` 表示不限流
+    pub rate_limit: Option<(usize, u64)>,
+    /// 相同传感器、类型、级别和状态的告警在该时长内只通知一次，0 表示不去重
+    pub dedup_ns: u64,
+    /// 收到第一条告警后等待该时长，把期间的告警合并为一条摘要，0 表示立即发送
+    pub digest_ns: u64,
+}
+
+impl Policy {
+    /// 读取 `NOTIFY_<通道>_RATE_LIMIT` / `_DEDUP` / `_DIGEST`，未设置时使用
+    /// 所有通道共用的 `NOTIFY_RATE_LIMIT` / `NOTIFY_DEDUP` / `NOTIFY_DIGEST`
+    pub fn from_env(sink: &str) -> Result<Self, Box<dyn Error>> {
+        let read = |name: &str, default: &str| -> String {
+            let prefix = sink.to_ascii_uppercase();
+            config::var(&format!("NOTIFY_{prefix}_{name}"))
+                .or_else(|| config::var(&format!("NOTIFY_{name}")))
+                .unwrap_or_else(|| default.to_owned())
+        };
+        Ok(Self {
+            rate_limit: parse_rate_limit(&read("RATE_LIMIT", "10/10m"))?,
+            dedup_ns: duration_ns(&read("DEDUP", "5m"))?,
+            digest_ns: duration_ns(&read("DIGEST", "10s"))?,
+        })
+    }
+}
+
+/// 解析 `10/1h` 形式的限流配置，`off` 或 `0` 表示不限流
+pub fn parse_rate_limit(text: &str) -> Result<Option<(usize, u64)>, Box<dyn Error>> {
+    let text = text.trim();
+    if text == "off" || text == "0" {
+        return Ok(None);
+    }
+    let (count, period) = text
+        .split_once('/')
+        .ok_or_else(|| format!("限流 `{text}` 应写成 `次数/周期`，例如 `10/1h`"))?;
+    let count: usize = count
+        .trim()
+        .parse()
+        .map_err(|e| format!("限流 `{text}` 的次数无效: {e}"))?;
+    let period = duration_ns(period)?;
+    if count == 0 || period == 0 {
+        return Err(format!("限流 `{text}` 的次数和周期必须大于 0").into());
+    }
+    Ok(Some((count, period)))
+}
+
+/// 一次要发出的通知：一条告警，或合并后的摘要
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub alerts: Vec<Alert>,
+    /// 因重复或超出摘要容量而未列出的告警数
+    pub suppressed: u64,
+}
+
+impl Notification {
+    /// 本次通知中最高的告警级别
+    pub fn severity(&self) -> Severity {
+        self.alerts
+            .iter()
+            .map(|a| a.severity)
+            .max()
+            .unwrap_or(Severity::Info)
+    }
+}
+
+type DedupKey = (String, AlertKind, Severity, AlertState);
+
+/// 按策略为单个通道决定何时发送什么
+///
+/// 告警先经过去重，再进入待发送队列；合并窗口结束且未超出限流时整体发出。
+/// 被限流时告警继续累积，在下一次允许发送时作为一条更大的摘要发出，不会丢失
+pub struct Dispatcher {
+    policy: Policy,
+    pending: Vec<Alert>,
+    pending_since: Option<u64>,
+    suppressed: u64,
+    /// 限流周期内已发送通知的时间
+    sent: VecDeque<u64>,
+    /// 各去重键最近一次被接受的时间
+    recent: HashMap<DedupKey, u64>,
+}
+
+impl Dispatcher {
+    pub fn new(policy: Policy) -> Self {
+        Self {
+            policy,
+            pending: Vec::new(),
+            pending_since: None,
+            suppressed: 0,
+            sent: VecDeque::new(),
+            recent: HashMap::new(),
+        }
+    }
+
+    pub fn push(&mut self, alert: &Alert, now_ns: u64) {
+        if self.policy.dedup_ns > 0 {
+            let key = (
+                alert.sensor_id.clone(),
+                alert.kind,
+                alert.severity,
+                alert.state,
+            );
+            match self.recent.get(&key) {
+                Some(&last) if now_ns.saturating_sub(last) < self.policy.dedup_ns => {
+                    self.suppressed += 1;
+                    return;
+                }
+                _ => {
+                    self.recent.insert(key, now_ns);
+                }
+            }
+        }
+        if self.pending.len() >= MAX_PENDING {
+            self.suppressed += 1;
+            return;
+        }
+        self.pending.push(alert.clone());
+        self.pending_since.get_or_insert(now_ns);
+    }
+
+    /// 合并窗口已结束且限流允许时取出待发送的通知
+    pub fn poll(&mut self, now_ns: u64) -> Option<Notification> {
+        let dedup_ns = self.policy.dedup_ns;
+        self.recent
+            .retain(|_, last| now_ns.saturating_sub(*last) < dedup_ns);
+
+        let since = self.pending_since?;
+        if now_ns.saturating_sub(since) < self.policy.digest_ns {
+            return None;
+        }
+        if let Some((count, period)) = self.policy.rate_limit {
+            while self
+                .sent
+                .front()
+                .is_some_and(|&t| now_ns.saturating_sub(t) >= period)
+            {
+                self.sent.pop_front();
+            }
+            if self.sent.len() >= count {
+                return None;
+            }
+            self.sent.push_back(now_ns);
+        }
+
+        self.pending_since = None;
+        Some(Notification {
+            alerts: std::mem::take(&mut self.pending),
+            suppressed: std::mem::take(&mut self.suppressed),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: u64 = 1_000_000_000;
+
+    fn alert(sensor_id: &str, severity: Severity) -> Alert {
+        Alert {
+            timestamp_ns: 0,
+            sensor_id: sensor_id.to_owned(),
+            kind: AlertKind::High,
+            severity,
+            state: AlertState::Raised,
+            value: 41.0,
+            baseline: 40.0,
+            deviation: 1.0,
+            message: "温度过高".to_owned(),
+        }
+    }
+
+    #[test]
+    fn bursts_are_deduplicated_and_merged_into_one_digest() {
+        let mut dispatcher = Dispatcher::new(Policy {
+            rate_limit: None,
+            dedup_ns: 60 * SEC,
+            digest_ns: 10 * SEC,
+        });
+        for i in 0..5 {
+            dispatcher.push(&alert("a", Severity::Warning), i * SEC);
+        }
+        dispatcher.push(&alert("a", Severity::Critical), 5 * SEC);
+        dispatcher.push(&alert("b", Severity::Warning), 6 * SEC);
+
+        assert_eq!(dispatcher.poll(9 * SEC), None);
+        let digest = dispatcher.poll(10 * SEC).unwrap();
+        assert_eq!(digest.alerts.len(), 3);
+        assert_eq!(digest.suppressed, 4);
+        assert_eq!(digest.severity(), Severity::Critical);
+        assert_eq!(dispatcher.poll(20 * SEC), None);
+
+        // 去重窗口过后同样的告警再次通知
+        dispatcher.push(&alert("a", Severity::Warning), 61 * SEC);
+        assert_eq!(dispatcher.poll(71 * SEC).unwrap().alerts.len(), 1);
+    }
+
+    #[test]
+    fn rate_limited_alerts_wait_for_next_slot() {
+        let mut dispatcher = Dispatcher::new(Policy {
+            rate_limit: Some((2, 60 * SEC)),
+            dedup_ns: 0,
+            digest_ns: 0,
+        });
+        let mut sent = Vec::new();
+        for i in 0..10 {
+            dispatcher.push(&alert("a", Severity::Warning), i * SEC);
+            sent.extend(dispatcher.poll(i * SEC));
+        }
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].alerts.len(), 1);
+
+        // 周期结束后积压的 8 条合并为一条摘要
+        let backlog = dispatcher.poll(60 * SEC).unwrap();
+        assert_eq!(backlog.alerts.len(), 8);
+    }
+
+    #[test]
+    fn parses_rate_limits() {
+        assert_eq!(parse_rate_limit("10/1h").unwrap(), Some((10, 3600 * SEC)));
+        assert_eq!(parse_rate_limit("off").unwrap(), None);
+        assert!(parse_rate_limit("10").is_err());
+        assert!(parse_rate_limit("0/1m").is_err());
+    }
+}
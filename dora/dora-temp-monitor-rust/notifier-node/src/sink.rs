@@ -0,0 +1,273 @@
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Value};
+use std::error::Error;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+use temp_common::alert::Alert;
+use temp_common::config;
+use temp_common::format_time;
+
+use crate::policy::Notification;
+
+/// 通知通道
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sink {
+    /// 以 JSON POST 到 HTTP webhook，`text` 字段兼容 Slack / Mattermost 等的 incoming webhook
+    Webhook { url: String },
+    /// 用 `sh -c` 执行本地命令，摘要放在环境变量中，JSON 写入标准输入
+    Command { command: String },
+    /// 追加到事件记录文件
+    File { path: PathBuf },
+}
+
+impl Sink {
+    /// 通道名，用于日志和 `NOTIFY_<通道>_*` 策略变量
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sink::Webhook { .. } => "webhook",
+            Sink::Command { .. } => "command",
+            Sink::File { .. } => "file",
+        }
+    }
+
+    /// 根据 `NOTIFY_WEBHOOK_URL`、`NOTIFY_COMMAND`、`NOTIFY_FILE` 创建已配置的通道
+    pub fn from_env() -> Result<Vec<Sink>, Box<dyn Error>> {
+        let var = |key: &str| {
+            config::var(key)
+                .map(|value| value.trim().to_owned())
+                .filter(|value| !value.is_empty())
+        };
+        let mut sinks = Vec::new();
+        if let Some(url) = var("NOTIFY_WEBHOOK_URL") {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(format!("NOTIFY_WEBHOOK_URL={url} 不是 http(s) 地址").into());
+            }
+            sinks.push(Sink::Webhook { url });
+        }
+        if let Some(command) = var("NOTIFY_COMMAND") {
+            sinks.push(Sink::Command { command });
+        }
+        if let Some(path) = var("NOTIFY_FILE") {
+            sinks.push(Sink::File { path: path.into() });
+        }
+        if sinks.is_empty() {
+            return Err(
+                "至少需要配置 NOTIFY_WEBHOOK_URL、NOTIFY_COMMAND 或 NOTIFY_FILE 中的一个".into(),
+            );
+        }
+        Ok(sinks)
+    }
+
+    pub fn deliver(&self, notification: &Notification) -> Result<(), Box<dyn Error>> {
+        match self {
+            Sink::Webhook { url } => {
+                ureq::post(url)
+                    .timeout(Duration::from_secs(10))
+                    .set("Content-Type", "application/json")
+                    .send_string(&payload(notification).to_string())?;
+            }
+            Sink::Command { command } => {
+                let mut child = Command::new("sh")
+                    .arg("-c")
+                    .arg(command)
+                    .env("ALERT_SUMMARY", text(notification))
+                    .env("ALERT_COUNT", notification.alerts.len().to_string())
+                    .env("ALERT_SEVERITY", notification.severity().as_str())
+                    .stdin(Stdio::piped())
+                    .spawn()?;
+                if let Some(mut stdin) = child.stdin.take() {
+                    // 命令不读取标准输入时写入会失败，不影响执行结果
+                    let _ = stdin.write_all(payload(notification).to_string().as_bytes());
+                }
+                let status = child.wait()?;
+                if !status.success() {
+                    return Err(format!("命令退出状态 {status}").into());
+                }
+            }
+            Sink::File { path } => {
+                if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(dir)?;
+                }
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+                writeln!(file, "[{now}] {}", text(notification))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 在独立线程中逐条投递，慢速 webhook 或命令不阻塞数据流
+    pub fn spawn(self) -> Sender<Notification> {
+        let (tx, rx) = mpsc::channel::<Notification>();
+        thread::spawn(move || {
+            for notification in rx {
+                match self.deliver(&notification) {
+                    Ok(()) => println!(
+                        "📨 已通过 {} 发送 {} 条告警",
+                        self.name(),
+                        notification.alerts.len()
+                    ),
+                    Err(e) => eprintln!("通过 {} 发送告警失败: {e}", self.name()),
+                }
+            }
+        });
+        tx
+    }
+}
+
+fn alert_line(alert: &Alert) -> String {
+    format!(
+        "{} [{}] {}/{}/{} {}",
+        format_time(alert.timestamp_ns),
+        alert.sensor_id,
+        alert.kind.as_str(),
+        alert.severity.as_str(),
+        alert.state.as_str(),
+        alert.message
+    )
+}
+
+/// 通知正文：单条告警为一行，摘要为标题加每条告警一行
+pub fn text(notification: &Notification) -> String {
+    let suppressed = match notification.suppressed {
+        0 => String::new(),
+        n => format!("，另有 {n} 条重复告警已忽略"),
+    };
+    match notification.alerts.as_slice() {
+        [alert] if notification.suppressed == 0 => alert_line(alert),
+        alerts => {
+            let mut text = format!(
+                "温度告警摘要：{} 条，最高级别 {}{suppressed}",
+                alerts.len(),
+                notification.severity().as_str()
+            );
+            for alert in alerts {
+                text.push_str("\n  - ");
+                text.push_str(&alert_line(alert));
+            }
+            text
+        }
+    }
+}
+
+fn payload(notification: &Notification) -> Value {
+    let alerts: Vec<Value> = notification
+        .alerts
+        .iter()
+        .map(|alert| {
+            json!({
+                "timestamp_ns": alert.timestamp_ns,
+                "sensor_id": alert.sensor_id,
+                "kind": alert.kind.as_str(),
+                "severity": alert.severity.as_str(),
+                "state": alert.state.as_str(),
+                "value": alert.value,
+                "baseline": alert.baseline,
+                "deviation": alert.deviation,
+                "message": alert.message,
+            })
+        })
+        .collect();
+    json!({
+        "text": text(notification),
+        "severity": notification.severity().as_str(),
+        "count": notification.alerts.len(),
+        "suppressed": notification.suppressed,
+        "alerts": alerts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use temp_common::alert::{AlertKind, AlertState, Severity};
+
+    fn notification(count: usize) -> Notification {
+        let alerts = (0..count)
+            .map(|i| Alert {
+                timestamp_ns: 0,
+                sensor_id: format!("sensor{i}"),
+                kind: AlertKind::High,
+                severity: if i == 0 {
+                    Severity::Critical
+                } else {
+                    Severity::Warning
+                },
+                state: AlertState::Raised,
+                value: 41.0,
+                baseline: 40.0,
+                deviation: 1.0,
+                message: "温度过高".to_owned(),
+            })
+            .collect();
+        Notification {
+            alerts,
+            suppressed: 0,
+        }
+    }
+
+    #[test]
+    fn file_and_command_sinks_receive_notifications() {
+        let dir = std::env::temp_dir().join(format!("notifier-{}", std::process::id()));
+        let incidents = dir.join("incidents.log");
+        Sink::File {
+            path: incidents.clone(),
+        }
+        .deliver(&notification(1))
+        .unwrap();
+        Sink::File {
+            path: incidents.clone(),
+        }
+        .deliver(&notification(3))
+        .unwrap();
+        let log = std::fs::read_to_string(&incidents).unwrap();
+        assert_eq!(log.lines().count(), 1 + 4);
+        assert!(log.contains("温度告警摘要：3 条，最高级别 critical"));
+
+        // 命令能拿到环境变量中的摘要和标准输入中的 JSON
+        let out = dir.join("command.out");
+        let command = format!(
+            "echo \"$ALERT_SEVERITY $ALERT_COUNT\" > {0}; cat >> {0}",
+            out.display()
+        );
+        Sink::Command { command }.deliver(&notification(2)).unwrap();
+        let written = std::fs::read_to_string(&out).unwrap();
+        let (first, json) = written.split_once('\n').unwrap();
+        assert_eq!(first, "critical 2");
+        let json: Value = serde_json::from_str(json).unwrap();
+        assert_eq!(json["alerts"][1]["sensor_id"], "sensor1");
+
+        assert!(Sink::Command {
+            command: "exit 3".into()
+        }
+        .deliver(&notification(1))
+        .is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn webhook_posts_json_payload() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", server.server_addr());
+        let receiver = thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            let path = request.url().to_owned();
+            request.respond(tiny_http::Response::empty(204)).unwrap();
+            (path, body)
+        });
+
+        Sink::Webhook { url }.deliver(&notification(1)).unwrap();
+        let (path, body) = receiver.join().unwrap();
+        assert_eq!(path, "/hook");
+        let json: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["count"], 1);
+        assert!(json["text"].as_str().unwrap().contains("[sensor0]"));
+    }
+}
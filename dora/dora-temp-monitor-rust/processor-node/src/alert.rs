@@ -0,0 +1,601 @@
+use std::error::Error;
+use temp_common::alert::{Alert, AlertKind, AlertState, Severity};
+use temp_common::config::{self, env_or};
+use temp_common::forecast::Forecast;
+
+/// 单个指标的分级阈值，未设置的级别不告警
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    pub warning: Option<f32>,
+    pub critical: Option<f32>,
+    /// 回差：触发后指标需回落到 `阈值 - hysteresis` 以下才解除
+    pub hysteresis: f32,
+}
+
+impl Limits {
+    /// 按当前已激活级别计算目标级别，已激活的级别享受回差
+    fn level(&self, metric: f32, active: Option<Severity>) -> Option<Severity> {
+        let holds = |limit: Option<f32>, severity: Severity| {
+            limit.is_some_and(|limit| {
+                metric >= limit || (active >= Some(severity) && metric > limit - self.hysteresis)
+            })
+        };
+        if holds(self.critical, Severity::Critical) {
+            Some(Severity::Critical)
+        } else if holds(self.warning, Severity::Warning) {
+            Some(Severity::Warning)
+        } else {
+            None
+        }
+    }
+
+    fn threshold(&self, severity: Severity) -> f32 {
+        match severity {
+            Severity::Critical => self.critical,
+            _ => self.warning,
+        }
+        .unwrap_or(f32::NAN)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertConfig {
+    pub high: Limits,
+    /// 下限阈值按温度填写，例如警告 10°C、严重 5°C
+    pub low: Limits,
+    /// 变化率阈值，单位 °C/s
+    pub rate: Limits,
+    pub deviation: Limits,
+    /// 条件需持续多少秒才触发
+    pub min_duration: f64,
+    /// 同级或更低级别的告警再次触发前的冷却时间（秒）
+    pub cooldown: f64,
+}
+
+/// 读取可选阈值：`off`/`none`/空字符串表示关闭
+fn env_limit(key: &str, default: Option<f32>) -> Result<Option<f32>, Box<dyn Error>> {
+    match config::var(key) {
+        Some(value) if matches!(value.trim(), "" | "off" | "none") => Ok(None),
+        Some(_) => Ok(Some(env_or(key, 0.0)?)),
+        None => Ok(default),
+    }
+}
+
+/// 未设置环境变量时使用的告警配置
+impl Default for AlertConfig {
+    fn default() -> Self {
+        let limits = |warning, critical, hysteresis| Limits {
+            warning,
+            critical,
+            hysteresis,
+        };
+        Self {
+            high: limits(Some(35.0), Some(40.0), 0.5),
+            low: limits(Some(10.0), Some(5.0), 0.5),
+            rate: limits(Some(1.0), Some(3.0), 0.1),
+            deviation: limits(Some(3.0), None, 0.5),
+            min_duration: 0.3,
+            cooldown: 10.0,
+        }
+    }
+}
+
+impl AlertConfig {
+    /// 从 `ALERT_*` 环境变量读取告警配置，未设置的项使用 [`AlertConfig::default`]
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let d = Self::default();
+        let hysteresis = env_or("ALERT_HYSTERESIS", d.high.hysteresis)?;
+        let config = Self {
+            high: Limits {
+                warning: env_limit("ALERT_HIGH_WARNING", d.high.warning)?,
+                critical: env_limit("ALERT_HIGH_CRITICAL", d.high.critical)?,
+                hysteresis,
+            },
+            low: Limits {
+                warning: env_limit("ALERT_LOW_WARNING", d.low.warning)?,
+                critical: env_limit("ALERT_LOW_CRITICAL", d.low.critical)?,
+                hysteresis,
+            },
+            rate: Limits {
+                warning: env_limit("ALERT_RATE_WARNING", d.rate.warning)?,
+                critical: env_limit("ALERT_RATE_CRITICAL", d.rate.critical)?,
+                hysteresis: env_or("ALERT_RATE_HYSTERESIS", d.rate.hysteresis)?,
+            },
+            deviation: Limits {
+                warning: env_limit("THRESHOLD", d.deviation.warning)?,
+                critical: env_limit("ALERT_DEVIATION_CRITICAL", d.deviation.critical)?,
+                hysteresis,
+            },
+            min_duration: env_or("ALERT_MIN_DURATION", d.min_duration)?,
+            cooldown: env_or("ALERT_COOLDOWN", d.cooldown)?,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        for (name, limits) in [
+            ("HIGH", &self.high),
+            ("RATE", &self.rate),
+            ("DEVIATION", &self.deviation),
+        ] {
+            if let (Some(w), Some(c)) = (limits.warning, limits.critical) {
+                if c < w {
+                    return Err(
+                        format!("ALERT_{name}_CRITICAL ({c}) 不能小于警告阈值 ({w})").into(),
+                    );
+                }
+            }
+        }
+        if let (Some(w), Some(c)) = (self.low.warning, self.low.critical) {
+            if c > w {
+                return Err(format!("ALERT_LOW_CRITICAL ({c}) 不能大于警告阈值 ({w})").into());
+            }
+        }
+        if self.min_duration < 0.0 || self.cooldown < 0.0 {
+            return Err("ALERT_MIN_DURATION/ALERT_COOLDOWN 不能为负数".into());
+        }
+        Ok(())
+    }
+}
+
+fn secs_to_ns(secs: f64) -> u64 {
+    (secs * 1e9).round() as u64
+}
+
+/// 单条规则的状态机：正常 → 待定（持续时间未满）→ 激活 → 解除
+#[derive(Debug, Default)]
+struct RuleState {
+    /// 当前激活的级别
+    active: Option<Severity>,
+    /// 等待满足最短持续时间的候选级别及开始时间 (ns)
+    pending: Option<(Severity, u64)>,
+    /// 最近一次实际发出的触发事件 (ns)，用于冷却
+    last_raised: Option<(Severity, u64)>,
+    /// 当前激活状态是否已经通知过下游（被冷却抑制的不发解除事件）
+    announced: bool,
+}
+
+impl RuleState {
+    /// 返回本次应发出的事件状态和级别
+    fn step(
+        &mut self,
+        target: Option<Severity>,
+        now: u64,
+        config: &AlertConfig,
+    ) -> Option<(AlertState, Severity)> {
+        if target == self.active {
+            self.pending = None;
+            return None;
+        }
+
+        if target < self.active {
+            // 降级或解除立即生效，回差已经防止了抖动
+            self.pending = None;
+            self.active = target;
+            if target.is_none() && self.announced {
+                self.announced = false;
+                return Some((AlertState::Cleared, Severity::Info));
+            }
+            return None;
+        }
+
+        // 升级：需要条件持续 min_duration
+        let target = target?;
+        let since = match self.pending {
+            // 更高级别的条件成立期间，较低级别的条件也一直成立
+            Some((pending, since)) if pending >= target => since,
+            _ => now,
+        };
+        self.pending = Some((target, since));
+        if now - since < secs_to_ns(config.min_duration) {
+            return None;
+        }
+
+        self.pending = None;
+        self.active = Some(target);
+        let cooling = self.last_raised.is_some_and(|(severity, at)| {
+            severity >= target && now - at < secs_to_ns(config.cooldown)
+        });
+        if cooling {
+            return None;
+        }
+        self.last_raised = Some((target, now));
+        self.announced = true;
+        Some((AlertState::Raised, target))
+    }
+}
+
+/// 告警引擎：上下限、变化率、偏离四条规则，以及基于预测值的上下限预警
+pub struct AlertEngine {
+    sensor_id: String,
+    config: AlertConfig,
+    high: RuleState,
+    low: RuleState,
+    rate: RuleState,
+    deviation: RuleState,
+    predicted: RuleState,
+    last_smoothed: Option<(u64, f32)>,
+}
+
+impl AlertEngine {
+    pub fn new(sensor_id: &str, config: AlertConfig) -> Self {
+        Self {
+            sensor_id: sensor_id.to_owned(),
+            config,
+            high: RuleState::default(),
+            low: RuleState::default(),
+            rate: RuleState::default(),
+            deviation: RuleState::default(),
+            predicted: RuleState::default(),
+            last_smoothed: None,
+        }
+    }
+
+    /// 热加载新的阈值，保留各规则当前的告警状态，已触发的告警按新阈值判断是否解除
+    pub fn set_config(&mut self, config: AlertConfig) {
+        self.config = config;
+    }
+
+    /// 输入 `timestamp_ns` 时刻的原始读数、平滑值和（可选的）预测值，返回需要发出的告警记录
+    pub fn evaluate(
+        &mut self,
+        timestamp_ns: u64,
+        raw: f32,
+        smoothed: f32,
+        forecast: Option<&Forecast>,
+    ) -> Vec<Alert> {
+        let now = timestamp_ns;
+        let mut triggers = Vec::new();
+        let config = &self.config;
+
+        // 上限：指标为温度本身
+        let target = config.high.level(smoothed, self.high.active);
+        if let Some((state, severity)) = self.high.step(target, now, config) {
+            let limit = config.high.threshold(severity.max(Severity::Warning));
+            triggers.push(Trigger {
+                kind: AlertKind::High,
+                severity,
+                state,
+                value: smoothed,
+                baseline: limit,
+                deviation: smoothed - limit,
+                detail: format!("{smoothed:.1}°C (阈值 {limit:.1}°C)"),
+            });
+        }
+
+        // 下限：取反后复用“越大越严重”的判断
+        let low = Limits {
+            warning: config.low.warning.map(|v| -v),
+            critical: config.low.critical.map(|v| -v),
+            hysteresis: config.low.hysteresis,
+        };
+        let target = low.level(-smoothed, self.low.active);
+        if let Some((state, severity)) = self.low.step(target, now, config) {
+            let limit = -low.threshold(severity.max(Severity::Warning));
+            triggers.push(Trigger {
+                kind: AlertKind::Low,
+                severity,
+                state,
+                value: smoothed,
+                baseline: limit,
+                deviation: limit - smoothed,
+                detail: format!("{smoothed:.1}°C (阈值 {limit:.1}°C)"),
+            });
+        }
+
+        // 变化率：基于平滑值，避免原始噪声造成误报
+        let rate = match self.last_smoothed {
+            Some((t, prev)) if now > t => (smoothed - prev) / ((now - t) as f64 * 1e-9) as f32,
+            _ => 0.0,
+        };
+        self.last_smoothed = Some((now, smoothed));
+        let target = config.rate.level(rate.abs(), self.rate.active);
+        if let Some((state, severity)) = self.rate.step(target, now, config) {
+            let limit = config.rate.threshold(severity.max(Severity::Warning));
+            triggers.push(Trigger {
+                kind: AlertKind::RateOfChange,
+                severity,
+                state,
+                value: smoothed,
+                baseline: limit,
+                deviation: rate,
+                detail: format!("{rate:+.2}°C/s (阈值 {limit:.2}°C/s)"),
+            });
+        }
+
+        // 偏离：原始读数相对平滑基线
+        let deviation = raw - smoothed;
+        let target = config
+            .deviation
+            .level(deviation.abs(), self.deviation.active);
+        if let Some((state, severity)) = self.deviation.step(target, now, config) {
+            triggers.push(Trigger {
+                kind: AlertKind::Deviation,
+                severity,
+                state,
+                value: raw,
+                baseline: smoothed,
+                deviation,
+                detail: format!("{raw:.1}°C (偏离均值{:.1}°C)", deviation.abs()),
+            });
+        }
+
+        // 预测：预测值将越过上下限、而实际温度尚未达到该级别时提前预警，
+        // 实际告警达到同一级别后预警自动解除
+        let predicted = forecast.map(|f| {
+            let high = config
+                .high
+                .level(f.value, self.predicted.active)
+                .filter(|&severity| Some(severity) > self.high.active);
+            let low = low
+                .level(-f.value, self.predicted.active)
+                .filter(|&severity| Some(severity) > self.low.active);
+            (f, high, low)
+        });
+        let target = predicted.and_then(|(_, high, low)| high.max(low));
+        if let Some((state, severity)) = self.predicted.step(target, now, config) {
+            let (value, horizon) = forecast.map_or((f32::NAN, 0.0), |f| (f.value, f.horizon_s));
+            let level = severity.max(Severity::Warning);
+            let (limit, deviation) = match predicted {
+                Some((_, _, Some(_))) => {
+                    let limit = -low.threshold(level);
+                    (limit, limit - value)
+                }
+                _ => {
+                    let limit = config.high.threshold(level);
+                    (limit, value - limit)
+                }
+            };
+            triggers.push(Trigger {
+                kind: AlertKind::Predicted,
+                severity,
+                state,
+                value,
+                baseline: limit,
+                deviation,
+                detail: format!("{horizon:.0}s 后预计 {value:.1}°C (阈值 {limit:.1}°C)"),
+            });
+        }
+
+        triggers
+            .into_iter()
+            .map(|trigger| trigger.into_alert(timestamp_ns, &self.sensor_id))
+            .collect()
+    }
+}
+
+/// 规则状态机产生的一次状态变化，补上时间戳和传感器 ID 后成为 [`Alert`]
+struct Trigger {
+    kind: AlertKind,
+    severity: Severity,
+    state: AlertState,
+    value: f32,
+    baseline: f32,
+    deviation: f32,
+    detail: String,
+}
+
+impl Trigger {
+    fn into_alert(self, timestamp_ns: u64, sensor_id: &str) -> Alert {
+        let Trigger {
+            kind,
+            severity,
+            state,
+            detail,
+            ..
+        } = self;
+        let message = match state {
+            AlertState::Raised => {
+                let icon = if severity == Severity::Critical {
+                    "🔥"
+                } else {
+                    "⚠️"
+                };
+                format!("{icon} [{severity}] {kind}: {detail}")
+            }
+            AlertState::Cleared => format!("✅ {kind}已解除: {detail}"),
+        };
+        Alert {
+            timestamp_ns,
+            sensor_id: sensor_id.to_owned(),
+            kind,
+            severity,
+            state,
+            value: self.value,
+            baseline: self.baseline,
+            deviation: self.deviation,
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OFF: Limits = Limits {
+        warning: None,
+        critical: None,
+        hysteresis: 0.0,
+    };
+
+    fn high_only(min_duration: f64, cooldown: f64) -> AlertEngine {
+        AlertEngine::new(
+            "sensor0",
+            AlertConfig {
+                high: Limits {
+                    warning: Some(30.0),
+                    critical: Some(40.0),
+                    hysteresis: 1.0,
+                },
+                low: OFF,
+                rate: OFF,
+                deviation: OFF,
+                min_duration,
+                cooldown,
+            },
+        )
+    }
+
+    const TICK_NS: u64 = 100_000_000;
+
+    /// 以 0.1s 间隔输入平滑值序列，返回 (样本序号, 状态, 级别)
+    fn feed(engine: &mut AlertEngine, values: &[f32]) -> Vec<(usize, AlertState, Severity)> {
+        let mut out = Vec::new();
+        for (i, &v) in values.iter().enumerate() {
+            for event in engine.evaluate(i as u64 * TICK_NS, v, v, None) {
+                out.push((i, event.state, event.severity));
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn raises_once_and_clears_after_hysteresis() {
+        let mut engine = high_only(0.0, 0.0);
+        let events = feed(&mut engine, &[25.0, 31.0, 32.0, 29.5, 31.0, 28.9, 25.0]);
+        assert_eq!(
+            events,
+            vec![
+                (1, AlertState::Raised, Severity::Warning),
+                (5, AlertState::Cleared, Severity::Info),
+            ]
+        );
+    }
+
+    #[test]
+    fn min_duration_filters_short_spikes() {
+        let mut engine = high_only(0.2, 0.0);
+        // 单点尖峰不触发；持续 3 个样本才触发
+        let events = feed(&mut engine, &[25.0, 35.0, 25.0, 35.0, 35.0, 35.0]);
+        assert_eq!(events, vec![(5, AlertState::Raised, Severity::Warning)]);
+    }
+
+    #[test]
+    fn escalates_to_critical_without_waiting_for_cooldown() {
+        let mut engine = high_only(0.0, 60.0);
+        let events = feed(&mut engine, &[31.0, 41.0, 35.0, 20.0]);
+        assert_eq!(
+            events,
+            vec![
+                (0, AlertState::Raised, Severity::Warning),
+                (1, AlertState::Raised, Severity::Critical),
+                (3, AlertState::Cleared, Severity::Info),
+            ]
+        );
+    }
+
+    #[test]
+    fn cooldown_suppresses_repeats_and_their_clear() {
+        let mut engine = high_only(0.0, 1.0);
+        let mut values = vec![31.0, 20.0, 31.0, 20.0];
+        // 冷却期过后再次触发
+        values.extend([20.0; 10]);
+        values.push(31.0);
+        let events = feed(&mut engine, &values);
+        assert_eq!(
+            events,
+            vec![
+                (0, AlertState::Raised, Severity::Warning),
+                (1, AlertState::Cleared, Severity::Info),
+                (14, AlertState::Raised, Severity::Warning),
+            ]
+        );
+    }
+
+    #[test]
+    fn low_limit_fires_below_threshold() {
+        let mut engine = AlertEngine::new(
+            "sensor0",
+            AlertConfig {
+                high: OFF,
+                low: Limits {
+                    warning: Some(10.0),
+                    critical: Some(5.0),
+                    hysteresis: 0.5,
+                },
+                rate: OFF,
+                deviation: OFF,
+                min_duration: 0.0,
+                cooldown: 0.0,
+            },
+        );
+        let events = engine.evaluate(0, 4.0, 4.0, None);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, AlertKind::Low);
+        assert_eq!(events[0].severity, Severity::Critical);
+        assert_eq!(events[0].baseline, 5.0);
+    }
+
+    #[test]
+    fn rate_of_change_uses_sample_times() {
+        let mut engine = AlertEngine::new(
+            "sensor0",
+            AlertConfig {
+                high: OFF,
+                low: OFF,
+                rate: Limits {
+                    warning: Some(1.0),
+                    critical: None,
+                    hysteresis: 0.1,
+                },
+                deviation: OFF,
+                min_duration: 0.0,
+                cooldown: 0.0,
+            },
+        );
+        assert!(engine.evaluate(0, 25.0, 25.0, None).is_empty());
+        // 1 秒内上升 0.5°C：未超限
+        assert!(engine.evaluate(10 * TICK_NS, 25.5, 25.5, None).is_empty());
+        // 0.1 秒内上升 0.5°C：5°C/s
+        let events = engine.evaluate(11 * TICK_NS, 26.0, 26.0, None);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, AlertKind::RateOfChange);
+        assert!((events[0].deviation - 5.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn predicted_alert_fires_before_actual_limit_and_clears_when_reached() {
+        let mut engine = high_only(0.0, 0.0);
+        let forecast = |value| Forecast {
+            timestamp_ns: 0,
+            sensor_id: "sensor0".to_owned(),
+            horizon_s: 30.0,
+            value,
+            lower: value - 1.0,
+            upper: value + 1.0,
+        };
+        // 实际 26→29→31°C，30s 预测值 28→32→34°C
+        let steps = [(26.0, 28.0), (29.0, 32.0), (31.0, 34.0)];
+        let events: Vec<Vec<(AlertKind, AlertState, Severity)>> = steps
+            .iter()
+            .enumerate()
+            .map(|(i, &(actual, predicted))| {
+                engine
+                    .evaluate(
+                        i as u64 * TICK_NS,
+                        actual,
+                        actual,
+                        Some(&forecast(predicted)),
+                    )
+                    .into_iter()
+                    .map(|a| (a.kind, a.state, a.severity))
+                    .collect()
+            })
+            .collect();
+
+        assert!(events[0].is_empty());
+        assert_eq!(
+            events[1],
+            vec![(AlertKind::Predicted, AlertState::Raised, Severity::Warning)]
+        );
+        assert_eq!(
+            events[2],
+            vec![
+                (AlertKind::High, AlertState::Raised, Severity::Warning),
+                (AlertKind::Predicted, AlertState::Cleared, Severity::Info),
+            ]
+        );
+    }
+}
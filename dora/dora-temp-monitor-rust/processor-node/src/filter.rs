@@ -0,0 +1,281 @@
+use std::collections::VecDeque;
+use std::error::Error;
+
+use temp_common::config::env_or;
+
+/// 平滑滤波器：每输入一个原始读数，输出一个平滑后的值
+pub trait Filter {
+    /// 滤波器名称，用于启动日志
+    fn name(&self) -> String;
+
+    /// 输入一个新读数，返回当前平滑值
+    fn update(&mut self, value: f32) -> f32;
+}
+
+/// 滤波器类型及参数，处理器为每个传感器各建一个独立的滤波器实例
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterConfig {
+    MovingAverage { window_size: usize },
+    Ema { alpha: f32 },
+    Median { window_size: usize },
+    Kalman { q: f32, r: f32 },
+}
+
+impl FilterConfig {
+    /// 根据 `FILTER` 环境变量（dataflow.yml 中的 `env:`）选择滤波器
+    ///
+    /// - `moving_average`（默认）：滑动平均，窗口 `WINDOW_SIZE`
+    /// - `ema`：指数滑动平均，平滑系数 `EMA_ALPHA`
+    /// - `median`：滑动中值，窗口 `WINDOW_SIZE`
+    /// - `kalman`：一维卡尔曼滤波，过程噪声 `KALMAN_Q`、测量噪声 `KALMAN_R`
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let window_size = env_or("WINDOW_SIZE", 10usize)?;
+        let config = match env_or("FILTER", "moving_average".to_owned())?.as_str() {
+            "moving_average" => FilterConfig::MovingAverage { window_size },
+            "ema" => FilterConfig::Ema {
+                alpha: env_or("EMA_ALPHA", 0.2)?,
+            },
+            "median" => FilterConfig::Median { window_size },
+            "kalman" => FilterConfig::Kalman {
+                q: env_or("KALMAN_Q", 0.01)?,
+                r: env_or("KALMAN_R", 1.0)?,
+            },
+            other => return Err(format!("未知的滤波器 FILTER={other}").into()),
+        };
+        // 启动时构造一次，尽早暴露无效参数
+        config.build()?;
+        Ok(config)
+    }
+
+    /// 创建一个新的滤波器实例
+    pub fn build(&self) -> Result<Box<dyn Filter>, Box<dyn Error>> {
+        let filter: Box<dyn Filter> = match *self {
+            FilterConfig::MovingAverage { window_size } => {
+                Box::new(MovingAverage::new(window_size)?)
+            }
+            FilterConfig::Ema { alpha } => Box::new(ExponentialMovingAverage::new(alpha)?),
+            FilterConfig::Median { window_size } => Box::new(RollingMedian::new(window_size)?),
+            FilterConfig::Kalman { q, r } => Box::new(Kalman1D::new(q, r)?),
+        };
+        Ok(filter)
+    }
+}
+
+/// 滑动平均
+pub struct MovingAverage {
+    window: VecDeque<f32>,
+    window_size: usize,
+}
+
+impl MovingAverage {
+    pub fn new(window_size: usize) -> Result<Self, Box<dyn Error>> {
+        if window_size == 0 {
+            return Err("WINDOW_SIZE 必须大于 0".into());
+        }
+        Ok(Self {
+            window: VecDeque::with_capacity(window_size),
+            window_size,
+        })
+    }
+}
+
+impl Filter for MovingAverage {
+    fn name(&self) -> String {
+        format!("滑动平均(窗口 {})", self.window_size)
+    }
+
+    fn update(&mut self, value: f32) -> f32 {
+        self.window.push_back(value);
+        if self.window.len() > self.window_size {
+            self.window.pop_front();
+        }
+        self.window.iter().sum::<f32>() / self.window.len() as f32
+    }
+}
+
+/// 指数滑动平均：`s = alpha * x + (1 - alpha) * s`
+pub struct ExponentialMovingAverage {
+    alpha: f32,
+    state: Option<f32>,
+}
+
+impl ExponentialMovingAverage {
+    pub fn new(alpha: f32) -> Result<Self, Box<dyn Error>> {
+        if !(alpha > 0.0 && alpha <= 1.0) {
+            return Err(format!("EMA_ALPHA 必须在 (0, 1] 之间，当前为 {alpha}").into());
+        }
+        Ok(Self { alpha, state: None })
+    }
+}
+
+impl Filter for ExponentialMovingAverage {
+    fn name(&self) -> String {
+        format!("指数滑动平均(alpha {})", self.alpha)
+    }
+
+    fn update(&mut self, value: f32) -> f32 {
+        // 第一个读数直接作为初值，避免从 0 开始爬升
+        let next = match self.state {
+            Some(prev) => self.alpha * value + (1.0 - self.alpha) * prev,
+            None => value,
+        };
+        self.state = Some(next);
+        next
+    }
+}
+
+/// 滑动中值，对单点尖峰不敏感
+pub struct RollingMedian {
+    window: VecDeque<f32>,
+    window_size: usize,
+}
+
+impl RollingMedian {
+    pub fn new(window_size: usize) -> Result<Self, Box<dyn Error>> {
+        if window_size == 0 {
+            return Err("WINDOW_SIZE 必须大于 0".into());
+        }
+        Ok(Self {
+            window: VecDeque::with_capacity(window_size),
+            window_size,
+        })
+    }
+}
+
+impl Filter for RollingMedian {
+    fn name(&self) -> String {
+        format!("滑动中值(窗口 {})", self.window_size)
+    }
+
+    fn update(&mut self, value: f32) -> f32 {
+        self.window.push_back(value);
+        if self.window.len() > self.window_size {
+            self.window.pop_front();
+        }
+
+        let mut sorted: Vec<f32> = self.window.iter().copied().collect();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let mid = sorted.len() / 2;
+        if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2.0
+        } else {
+            sorted[mid]
+        }
+    }
+}
+
+/// 一维卡尔曼滤波（常值模型）
+///
+/// `q` 为过程噪声方差（温度本身变化有多快），`r` 为测量噪声方差（传感器有多吵）
+pub struct Kalman1D {
+    q: f32,
+    r: f32,
+    estimate: f32,
+    error: f32,
+    initialized: bool,
+}
+
+impl Kalman1D {
+    pub fn new(q: f32, r: f32) -> Result<Self, Box<dyn Error>> {
+        if q <= 0.0 || r <= 0.0 {
+            return Err(format!("KALMAN_Q/KALMAN_R 必须大于 0，当前为 {q}/{r}").into());
+        }
+        Ok(Self {
+            q,
+            r,
+            estimate: 0.0,
+            error: 1.0,
+            initialized: false,
+        })
+    }
+}
+
+impl Filter for Kalman1D {
+    fn name(&self) -> String {
+        format!("卡尔曼滤波(Q {}, R {})", self.q, self.r)
+    }
+
+    fn update(&mut self, value: f32) -> f32 {
+        if !self.initialized {
+            self.estimate = value;
+            self.error = self.r;
+            self.initialized = true;
+            return value;
+        }
+
+        // 预测
+        self.error += self.q;
+        // 更新
+        let gain = self.error / (self.error + self.r);
+        self.estimate += gain * (value - self.estimate);
+        self.error *= 1.0 - gain;
+        self.estimate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(filter: &mut dyn Filter, input: &[f32]) -> Vec<f32> {
+        input.iter().map(|&x| filter.update(x)).collect()
+    }
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-4, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn moving_average_respects_window_size() {
+        let mut filter = MovingAverage::new(3).unwrap();
+        let out = run(&mut filter, &[1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_close(&out, &[1.0, 1.5, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn ema_starts_from_first_sample() {
+        let mut filter = ExponentialMovingAverage::new(0.5).unwrap();
+        let out = run(&mut filter, &[10.0, 20.0, 20.0, 0.0]);
+        assert_close(&out, &[10.0, 15.0, 17.5, 8.75]);
+    }
+
+    #[test]
+    fn median_rejects_single_spike() {
+        let mut filter = RollingMedian::new(3).unwrap();
+        let out = run(&mut filter, &[25.0, 25.0, 90.0, 25.0, 26.0, 27.0]);
+        assert_close(&out, &[25.0, 25.0, 25.0, 25.0, 26.0, 26.0]);
+    }
+
+    #[test]
+    fn median_of_even_window_averages_middle_pair() {
+        let mut filter = RollingMedian::new(4).unwrap();
+        let out = run(&mut filter, &[4.0, 1.0, 3.0, 2.0]);
+        assert_close(&out, &[4.0, 2.5, 3.0, 2.5]);
+    }
+
+    #[test]
+    fn kalman_matches_hand_computed_gain() {
+        let mut filter = Kalman1D::new(1.0, 1.0).unwrap();
+        // P0 = R = 1；第二步 P = 2, K = 2/3；第三步 P = 2/3 + 1, K = 5/8
+        let out = run(&mut filter, &[0.0, 3.0, 3.0]);
+        assert_close(&out, &[0.0, 2.0, 2.625]);
+    }
+
+    #[test]
+    fn kalman_converges_to_constant_signal() {
+        let mut filter = Kalman1D::new(0.01, 1.0).unwrap();
+        let out = run(&mut filter, &[30.0; 50]);
+        assert!((out[49] - 30.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn invalid_parameters_are_rejected() {
+        assert!(MovingAverage::new(0).is_err());
+        assert!(ExponentialMovingAverage::new(0.0).is_err());
+        assert!(ExponentialMovingAverage::new(1.5).is_err());
+        assert!(Kalman1D::new(0.0, 1.0).is_err());
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::VecDeque;
+use std::error::Error;
+
+use temp_common::config::env_or;
+
+/// 短期预测器：按时间输入平滑温度，预测若干秒后的温度
+pub trait Forecaster {
+    /// 预测方法名称，用于启动日志
+    fn name(&self) -> String;
+
+    /// 输入 `t` 秒时刻的值，时间不递增的样本会被忽略
+    fn update(&mut self, t: f64, value: f32);
+
+    /// 预测 `horizon` 秒后的值，返回 `(预测值, 标准误差)`；样本不足时返回 `None`
+    fn predict(&self, horizon: f64) -> Option<(f32, f32)>;
+}
+
+/// 预测方法及参数，处理器为每个传感器各建一个独立的预测器
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ForecastMethod {
+    Holt { alpha: f32, beta: f32 },
+    LeastSquares { window: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForecastConfig {
+    pub method: ForecastMethod,
+    /// 预测提前量（秒）
+    pub horizon: f64,
+    /// 置信区间宽度，以标准误差的倍数表示（1.96 ≈ 95%）
+    pub z: f32,
+}
+
+impl ForecastConfig {
+    /// 根据 `FORECAST` 环境变量选择预测方法，`off` 表示关闭预测
+    ///
+    /// - `holt`（默认）：Holt 线性趋势指数平滑，`HOLT_ALPHA`、`HOLT_BETA`
+    /// - `linear`：最近 `FORECAST_WINDOW` 个样本的最小二乘直线
+    pub fn from_env() -> Result<Option<Self>, Box<dyn Error>> {
+        let method = match env_or("FORECAST", "holt".to_owned())?.as_str() {
+            "off" => return Ok(None),
+            "holt" => ForecastMethod::Holt {
+                alpha: env_or("HOLT_ALPHA", 0.5)?,
+                beta: env_or("HOLT_BETA", 0.1)?,
+            },
+            "linear" => ForecastMethod::LeastSquares {
+                window: env_or("FORECAST_WINDOW", 50usize)?,
+            },
+            other => return Err(format!("未知的预测方法 FORECAST={other}").into()),
+        };
+        let config = Self {
+            method,
+            horizon: env_or("FORECAST_HORIZON", 30.0)?,
+            z: env_or("FORECAST_Z", 1.96)?,
+        };
+        if !(config.horizon > 0.0 && config.z >= 0.0) {
+            return Err("FORECAST_HORIZON 必须大于 0，FORECAST_Z 不能为负数".into());
+        }
+        // 启动时构造一次，尽早暴露无效参数
+        config.build()?;
+        Ok(Some(config))
+    }
+
+    /// 创建一个新的预测器实例
+    pub fn build(&self) -> Result<Box<dyn Forecaster>, Box<dyn Error>> {
+        let forecaster: Box<dyn Forecaster> = match self.method {
+            ForecastMethod::Holt { alpha, beta } => Box::new(Holt::new(alpha, beta)?),
+            ForecastMethod::LeastSquares { window } => Box::new(LeastSquares::new(window)?),
+        };
+        Ok(forecaster)
+    }
+}
+
+/// Holt 线性趋势指数平滑，趋势以 °C/s 计，支持不等间隔采样
+///
+/// `level = α·x + (1-α)·(level + trend·dt)`，`trend = β·Δlevel/dt + (1-β)·trend`
+pub struct Holt {
+    alpha: f32,
+    beta: f32,
+    /// 上一个样本的时间 (s)、水平和趋势
+    state: Option<(f64, f64, f64)>,
+    /// 一步预测误差的方差和平均采样间隔，均为指数加权
+    error_var: f64,
+    mean_dt: f64,
+    samples: usize,
+}
+
+impl Holt {
+    pub fn new(alpha: f32, beta: f32) -> Result<Self, Box<dyn Error>> {
+        if !(alpha > 0.0 && alpha <= 1.0 && beta > 0.0 && beta <= 1.0) {
+            return Err(
+                format!("HOLT_ALPHA/HOLT_BETA 必须在 (0, 1] 之间，当前为 {alpha}/{beta}").into(),
+            );
+        }
+        Ok(Self {
+            alpha,
+            beta,
+            state: None,
+            error_var: 0.0,
+            mean_dt: 0.0,
+            samples: 0,
+        })
+    }
+}
+
+impl Forecaster for Holt {
+    fn name(&self) -> String {
+        format!("Holt(α={}, β={})", self.alpha, self.beta)
+    }
+
+    fn update(&mut self, t: f64, value: f32) {
+        let x = value as f64;
+        let Some((last_t, level, trend)) = self.state else {
+            self.state = Some((t, x, 0.0));
+            self.samples = 1;
+            return;
+        };
+        let dt = t - last_t;
+        if dt <= 0.0 {
+            return;
+        }
+
+        let (alpha, beta) = (self.alpha as f64, self.beta as f64);
+        let predicted = level + trend * dt;
+        let error = x - predicted;
+        let new_level = alpha * x + (1.0 - alpha) * predicted;
+        let new_trend = beta * (new_level - level) / dt + (1.0 - beta) * trend;
+        self.state = Some((t, new_level, new_trend));
+
+        // 早期样本按算术平均，之后按 5% 权重跟踪最近的误差水平
+        self.samples += 1;
+        let weight = (1.0 / (self.samples - 1) as f64).max(0.05);
+        self.error_var += weight * (error * error - self.error_var);
+        self.mean_dt += weight * (dt - self.mean_dt);
+    }
+
+    fn predict(&self, horizon: f64) -> Option<(f32, f32)> {
+        let (_, level, trend) = self.state?;
+        if self.samples < 3 {
+            return None;
+        }
+        // h 步预测误差方差：σ²·(1 + Σ_{j=1}^{h-1} (α·(1 + j·β))²)
+        let steps = (horizon / self.mean_dt).round().clamp(1.0, 10_000.0) as usize;
+        let (alpha, beta) = (self.alpha as f64, self.beta as f64);
+        let growth: f64 = (1..steps)
+            .map(|j| (alpha * (1.0 + j as f64 * beta)).powi(2))
+            .sum();
+        let std_err = (self.error_var * (1.0 + growth)).sqrt();
+        Some(((level + trend * horizon) as f32, std_err as f32))
+    }
+}
+
+/// 最近 `window` 个样本的最小二乘直线外推，置信区间为回归的预测区间
+pub struct LeastSquares {
+    samples: VecDeque<(f64, f64)>,
+    window: usize,
+}
+
+impl LeastSquares {
+    pub fn new(window: usize) -> Result<Self, Box<dyn Error>> {
+        if window < 3 {
+            return Err("FORECAST_WINDOW 至少为 3".into());
+        }
+        Ok(Self {
+            samples: VecDeque::with_capacity(window),
+            window,
+        })
+    }
+}
+
+impl Forecaster for LeastSquares {
+    fn name(&self) -> String {
+        format!("最小二乘(窗口 {})", self.window)
+    }
+
+    fn update(&mut self, t: f64, value: f32) {
+        if self.samples.back().is_some_and(|&(last, _)| t <= last) {
+            return;
+        }
+        self.samples.push_back((t, value as f64));
+        if self.samples.len() > self.window {
+            self.samples.pop_front();
+        }
+    }
+
+    fn predict(&self, horizon: f64) -> Option<(f32, f32)> {
+        let n = self.samples.len();
+        if n < 3 {
+            return None;
+        }
+        let nf = n as f64;
+        let t_mean = self.samples.iter().map(|&(t, _)| t).sum::<f64>() / nf;
+        let x_mean = self.samples.iter().map(|&(_, x)| x).sum::<f64>() / nf;
+        let sxx: f64 = self
+            .samples
+            .iter()
+            .map(|&(t, _)| (t - t_mean).powi(2))
+            .sum();
+        let sxy: f64 = self
+            .samples
+            .iter()
+            .map(|&(t, x)| (t - t_mean) * (x - x_mean))
+            .sum();
+        if sxx <= 0.0 {
+            return None;
+        }
+        let slope = sxy / sxx;
+        let residual_var = self
+            .samples
+            .iter()
+            .map(|&(t, x)| (x - x_mean - slope * (t - t_mean)).powi(2))
+            .sum::<f64>()
+            / (nf - 2.0);
+
+        let target = self.samples[n - 1].0 + horizon;
+        let value = x_mean + slope * (target - t_mean);
+        let std_err = (residual_var * (1.0 + 1.0 / nf + (target - t_mean).powi(2) / sxx)).sqrt();
+        Some((value as f32, std_err as f32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0.1s 间隔、每秒升温 0.2°C 的斜坡，可叠加确定性的锯齿噪声
+    fn feed(forecaster: &mut dyn Forecaster, samples: usize, noise: f32) {
+        for i in 0..samples {
+            let t = i as f64 * 0.1;
+            let jitter = if i.is_multiple_of(2) { noise } else { -noise };
+            forecaster.update(t, 20.0 + 0.2 * t as f32 + jitter);
+        }
+    }
+
+    #[test]
+    fn both_methods_extrapolate_a_clean_ramp() {
+        let mut holt = Holt::new(0.5, 0.1).unwrap();
+        let mut linear = LeastSquares::new(50).unwrap();
+        assert_eq!(holt.predict(10.0), None);
+        feed(&mut holt, 300, 0.0);
+        feed(&mut linear, 300, 0.0);
+
+        // 最后一个样本在 29.9s (26.0°C)，10s 后应为 28.0°C
+        for forecaster in [&holt as &dyn Forecaster, &linear] {
+            let (value, std_err) = forecaster.predict(10.0).unwrap();
+            assert!(
+                (value - 27.98).abs() < 0.05,
+                "{}: {value}",
+                forecaster.name()
+            );
+            assert!(std_err < 0.05, "{}: {std_err}", forecaster.name());
+        }
+    }
+
+    #[test]
+    fn band_widens_with_noise_and_horizon() {
+        for mut forecaster in [
+            Box::new(Holt::new(0.5, 0.1).unwrap()) as Box<dyn Forecaster>,
+            Box::new(LeastSquares::new(50).unwrap()),
+        ] {
+            feed(forecaster.as_mut(), 300, 0.5);
+            let (_, near) = forecaster.predict(1.0).unwrap();
+            let (_, far) = forecaster.predict(30.0).unwrap();
+            assert!(near > 0.1, "{}: {near}", forecaster.name());
+            assert!(far > near, "{}: {far} <= {near}", forecaster.name());
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        assert!(Holt::new(0.0, 0.1).is_err());
+        assert!(Holt::new(0.5, 1.5).is_err());
+        assert!(LeastSquares::new(2).is_err());
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::HashSet;
+use std::error::Error;
+use temp_common::alert::{AlertState, Severity};
+use temp_common::config::env_or;
+use temp_common::health::{HealthEvent, HealthIssue, BATCH_SENSOR_ID};
+use temp_common::reading::Reading;
+
+/// 数据质量检测参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HealthConfig {
+    /// 物理上可能的温度范围 (°C)，范围外的读数丢弃
+    pub min_valid: f32,
+    pub max_valid: f32,
+    /// 连续多少个读数的变化都不超过 `stuck_tolerance` 视为卡死，0 表示关闭
+    pub stuck_samples: usize,
+    pub stuck_tolerance: f32,
+    /// 读数间隔超过平均采样间隔的多少倍视为中断，0 表示关闭
+    pub dropout_factor: f64,
+}
+
+/// 未设置环境变量时使用的检测参数
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            min_valid: -55.0,
+            max_valid: 150.0,
+            stuck_samples: 50,
+            stuck_tolerance: 0.001,
+            dropout_factor: 5.0,
+        }
+    }
+}
+
+impl HealthConfig {
+    /// 从 `HEALTH_*` 环境变量读取检测参数，未设置的项使用 [`HealthConfig::default`]
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let d = Self::default();
+        let config = Self {
+            min_valid: env_or("HEALTH_MIN_VALID", d.min_valid)?,
+            max_valid: env_or("HEALTH_MAX_VALID", d.max_valid)?,
+            stuck_samples: env_or("HEALTH_STUCK_SAMPLES", d.stuck_samples)?,
+            stuck_tolerance: env_or("HEALTH_STUCK_TOLERANCE", d.stuck_tolerance)?,
+            dropout_factor: env_or("HEALTH_DROPOUT_FACTOR", d.dropout_factor)?,
+        };
+        let range_ok = config.min_valid < config.max_valid;
+        if !range_ok {
+            return Err("HEALTH_MIN_VALID 必须小于 HEALTH_MAX_VALID".into());
+        }
+        let factor_ok = config.dropout_factor == 0.0 || config.dropout_factor > 1.0;
+        if !factor_ok {
+            return Err("HEALTH_DROPOUT_FACTOR 必须大于 1，或为 0 表示关闭".into());
+        }
+        Ok(config)
+    }
+}
+
+/// 估计采样间隔前至少需要的正常间隔数
+const MIN_INTERVALS: usize = 5;
+
+/// 单个传感器的数据质量状态，每类问题只在出现和恢复时各发出一条事件
+pub struct HealthMonitor {
+    sensor_id: String,
+    config: HealthConfig,
+    active: HashSet<HealthIssue>,
+    last_ns: Option<u64>,
+    /// 指数加权的平均采样间隔 (s) 及参与估计的间隔数
+    mean_interval: f64,
+    intervals: usize,
+    /// 卡死检测：当前连续不变读数的参考值和个数
+    stuck_value: f32,
+    stuck_run: usize,
+}
+
+impl HealthMonitor {
+    pub fn new(sensor_id: &str, config: HealthConfig) -> Self {
+        Self {
+            sensor_id: sensor_id.to_owned(),
+            config,
+            active: HashSet::new(),
+            last_ns: None,
+            mean_interval: 0.0,
+            intervals: 0,
+            stuck_value: f32::NAN,
+            stuck_run: 0,
+        }
+    }
+
+    /// 热加载新的检测参数，保留已估计的采样间隔和当前的问题状态
+    pub fn set_config(&mut self, config: HealthConfig) {
+        self.config = config;
+    }
+
+    /// 检查一个读数，返回它是否可以参与滤波、预测和告警
+    pub fn check(&mut self, reading: &Reading, events: &mut Vec<HealthEvent>) -> bool {
+        let ts = reading.timestamp_ns;
+        let value = reading.value;
+        self.check_interval(ts, events);
+
+        if !value.is_finite() {
+            self.raise(
+                ts,
+                HealthIssue::NonFinite,
+                value,
+                "读数为 NaN 或无穷大".to_owned(),
+                events,
+            );
+            return false;
+        }
+        self.clear(ts, HealthIssue::NonFinite, value, events);
+
+        if value < self.config.min_valid || value > self.config.max_valid {
+            let message = format!(
+                "读数 {value:.1}°C 超出有效范围 [{:.1}, {:.1}]°C",
+                self.config.min_valid, self.config.max_valid
+            );
+            self.raise(ts, HealthIssue::OutOfRange, value, message, events);
+            return false;
+        }
+        self.clear(ts, HealthIssue::OutOfRange, value, events);
+
+        self.check_stuck(ts, value, events);
+        true
+    }
+
+    /// 当前时刻仍未收到新读数时提前报告中断，不必等到传感器恢复
+    pub fn check_silence(&mut self, now_ns: u64, events: &mut Vec<HealthEvent>) {
+        let (Some(last_ns), Some(limit)) = (self.last_ns, self.dropout_limit()) else {
+            return;
+        };
+        let gap = now_ns.saturating_sub(last_ns) as f64 / 1e9;
+        if gap > limit {
+            let message = format!(
+                "已 {gap:.1}s 未收到读数 (预期间隔 {:.2}s)",
+                self.mean_interval
+            );
+            self.raise(now_ns, HealthIssue::Dropout, gap as f32, message, events);
+        }
+    }
+
+    /// 采样间隔足够稳定后才判断中断
+    fn dropout_limit(&self) -> Option<f64> {
+        (self.config.dropout_factor > 0.0 && self.intervals >= MIN_INTERVALS)
+            .then_some(self.config.dropout_factor * self.mean_interval)
+    }
+
+    fn check_interval(&mut self, ts: u64, events: &mut Vec<HealthEvent>) {
+        let Some(last_ns) = self.last_ns else {
+            self.last_ns = Some(ts);
+            return;
+        };
+        // 乱序或重复的时间戳不参与间隔估计
+        if ts <= last_ns {
+            return;
+        }
+        self.last_ns = Some(ts);
+        let gap = (ts - last_ns) as f64 / 1e9;
+
+        if self.dropout_limit().is_some_and(|limit| gap > limit) {
+            // 中断期间没有其他批次触发 check_silence 时，恢复时补发一对事件
+            let message = format!(
+                "中断 {gap:.1}s 后恢复 (预期间隔 {:.2}s)",
+                self.mean_interval
+            );
+            self.raise(ts, HealthIssue::Dropout, gap as f32, message, events);
+            self.clear(ts, HealthIssue::Dropout, gap as f32, events);
+            return;
+        }
+        self.clear(ts, HealthIssue::Dropout, gap as f32, events);
+
+        self.intervals += 1;
+        let weight = (1.0 / self.intervals as f64).max(0.05);
+        self.mean_interval += weight * (gap - self.mean_interval);
+    }
+
+    fn check_stuck(&mut self, ts: u64, value: f32, events: &mut Vec<HealthEvent>) {
+        if self.config.stuck_samples == 0 {
+            return;
+        }
+        if (value - self.stuck_value).abs() <= self.config.stuck_tolerance {
+            self.stuck_run += 1;
+        } else {
+            self.stuck_value = value;
+            self.stuck_run = 1;
+            self.clear(ts, HealthIssue::Stuck, value, events);
+        }
+        if self.stuck_run >= self.config.stuck_samples {
+            let message = format!("连续 {} 个读数停留在 {value:.2}°C", self.stuck_run);
+            self.raise(ts, HealthIssue::Stuck, value, message, events);
+        }
+    }
+
+    fn raise(
+        &mut self,
+        ts: u64,
+        issue: HealthIssue,
+        value: f32,
+        message: String,
+        events: &mut Vec<HealthEvent>,
+    ) {
+        if self.active.insert(issue) {
+            events.push(event(
+                ts,
+                &self.sensor_id,
+                issue,
+                AlertState::Raised,
+                value,
+                message,
+            ));
+        }
+    }
+
+    fn clear(&mut self, ts: u64, issue: HealthIssue, value: f32, events: &mut Vec<HealthEvent>) {
+        if self.active.remove(&issue) {
+            let message = format!("{issue}已恢复");
+            events.push(event(
+                ts,
+                &self.sensor_id,
+                issue,
+                AlertState::Cleared,
+                value,
+                message,
+            ));
+        }
+    }
+}
+
+/// 批次级检测：空批次和无法解码的数据，恢复正常批次时解除
+#[derive(Default)]
+pub struct BatchMonitor {
+    active: Option<HealthIssue>,
+}
+
+impl BatchMonitor {
+    /// `issue` 为 `None` 表示本批次正常
+    pub fn update(
+        &mut self,
+        ts: u64,
+        issue: Option<(HealthIssue, String)>,
+        events: &mut Vec<HealthEvent>,
+    ) {
+        let next = issue.as_ref().map(|(issue, _)| *issue);
+        if self.active == next {
+            return;
+        }
+        if let Some(previous) = self.active.take() {
+            let message = format!("{previous}已恢复");
+            events.push(event(
+                ts,
+                BATCH_SENSOR_ID,
+                previous,
+                AlertState::Cleared,
+                f32::NAN,
+                message,
+            ));
+        }
+        if let Some((issue, message)) = issue {
+            events.push(event(
+                ts,
+                BATCH_SENSOR_ID,
+                issue,
+                AlertState::Raised,
+                f32::NAN,
+                message,
+            ));
+            self.active = Some(issue);
+        }
+    }
+}
+
+fn event(
+    ts: u64,
+    sensor_id: &str,
+    issue: HealthIssue,
+    state: AlertState,
+    value: f32,
+    message: String,
+) -> HealthEvent {
+    let severity = match (state, issue) {
+        (AlertState::Cleared, _) => Severity::Info,
+        (_, HealthIssue::Stuck | HealthIssue::Dropout | HealthIssue::EmptyBatch) => {
+            Severity::Warning
+        }
+        _ => Severity::Critical,
+    };
+    HealthEvent {
+        timestamp_ns: ts,
+        sensor_id: sensor_id.to_owned(),
+        issue,
+        severity,
+        state,
+        value,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> HealthConfig {
+        HealthConfig {
+            min_valid: -55.0,
+            max_valid: 150.0,
+            stuck_samples: 5,
+            stuck_tolerance: 0.001,
+            dropout_factor: 5.0,
+        }
+    }
+
+    /// 100ms 采样间隔下第 `i` 个读数
+    fn reading(i: u64, value: f32) -> Reading {
+        Reading::new(i * 100_000_000, "sensor0", value)
+    }
+
+    fn issues(events: &[HealthEvent]) -> Vec<(HealthIssue, AlertState)> {
+        events.iter().map(|e| (e.issue, e.state)).collect()
+    }
+
+    #[test]
+    fn invalid_values_are_rejected_and_reported_once() {
+        let mut monitor = HealthMonitor::new("sensor0", config());
+        let mut events = Vec::new();
+
+        assert!(monitor.check(&reading(0, 25.0), &mut events));
+        assert!(!monitor.check(&reading(1, f32::NAN), &mut events));
+        assert!(!monitor.check(&reading(2, f32::INFINITY), &mut events));
+        assert!(!monitor.check(&reading(3, 850.0), &mut events));
+        assert!(monitor.check(&reading(4, 25.5), &mut events));
+
+        assert_eq!(
+            issues(&events),
+            [
+                (HealthIssue::NonFinite, AlertState::Raised),
+                (HealthIssue::NonFinite, AlertState::Cleared),
+                (HealthIssue::OutOfRange, AlertState::Raised),
+                (HealthIssue::OutOfRange, AlertState::Cleared),
+            ]
+        );
+        assert_eq!(events[2].severity, Severity::Critical);
+        assert_eq!(events[2].value, 850.0);
+    }
+
+    #[test]
+    fn detects_stuck_sensor_until_value_changes() {
+        let mut monitor = HealthMonitor::new("sensor0", config());
+        let mut events = Vec::new();
+        for i in 0..8 {
+            // 卡死的读数仍然有效，只是被标记
+            assert!(monitor.check(&reading(i, 25.0), &mut events));
+        }
+        assert_eq!(issues(&events), [(HealthIssue::Stuck, AlertState::Raised)]);
+
+        monitor.check(&reading(8, 25.3), &mut events);
+        assert_eq!(events[1].state, AlertState::Cleared);
+    }
+
+    #[test]
+    fn detects_dropouts_from_timestamps() {
+        let mut monitor = HealthMonitor::new("sensor0", config());
+        let mut events = Vec::new();
+        for i in 0..10 {
+            monitor.check(&reading(i, 25.0 + i as f32 * 0.1), &mut events);
+        }
+        // 0.4s 没有新读数还在 5 倍间隔以内
+        monitor.check_silence(1_300_000_000, &mut events);
+        assert!(events.is_empty());
+
+        monitor.check_silence(2_000_000_000, &mut events);
+        monitor.check_silence(2_500_000_000, &mut events);
+        assert_eq!(
+            issues(&events),
+            [(HealthIssue::Dropout, AlertState::Raised)]
+        );
+        assert!((events[0].value - 1.1).abs() < 1e-6);
+
+        monitor.check(&reading(30, 26.0), &mut events);
+        assert_eq!(events[1].state, AlertState::Cleared);
+
+        // 没有提前发现的中断在恢复时补发一对事件
+        events.clear();
+        monitor.check(&reading(60, 26.1), &mut events);
+        assert_eq!(
+            issues(&events),
+            [
+                (HealthIssue::Dropout, AlertState::Raised),
+                (HealthIssue::Dropout, AlertState::Cleared),
+            ]
+        );
+    }
+
+    #[test]
+    fn batch_issues_latch_until_a_good_batch() {
+        let mut monitor = BatchMonitor::default();
+        let mut events = Vec::new();
+        let empty = || Some((HealthIssue::EmptyBatch, "空批次".to_owned()));
+
+        monitor.update(0, empty(), &mut events);
+        monitor.update(1, empty(), &mut events);
+        monitor.update(2, None, &mut events);
+        monitor.update(3, None, &mut events);
+
+        assert_eq!(
+            issues(&events),
+            [
+                (HealthIssue::EmptyBatch, AlertState::Raised),
+                (HealthIssue::EmptyBatch, AlertState::Cleared),
+            ]
+        );
+        assert_eq!(events[0].sensor_id, BATCH_SENSOR_ID);
+    }
+}
@@ -1,90 +1,771 @@
-use dora_node_api::{
-    arrow::array::{Float32Array, StringArray},
-    dora_core::config::DataId,
-    DoraNode, Event,
-};
-use std::collections::VecDeque;
+use dora_node_api::arrow::array::{ArrayRef, StructArray};
+use dora_node_api::{ArrowData, MetadataParameters, Parameter};
+use node_harness::{NodeHandler, Outputs};
+use std::collections::HashMap;
 use std::error::Error;
+use std::sync::Arc;
+use temp_common::alert::{alerts_to_arrow, Alert};
+use temp_common::config;
+use temp_common::forecast::{forecasts_to_arrow, Forecast};
+use temp_common::health::{health_to_arrow, HealthEvent, HealthIssue};
+use temp_common::reading::{arrow_to_readings, readings_to_arrow, Reading};
+use temp_common::units::{
+    set_source_units, source_units_param, unit_param, Unit, SOURCE_UNITS_PARAM, UNIT_PARAM,
+};
+
+mod alert;
+mod filter;
+mod forecast;
+mod health;
+mod reorder;
+
+use alert::{AlertConfig, AlertEngine};
+use filter::{Filter, FilterConfig};
+use forecast::{ForecastConfig, Forecaster};
+use health::{BatchMonitor, HealthConfig, HealthMonitor};
+use reorder::ReorderBuffer;
+
+/// 单个传感器的数据质量、滤波、预测与告警状态
+struct SensorState {
+    health: HealthMonitor,
+    filter: Box<dyn Filter>,
+    forecaster: Option<Box<dyn Forecaster>>,
+    alerts: AlertEngine,
+    /// 首个读数的时间，预测器使用相对秒数以保证精度
+    origin_ns: u64,
+}
+
+/// 一批读数的处理结果
+#[derive(Default)]
+struct BatchOutput {
+    smoothed: Vec<Reading>,
+    forecasts: Vec<Forecast>,
+    alerts: Vec<Alert>,
+    health: Vec<HealthEvent>,
+}
+
+/// 处理器节点可热加载的配置，从配置文件和 dataflow.yml 中的 `env:` 读取并校验
+#[derive(Debug, Clone, PartialEq)]
+struct ProcessorConfig {
+    filter: FilterConfig,
+    forecast: Option<ForecastConfig>,
+    alert: AlertConfig,
+    health: HealthConfig,
+}
 
+impl ProcessorConfig {
+    fn from_env() -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            filter: FilterConfig::from_env()?,
+            forecast: ForecastConfig::from_env()?,
+            alert: AlertConfig::from_env()?,
+            health: HealthConfig::from_env()?,
+        })
+    }
+}
+
+/// 按传感器 ID 维护各自独立的滤波器、预测器和告警引擎，新传感器首次出现时自动创建
 struct TemperatureProcessor {
-    window: VecDeque<f32>,
-    threshold: f32, // 异常阈值
+    filter_config: FilterConfig,
+    forecast_config: Option<ForecastConfig>,
+    alert_config: AlertConfig,
+    health_config: HealthConfig,
+    sensors: HashMap<String, SensorState>,
+    batch_health: BatchMonitor,
+    reorder: ReorderBuffer,
 }
 
 impl TemperatureProcessor {
-    fn new(window_size: usize, threshold: f32) -> Self {
+    fn new(
+        filter_config: FilterConfig,
+        forecast_config: Option<ForecastConfig>,
+        alert_config: AlertConfig,
+        health_config: HealthConfig,
+        reorder: ReorderBuffer,
+    ) -> Self {
         Self {
-            window: VecDeque::with_capacity(window_size),
-            threshold,
+            filter_config,
+            forecast_config,
+            alert_config,
+            health_config,
+            sensors: HashMap::new(),
+            batch_health: BatchMonitor::default(),
+            reorder,
+        }
+    }
+
+    /// 应用热加载的配置：告警阈值和数据质量参数保留各传感器的当前状态，
+    /// 滤波器或预测器的参数变化时重新创建，平滑值和预测从新的读数重新积累
+    fn reconfigure(&mut self, config: ProcessorConfig) -> Result<(), Box<dyn Error>> {
+        let filter_changed = config.filter != self.filter_config;
+        let forecast_changed = config.forecast != self.forecast_config;
+        for state in self.sensors.values_mut() {
+            if filter_changed {
+                state.filter = config.filter.build()?;
+            }
+            if forecast_changed {
+                state.forecaster = config.forecast.map(|c| c.build()).transpose()?;
+            }
+            state.alerts.set_config(config.alert.clone());
+            state.health.set_config(config.health);
         }
+        self.filter_config = config.filter;
+        self.forecast_config = config.forecast;
+        self.alert_config = config.alert;
+        self.health_config = config.health;
+        Ok(())
     }
 
-    fn process(&mut self, temp: f32) -> (f32, Option<String>) {
-        self.window.push_back(temp);
-        if self.window.len() > 10 {
-            self.window.pop_front();
+    /// 处理一个读数，把平滑值、预测值（样本足够时）和需要发出的告警记录追加到 `output`
+    fn process(
+        &mut self,
+        reading: &Reading,
+        output: &mut BatchOutput,
+    ) -> Result<(), Box<dyn Error>> {
+        if !self.sensors.contains_key(&reading.sensor_id) {
+            println!("📡 发现新传感器: {}", reading.sensor_id);
+            let state = SensorState {
+                health: HealthMonitor::new(&reading.sensor_id, self.health_config),
+                filter: self.filter_config.build()?,
+                forecaster: self.forecast_config.map(|c| c.build()).transpose()?,
+                alerts: AlertEngine::new(&reading.sensor_id, self.alert_config.clone()),
+                origin_ns: reading.timestamp_ns,
+            };
+            self.sensors.insert(reading.sensor_id.clone(), state);
+        }
+        let state = self
+            .sensors
+            .get_mut(&reading.sensor_id)
+            .expect("sensor state was just inserted");
+
+        // 无效读数只发出数据质量事件，不进入滤波器，以免污染平均值
+        if !state.health.check(reading, &mut output.health) {
+            return Ok(());
         }
 
-        let avg = self.window.iter().sum::<f32>() / self.window.len() as f32;
+        let avg = state.filter.update(reading.value);
+
+        // 基于平滑值做短期预测
+        let forecast = match (&mut state.forecaster, &self.forecast_config) {
+            (Some(forecaster), Some(config)) => {
+                let t = reading.timestamp_ns.saturating_sub(state.origin_ns) as f64 / 1e9;
+                forecaster.update(t, avg);
+                forecaster
+                    .predict(config.horizon)
+                    .map(|(value, std_err)| Forecast {
+                        timestamp_ns: reading.timestamp_ns,
+                        sensor_id: reading.sensor_id.clone(),
+                        horizon_s: config.horizon as f32,
+                        value,
+                        lower: value - config.z * std_err,
+                        upper: value + config.z * std_err,
+                    })
+            }
+            _ => None,
+        };
 
         // 异常检测逻辑
-        let alert = if (temp - avg).abs() > self.threshold {
-            Some(format!(
-                "⚠️ 温度突变: {:.1}°C (偏离均值{:.1}°C)",
-                temp,
-                (temp - avg).abs()
-            ))
-        } else {
-            None
+        let alerts =
+            state
+                .alerts
+                .evaluate(reading.timestamp_ns, reading.value, avg, forecast.as_ref());
+
+        output.smoothed.push(Reading {
+            value: avg,
+            ..reading.clone()
+        });
+        output.forecasts.extend(forecast);
+        output.alerts.extend(alerts);
+        Ok(())
+    }
+
+    /// 处理一批读数，返回平滑后的读数批次、预测和本批次产生的全部告警
+    fn process_batch(&mut self, readings: &[Reading]) -> Result<BatchOutput, Box<dyn Error>> {
+        let mut output = BatchOutput::default();
+        for reading in readings {
+            self.process(reading, &mut output)?;
+        }
+        Ok(output)
+    }
+
+    /// 处理收到的一个输入：`decoded` 为解码结果，空批次和解码失败记为批次级数据质量问题
+    ///
+    /// 读数先经过重排缓存，按每个传感器的时间顺序处理，迟到的读数丢弃
+    fn receive(
+        &mut self,
+        received_ns: u64,
+        decoded: Result<Vec<Reading>, String>,
+    ) -> Result<BatchOutput, Box<dyn Error>> {
+        let (mut output, issue) = match decoded {
+            Ok(readings) if readings.is_empty() => (
+                BatchOutput::default(),
+                Some((HealthIssue::EmptyBatch, "收到空的读数批次".to_owned())),
+            ),
+            Ok(readings) => {
+                let (ready, late) = self.reorder.push(received_ns, readings);
+                if late > 0 {
+                    eprintln!("丢弃 {late} 个迟到的读数");
+                }
+                (self.process_batch(&ready)?, None)
+            }
+            Err(e) => (
+                BatchOutput::default(),
+                Some((HealthIssue::Malformed, format!("无法解码读数批次: {e}"))),
+            ),
         };
+        self.batch_health
+            .update(received_ns, issue, &mut output.health);
+        Ok(output)
+    }
 
-        (avg, alert)
+    /// 定时调用：处理重排缓存中到期的读数，并检查所有已知传感器是否长时间没有新读数
+    fn tick(&mut self, now_ns: u64) -> Result<BatchOutput, Box<dyn Error>> {
+        let ready = self.reorder.flush(now_ns);
+        let mut output = self.process_batch(&ready)?;
+        for state in self.sensors.values_mut() {
+            state.health.check_silence(now_ns, &mut output.health);
+        }
+        Ok(output)
     }
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let (mut node, mut events) = DoraNode::init_from_env()?;
-    let output_temp_smoothed = DataId::from("temp_smoothed".to_owned());
-    let output_temp_alert = DataId::from("temp_smootemp_alertthed".to_owned());
-    println!("🧮 处理器节点启动 (滑动窗口+异常检测)");
-
-    let mut processor = TemperatureProcessor::new(10, 3.0);
-
-    while let Some(event) = events.recv() {
-        match event {
-            Event::Input { id, metadata, data } => match id.as_str() {
-                "temp" => {
-                    let array = data
-                        .as_any()
-                        .downcast_ref::<Float32Array>()
-                        .ok_or("类型转换失败")?;
-                    let temp = array.value(0) as f32;
-
-                    let (avg, alert) = processor.process(temp);
-
-                    // 发送平滑数据
-                    let avg_array = Float32Array::from(vec![avg]);
-                    node.send_output(
-                        output_temp_smoothed.clone(),
-                        metadata.parameters.clone(),
-                        avg_array,
-                    )?;
-
-                    // 如果有异常，发送警报
-                    if let Some(alert_msg) = alert {
-                        let alert_array = StringArray::from(vec![alert_msg]);
-                        node.send_output(
-                            output_temp_alert.clone(),
-                            metadata.parameters,
-                            alert_array,
-                        )?;
+/// 把输入解码为读数批次，失败时返回可读的原因而不是中止节点
+///
+/// 上游通过 `unit` 参数声明非摄氏度单位时在这里换算，并在 `parameters` 中
+/// 把 `unit` 改为摄氏度、补上各传感器的 `source_units`，供下游节点使用
+fn decode_readings(
+    data: &ArrayRef,
+    parameters: &mut MetadataParameters,
+) -> Result<Vec<Reading>, String> {
+    let array = data
+        .as_any()
+        .downcast_ref::<StructArray>()
+        .ok_or_else(|| format!("数据类型为 {}，不是 StructArray", data.data_type()))?;
+    let mut readings = arrow_to_readings(array).map_err(|e| e.to_string())?;
+
+    let unit = unit_param(parameters).map_err(|e| e.to_string())?;
+    if unit != Unit::Celsius {
+        if unit == Unit::Raw {
+            return Err("读数为未标定的原始计数，请在传感器节点配置 CALIBRATION_FILE".into());
+        }
+        for reading in &mut readings {
+            reading.value = unit.to_celsius(reading.value as f64).unwrap_or_default() as f32;
+        }
+        if !parameters.contains_key(SOURCE_UNITS_PARAM) {
+            let mut units: Vec<(String, Unit)> = readings
+                .iter()
+                .map(|r| (r.sensor_id.clone(), unit))
+                .collect();
+            units.sort_by(|a, b| a.0.cmp(&b.0));
+            units.dedup();
+            set_source_units(parameters, &units);
+        }
+    } else {
+        // 尽早暴露格式错误的 `source_units`
+        source_units_param(parameters).map_err(|e| e.to_string())?;
+    }
+    parameters.insert(
+        UNIT_PARAM.to_owned(),
+        Parameter::String(Unit::Celsius.as_str().to_owned()),
+    );
+    Ok(readings)
+}
+
+/// 处理器节点：`temp` 批次交给 `TemperatureProcessor` 并发送各类输出，`tick` 时处理重排缓存中
+/// 到期的读数并检查断流的传感器
+struct ProcessorNode {
+    processor: TemperatureProcessor,
+    /// 当前时间（Unix 纳秒），测试中替换为固定时钟
+    clock: fn() -> u64,
+    /// 最近一个 `temp` 批次换算后的元数据参数，`tick` 时的输出沿用
+    parameters: MetadataParameters,
+}
+
+impl ProcessorNode {
+    fn send(
+        output: BatchOutput,
+        parameters: &MetadataParameters,
+        outputs: &mut dyn Outputs,
+    ) -> Result<(), Box<dyn Error>> {
+        // 发送平滑数据，整批读数都无效时不发送
+        if !output.smoothed.is_empty() {
+            outputs.send(
+                temp_common::TEMP_SMOOTHED,
+                parameters.clone(),
+                Arc::new(readings_to_arrow(&output.smoothed)),
+            )?;
+        }
+
+        // 数据质量问题出现或恢复时发送
+        if !output.health.is_empty() {
+            outputs.send(
+                temp_common::SENSOR_HEALTH,
+                parameters.clone(),
+                Arc::new(health_to_arrow(&output.health)),
+            )?;
+        }
+
+        // 预测器积累到足够样本后发送预测
+        if !output.forecasts.is_empty() {
+            outputs.send(
+                temp_common::TEMP_FORECAST,
+                parameters.clone(),
+                Arc::new(forecasts_to_arrow(&output.forecasts)),
+            )?;
+        }
+
+        // 如果有告警状态变化，发送结构化告警记录
+        if !output.alerts.is_empty() {
+            outputs.send(
+                temp_common::TEMP_ALERT,
+                parameters.clone(),
+                Arc::new(alerts_to_arrow(&output.alerts)),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl NodeHandler for ProcessorNode {
+    fn on_input(
+        &mut self,
+        id: &str,
+        parameters: MetadataParameters,
+        data: ArrowData,
+        outputs: &mut dyn Outputs,
+    ) -> Result<(), Box<dyn Error>> {
+        match id {
+            "temp" => {
+                let mut parameters = parameters;
+                let decoded = decode_readings(&data, &mut parameters);
+                if decoded.is_ok() {
+                    self.parameters = parameters.clone();
+                }
+                let output = self.processor.receive((self.clock)(), decoded)?;
+                Self::send(output, &parameters, outputs)?;
+            }
+            // 配置文件修改后热加载，新配置无效时继续使用之前的配置
+            "reload" => match config::reload_if_changed() {
+                Ok(true) => {
+                    match ProcessorConfig::from_env().and_then(|c| self.processor.reconfigure(c)) {
+                        Ok(()) => println!("🔄 已重新加载配置"),
+                        Err(e) => eprintln!("配置无效，继续使用之前的配置: {e}"),
                     }
                 }
-                _ => {}
+                Ok(false) => {}
+                Err(e) => eprintln!("重新加载配置失败: {e}"),
             },
-            _ => {}
+            // 定时处理缓存的读数、检查断流的传感器，不依赖其他传感器的批次
+            "tick" => {
+                let output = self.processor.tick((self.clock)())?;
+                Self::send(output, &self.parameters, outputs)?;
+            }
+            other => eprintln!("Received input `{other}`"),
         }
+        Ok(())
     }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    config::load_file("processor")?;
+    let config = ProcessorConfig::from_env()?;
+    let forecast_name = match &config.forecast {
+        Some(forecast) => format!("+{} {}s 预测", forecast.build()?.name(), forecast.horizon),
+        None => String::new(),
+    };
+    println!(
+        "🧮 处理器节点启动 ({}{forecast_name}+异常检测)",
+        config.filter.build()?.name()
+    );
+
+    let processor = TemperatureProcessor::new(
+        config.filter,
+        config.forecast,
+        config.alert,
+        config.health,
+        ReorderBuffer::from_env()?,
+    );
+    node_harness::run(&mut ProcessorNode {
+        processor,
+        clock: temp_common::now_ns,
+        parameters: MetadataParameters::new(),
+    })
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forecast::ForecastMethod;
+    use node_harness::{FakeNode, Input};
+    use std::path::Path;
+    use temp_common::alert::{arrow_to_alerts, AlertKind, AlertState, Severity};
+    use temp_common::forecast::arrow_to_forecasts;
+    use temp_common::health::arrow_to_health;
+
+    fn processor() -> TemperatureProcessor {
+        let filter_config = FilterConfig::MovingAverage { window_size: 10 };
+        TemperatureProcessor::new(
+            filter_config,
+            None,
+            AlertConfig::default(),
+            HealthConfig::default(),
+            ReorderBuffer::new(0),
+        )
+    }
+
+    /// dataflow.yml 中 `node` 的输入 → 数据来源 `node/output`，以及声明的输出
+    fn wiring(node: &str) -> (Vec<(String, String)>, Vec<String>) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../dataflow.yml");
+        let dataflow: serde_yaml::Value =
+            serde_yaml::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        let node = dataflow["nodes"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .find(|n| n["id"].as_str() == Some(node))
+            .unwrap();
+        let inputs = node["inputs"]
+            .as_mapping()
+            .map(|inputs| {
+                inputs
+                    .iter()
+                    .map(|(k, v)| {
+                        (
+                            k.as_str().unwrap().to_owned(),
+                            v.as_str().unwrap().to_owned(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let outputs = node["outputs"]
+            .as_sequence()
+            .map(|o| o.iter().map(|o| o.as_str().unwrap().to_owned()).collect())
+            .unwrap_or_default();
+        (inputs, outputs)
+    }
+
+    #[test]
+    fn spike_alert_reaches_logger_through_dataflow_wiring() {
+        // 处理器只能发送 dataflow.yml 中声明的输出，未声明的输出会被 dora 丢弃
+        let (_, declared) = wiring("data_processor");
+        let declared: Vec<&str> = declared.iter().map(String::as_str).collect();
+        let mut node = ProcessorNode {
+            processor: processor(),
+            clock: || 0,
+            parameters: MetadataParameters::new(),
+        };
+        let mut script = FakeNode::with_outputs(&declared);
+
+        // 平稳 25°C 后持续的 +8°C 偏离，超过默认 3°C 阈值和 0.3s 最短持续时间
+        for i in 0..30u64 {
+            let temp = if i < 20 { 25.0 } else { 33.0 };
+            script.push(Input::new(
+                "temp",
+                readings_to_arrow(&[Reading::new(i * 100_000_000, "sensor0", temp)]),
+            ));
+        }
+        let sent = script.run(&mut node).unwrap();
+
+        // 按 logger 的连线把处理器的输出投递到对应输入，`alert` 输入按 logger 的方式解码
+        let (logger_inputs, _) = wiring("logger");
+        let mut received: Vec<Alert> = Vec::new();
+        for output in &sent {
+            let source = format!("data_processor/{}", output.id);
+            for (input, _) in logger_inputs.iter().filter(|(_, s)| *s == source) {
+                if input == "alert" {
+                    let array = output.downcast::<StructArray>().unwrap();
+                    received.extend(arrow_to_alerts(array).unwrap());
+                }
+            }
+        }
+
+        let deviation = received
+            .iter()
+            .find(|alert| alert.kind == AlertKind::Deviation)
+            .expect("deviation alert should arrive at the logger");
+        assert_eq!(deviation.sensor_id, "sensor0");
+        assert_eq!(deviation.severity, Severity::Warning);
+        assert_eq!(deviation.state, AlertState::Raised);
+        assert_eq!(deviation.value, 33.0);
+        assert!(deviation.message.contains("温度突变"));
+    }
+
+    #[test]
+    fn sensors_are_smoothed_and_alerted_independently() {
+        let mut processor = processor();
+
+        // sensor0 稳定在 25°C，sensor1 稳定在 30°C 后突升；两者交错在同一批次中
+        let mut alerts = Vec::new();
+        for i in 0..30u64 {
+            let ts = i * 100_000_000;
+            let hot = if i < 20 { 30.0 } else { 38.0 };
+            let batch = [
+                Reading::new(ts, "sensor0", 25.0),
+                Reading::new(ts, "sensor1", hot),
+            ];
+            let mut output = processor.process_batch(&batch).unwrap();
+            assert_eq!(output.smoothed[0].sensor_id, "sensor0");
+            assert_eq!(output.smoothed[0].value, 25.0);
+            assert_eq!(output.smoothed[1].timestamp_ns, ts);
+            alerts.append(&mut output.alerts);
+        }
+
+        assert!(!alerts.is_empty());
+        assert!(alerts.iter().all(|alert| alert.sensor_id == "sensor1"));
+        assert_eq!(processor.sensors.len(), 2);
+    }
+
+    #[test]
+    fn rising_trend_is_forecast_and_predicted_before_high_alert() {
+        let forecast_config = ForecastConfig {
+            method: ForecastMethod::LeastSquares { window: 50 },
+            horizon: 30.0,
+            z: 1.96,
+        };
+        let mut processor = TemperatureProcessor::new(
+            FilterConfig::MovingAverage { window_size: 10 },
+            Some(forecast_config),
+            AlertConfig::default(),
+            HealthConfig::default(),
+            ReorderBuffer::new(0),
+        );
+
+        // 从 25°C 起每秒升温 0.2°C，约 50s 后越过 35°C 的默认上限警告
+        let mut forecasts = Vec::new();
+        let mut alerts = Vec::new();
+        for i in 0..700u64 {
+            let reading = Reading::new(i * 100_000_000, "sensor0", 25.0 + 0.02 * i as f32);
+            let mut output = processor.process_batch(&[reading]).unwrap();
+            if !output.forecasts.is_empty() {
+                forecasts
+                    .extend(arrow_to_forecasts(&forecasts_to_arrow(&output.forecasts)).unwrap());
+            }
+            alerts.append(&mut output.alerts);
+        }
+
+        let last = forecasts.last().unwrap();
+        assert_eq!(last.horizon_s, 30.0);
+        // 平滑值滞后约 0.45s，69.9s 时约 38.89°C，30s 后约 44.9°C
+        assert!((last.value - 44.9).abs() < 0.1, "{}", last.value);
+        assert!(last.lower <= last.value && last.value <= last.upper);
+
+        let first = |kind| {
+            alerts
+                .iter()
+                .position(|a| a.kind == kind && a.state == AlertState::Raised)
+                .unwrap()
+        };
+        assert!(first(AlertKind::Predicted) < first(AlertKind::High));
+    }
+
+    #[test]
+    fn invalid_readings_and_batches_are_reported_without_polluting_average() {
+        let mut processor = processor();
+        let batch = |i: u64, value: f32| Ok(vec![Reading::new(i * 100_000_000, "sensor0", value)]);
+
+        let mut smoothed = Vec::new();
+        let mut health = Vec::new();
+        let inputs = [
+            batch(0, 25.0),
+            batch(1, f32::NAN),
+            Ok(Vec::new()),
+            Err("Missing or incorrect `value` column".to_owned()),
+            batch(2, -273.0),
+            batch(3, 27.0),
+        ];
+        for (i, decoded) in inputs.into_iter().enumerate() {
+            let mut output = processor.receive(i as u64, decoded).unwrap();
+            smoothed.append(&mut output.smoothed);
+            health.append(&mut output.health);
+        }
+
+        let values: Vec<f32> = smoothed.iter().map(|r| r.value).collect();
+        assert_eq!(values, [25.0, 26.0]);
+        let raised: Vec<HealthIssue> = health
+            .iter()
+            .filter(|e| e.state == AlertState::Raised)
+            .map(|e| e.issue)
+            .collect();
+        assert_eq!(
+            raised,
+            [
+                HealthIssue::NonFinite,
+                HealthIssue::EmptyBatch,
+                HealthIssue::Malformed,
+                HealthIssue::OutOfRange,
+            ]
+        );
+        // 每个问题都在之后的正常读数或批次中解除
+        let cleared = health
+            .iter()
+            .filter(|e| e.state == AlertState::Cleared)
+            .count();
+        assert_eq!(cleared, raised.len());
+    }
+
+    #[test]
+    fn batched_out_of_order_samples_are_processed_in_time_order() {
+        let ms = 1_000_000;
+        let mut processor = TemperatureProcessor::new(
+            FilterConfig::MovingAverage { window_size: 10 },
+            None,
+            AlertConfig::default(),
+            HealthConfig::default(),
+            ReorderBuffer::new(50 * ms),
+        );
+
+        // 1 kHz 采样的两个传感器，每 100ms 一批且批内倒序；sensor0 每批最后 5 个样本推迟到下一批
+        let mut smoothed = Vec::new();
+        for batch in 0..5u64 {
+            let ts = |i: u64| (batch * 100 + i) * ms;
+            let mut readings: Vec<Reading> = (0..100u64)
+                .rev()
+                .map(|i| Reading::new(ts(i), "sensor1", 30.0))
+                .chain((0..95).rev().map(|i| Reading::new(ts(i), "sensor0", 25.0)))
+                .collect();
+            if batch > 0 {
+                readings.extend((95..100).map(|i| Reading::new(ts(i) - 100 * ms, "sensor0", 25.0)));
+            }
+            let received = (batch + 1) * 100 * ms;
+            let mut output = processor.receive(received, Ok(readings)).unwrap();
+            smoothed.append(&mut output.smoothed);
+        }
+        // 窗口内的样本未丢失；tick 释放最后一批缓存的读数，但早于已处理读数的样本被丢弃
+        smoothed.append(&mut processor.tick(700 * ms).unwrap().smoothed);
+        processor
+            .receive(800 * ms, Ok(vec![Reading::new(10 * ms, "sensor1", 99.0)]))
+            .unwrap();
+        assert!(processor.tick(900 * ms).unwrap().smoothed.is_empty());
+
+        for (sensor_id, count) in [("sensor0", 500 - 5), ("sensor1", 500)] {
+            let times: Vec<u64> = smoothed
+                .iter()
+                .filter(|r| r.sensor_id == sensor_id)
+                .map(|r| r.timestamp_ns)
+                .collect();
+            assert!(times.windows(2).all(|w| w[0] < w[1]), "{sensor_id}");
+            assert_eq!(times.len(), count, "{sensor_id}");
+        }
+    }
+
+    #[test]
+    fn reloaded_config_applies_to_existing_sensors() {
+        let mut processor = processor();
+        let feed = |processor: &mut TemperatureProcessor, from: u64, value: f32| {
+            let mut output = BatchOutput::default();
+            for i in from..from + 10 {
+                let reading = Reading::new(i * 100_000_000, "sensor0", value);
+                let mut batch = processor.process_batch(&[reading]).unwrap();
+                output.smoothed.append(&mut batch.smoothed);
+                output.alerts.append(&mut batch.alerts);
+            }
+            output
+        };
+        // 30°C 低于默认 35°C 的上限警告
+        assert!(feed(&mut processor, 0, 30.0).alerts.is_empty());
+
+        let mut config = ProcessorConfig {
+            filter: FilterConfig::MovingAverage { window_size: 10 },
+            forecast: None,
+            alert: AlertConfig::default(),
+            health: HealthConfig::default(),
+        };
+        config.alert.high.warning = Some(28.0);
+        processor.reconfigure(config.clone()).unwrap();
+        let output = feed(&mut processor, 10, 30.0);
+        assert!(output
+            .alerts
+            .iter()
+            .any(|a| a.kind == AlertKind::High && a.state == AlertState::Raised));
+        // 滤波器参数未变，平滑值继续累积
+        assert_eq!(output.smoothed[0].value, 30.0);
+
+        // 滤波器参数变化后重新创建，不再受之前读数的影响
+        config.filter = FilterConfig::MovingAverage { window_size: 2 };
+        processor.reconfigure(config).unwrap();
+        assert_eq!(feed(&mut processor, 20, 20.0).smoothed[0].value, 20.0);
+    }
+
+    #[test]
+    fn fahrenheit_input_is_normalised_and_unit_recorded() {
+        let readings = [
+            Reading::new(0, "a", 212.0),
+            Reading::new(0, "b", 32.0),
+            Reading::new(1, "a", 50.0),
+        ];
+        let data: ArrayRef = std::sync::Arc::new(readings_to_arrow(&readings));
+
+        let mut parameters = MetadataParameters::new();
+        parameters.insert(
+            UNIT_PARAM.to_owned(),
+            Parameter::String("fahrenheit".into()),
+        );
+        let values: Vec<f32> = decode_readings(&data, &mut parameters)
+            .unwrap()
+            .iter()
+            .map(|r| r.value)
+            .collect();
+        assert_eq!(values, [100.0, 0.0, 10.0]);
+        assert_eq!(unit_param(&parameters).unwrap(), Unit::Celsius);
+        assert_eq!(
+            source_units_param(&parameters).unwrap(),
+            [
+                ("a".to_owned(), Unit::Fahrenheit),
+                ("b".to_owned(), Unit::Fahrenheit)
+            ]
+        );
+
+        parameters.insert(UNIT_PARAM.to_owned(), Parameter::String("raw".into()));
+        assert!(decode_readings(&data, &mut parameters).is_err());
+    }
+
+    #[test]
+    fn scripted_fahrenheit_stream_is_smoothed_alerted_and_checked_for_silence() {
+        let mut node = ProcessorNode {
+            processor: processor(),
+            // 最后一个读数 2.9s 之后，远超 5 倍的 0.1s 采样间隔
+            clock: || 5_000_000_000,
+            parameters: MetadataParameters::new(),
+        };
+        let mut script = FakeNode::with_outputs(&[
+            temp_common::TEMP_SMOOTHED,
+            temp_common::TEMP_ALERT,
+            temp_common::TEMP_FORECAST,
+            temp_common::SENSOR_HEALTH,
+        ]);
+        // 77°F (25°C) 平稳后升到 91.4°F (33°C)
+        for i in 0..30u64 {
+            let temp = if i < 20 { 77.0 } else { 91.4 };
+            let batch = readings_to_arrow(&[Reading::new(i * 100_000_000, "sensor0", temp)]);
+            script.push(
+                Input::new("temp", batch)
+                    .with_parameter(UNIT_PARAM, Parameter::String("fahrenheit".into())),
+            );
+        }
+        script.push(Input::tick("tick"));
+        let sent = script.run(&mut node).unwrap();
+
+        let smoothed = sent
+            .iter()
+            .find(|o| o.id == temp_common::TEMP_SMOOTHED)
+            .unwrap();
+        assert_eq!(unit_param(&smoothed.parameters).unwrap(), Unit::Celsius);
+        let first = arrow_to_readings(smoothed.downcast::<StructArray>().unwrap()).unwrap();
+        assert_eq!(first[0].value, 25.0);
+
+        let alerts: Vec<Alert> = sent
+            .iter()
+            .filter(|o| o.id == temp_common::TEMP_ALERT)
+            .flat_map(|o| arrow_to_alerts(o.downcast::<StructArray>().unwrap()).unwrap())
+            .collect();
+        assert!(alerts.iter().any(|a| a.kind == AlertKind::Deviation));
+
+        // 最后的 tick 发现断流
+        let last = sent.last().unwrap();
+        assert_eq!(last.id, temp_common::SENSOR_HEALTH);
+        let events = arrow_to_health(last.downcast::<StructArray>().unwrap()).unwrap();
+        assert_eq!(events[0].issue, HealthIssue::Dropout);
+        assert_eq!(events[0].timestamp_ns, 5_000_000_000);
+    }
 }
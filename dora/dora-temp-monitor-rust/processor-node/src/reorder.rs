@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::error::Error;
+use temp_common::config::{env_or, parse_duration};
+use temp_common::reading::Reading;
+
+/// 把批量、乱序到达的读数整理为每个传感器按时间升序的读数流
+///
+/// 读数先按采样时间戳缓存 `window_ns`：同一传感器出现更新 `window_ns` 以上的读数，
+/// 或缓存时间（按收到时刻计）超过 `window_ns` 后释放。早于该传感器已释放读数的迟到读数丢弃，
+/// 以免滤波器、预测器和告警引擎看到倒退的时间。窗口为 0 时只在批次内排序
+pub struct ReorderBuffer {
+    window_ns: u64,
+    /// 待释放的 `(读数, 收到时刻)`，按时间戳升序，时间戳相同的保持到达顺序
+    pending: Vec<(Reading, u64)>,
+    /// 传感器 ID → (收到的最新时间戳, 已释放的最新时间戳)
+    sensors: HashMap<String, (u64, Option<u64>)>,
+}
+
+impl ReorderBuffer {
+    pub fn new(window_ns: u64) -> Self {
+        Self {
+            window_ns,
+            pending: Vec::new(),
+            sensors: HashMap::new(),
+        }
+    }
+
+    /// 从 `REORDER_WINDOW` 环境变量读取缓存窗口，例如 `200ms`，默认 0
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let window = parse_duration(&env_or("REORDER_WINDOW", "0".to_owned())?)?;
+        Ok(Self::new(window.as_nanos() as u64))
+    }
+
+    /// 计入 `received_ns` 时刻收到的一批读数，返回可以处理的读数和丢弃的迟到读数个数
+    pub fn push(&mut self, received_ns: u64, readings: Vec<Reading>) -> (Vec<Reading>, usize) {
+        let mut late = 0;
+        for reading in readings {
+            let (latest, released) = self
+                .sensors
+                .entry(reading.sensor_id.clone())
+                .or_insert((reading.timestamp_ns, None));
+            if released.is_some_and(|t| reading.timestamp_ns < t) {
+                late += 1;
+                continue;
+            }
+            *latest = (*latest).max(reading.timestamp_ns);
+            let at = self
+                .pending
+                .partition_point(|(r, _)| r.timestamp_ns <= reading.timestamp_ns);
+            self.pending.insert(at, (reading, received_ns));
+        }
+        (self.release(received_ns), late)
+    }
+
+    /// 释放缓存时间超过窗口的读数，`tick` 时调用，使停发的传感器最后的读数也能得到处理
+    pub fn flush(&mut self, now_ns: u64) -> Vec<Reading> {
+        self.release(now_ns)
+    }
+
+    fn release(&mut self, now_ns: u64) -> Vec<Reading> {
+        // 某个读数到期时，同一传感器更早的读数一并释放，保证每个传感器的输出不倒退
+        let mut cutoff: HashMap<String, u64> = HashMap::new();
+        for (reading, received_ns) in &self.pending {
+            let latest = self.sensors[&reading.sensor_id].0;
+            let due = reading.timestamp_ns.saturating_add(self.window_ns) <= latest
+                || received_ns.saturating_add(self.window_ns) <= now_ns;
+            if due {
+                cutoff.insert(reading.sensor_id.clone(), reading.timestamp_ns);
+            }
+        }
+
+        let mut ready = Vec::new();
+        let mut kept = Vec::with_capacity(self.pending.len());
+        for (reading, received_ns) in self.pending.drain(..) {
+            match cutoff.get(&reading.sensor_id) {
+                Some(&t) if reading.timestamp_ns <= t => {
+                    if let Some(state) = self.sensors.get_mut(&reading.sensor_id) {
+                        state.1 = Some(reading.timestamp_ns);
+                    }
+                    ready.push(reading);
+                }
+                _ => kept.push((reading, received_ns)),
+            }
+        }
+        self.pending = kept;
+        ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: u64 = 1_000_000;
+
+    fn values(readings: &[Reading]) -> Vec<f32> {
+        readings.iter().map(|r| r.value).collect()
+    }
+
+    #[test]
+    fn readings_are_released_in_time_order_and_late_ones_dropped() {
+        let mut buffer = ReorderBuffer::new(0);
+        // 窗口为 0 时批次内排序，相同时间戳的两个传感器保持到达顺序
+        let (ready, late) = buffer.push(
+            0,
+            vec![
+                Reading::new(2 * MS, "a", 2.0),
+                Reading::new(MS, "a", 1.0),
+                Reading::new(MS, "b", 10.0),
+            ],
+        );
+        assert_eq!(values(&ready), [1.0, 10.0, 2.0]);
+        assert_eq!(late, 0);
+        // 早于已处理读数的 a 被丢弃，b 不受其他传感器影响
+        let (ready, late) = buffer.push(
+            0,
+            vec![Reading::new(MS, "a", 1.5), Reading::new(MS, "b", 11.0)],
+        );
+        assert_eq!(values(&ready), [11.0]);
+        assert_eq!(late, 1);
+
+        let mut buffer = ReorderBuffer::new(100 * MS);
+        let (ready, _) = buffer.push(
+            0,
+            vec![Reading::new(0, "a", 0.0), Reading::new(50 * MS, "a", 5.0)],
+        );
+        assert!(ready.is_empty());
+        // 晚到一个批次的读数在窗口内，按时间插入
+        let (ready, late) = buffer.push(
+            10 * MS,
+            vec![
+                Reading::new(120 * MS, "a", 12.0),
+                Reading::new(30 * MS, "a", 3.0),
+            ],
+        );
+        assert_eq!(values(&ready), [0.0]);
+        assert_eq!(late, 0);
+        // 传感器停发后由 tick 按收到时刻释放
+        assert!(buffer.flush(50 * MS).is_empty());
+        assert_eq!(values(&buffer.flush(100 * MS)), [3.0, 5.0]);
+        assert_eq!(values(&buffer.flush(110 * MS)), [12.0]);
+    }
+}
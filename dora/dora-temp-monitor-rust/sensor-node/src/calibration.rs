@@ -0,0 +1,229 @@
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::time::Duration;
+use temp_common::units::Unit;
+
+use crate::source::TemperatureSource;
+
+fn unit<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Unit, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+fn celsius() -> Unit {
+    Unit::Celsius
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+/// 单个传感器的标定参数
+///
+/// 标定在传感器报告的单位下进行，之后换算为摄氏度；
+/// `raw` 原始计数必须配置多项式或查找表，其结果直接视为摄氏度
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SensorCalibration {
+    /// 传感器报告的单位
+    #[serde(default = "celsius", deserialize_with = "unit")]
+    pub unit: Unit,
+    /// 线性标定 `gain * x + offset`
+    #[serde(default = "one")]
+    pub gain: f64,
+    #[serde(default)]
+    pub offset: f64,
+    /// 多项式系数，从常数项开始：`c0 + c1*x + c2*x^2 + ...`
+    #[serde(default)]
+    pub polynomial: Option<Vec<f64>>,
+    /// 查找表 `[[原始值, 标定值], ...]`，按原始值递增，表内线性插值，表外按端点两段外推
+    #[serde(default)]
+    pub table: Option<Vec<[f64; 2]>>,
+}
+
+impl SensorCalibration {
+    fn validate(&self) -> Result<(), String> {
+        let linear = self.gain != 1.0 || self.offset != 0.0;
+        match (&self.polynomial, &self.table) {
+            (Some(_), Some(_)) => return Err("polynomial 和 table 只能配置一个".into()),
+            (Some(_), None) | (None, Some(_)) if linear => {
+                return Err("gain / offset 不能与 polynomial 或 table 同时使用".into())
+            }
+            (None, None) if self.unit == Unit::Raw => {
+                return Err("raw 单位需要配置 polynomial 或 table".into())
+            }
+            _ => {}
+        }
+        if self.polynomial.as_ref().is_some_and(|c| c.is_empty()) {
+            return Err("polynomial 至少需要一个系数".into());
+        }
+        if let Some(table) = &self.table {
+            if table.len() < 2 {
+                return Err("table 至少需要两行".into());
+            }
+            if table.windows(2).any(|w| w[1][0] <= w[0][0]) {
+                return Err("table 的原始值必须严格递增".into());
+            }
+        }
+        Ok(())
+    }
+
+    /// 标定并换算为摄氏度
+    pub fn apply(&self, raw: f32) -> f32 {
+        let x = raw as f64;
+        let calibrated = if let Some(coefficients) = &self.polynomial {
+            // 霍纳法
+            coefficients.iter().rev().fold(0.0, |acc, c| acc * x + c)
+        } else if let Some(table) = &self.table {
+            interpolate(table, x)
+        } else {
+            self.gain * x + self.offset
+        };
+        let celsius = match self.unit {
+            Unit::Raw => calibrated,
+            unit => unit.to_celsius(calibrated).unwrap_or(calibrated),
+        };
+        celsius as f32
+    }
+}
+
+fn interpolate(table: &[[f64; 2]], x: f64) -> f64 {
+    let i = table
+        .windows(2)
+        .position(|w| x < w[1][0])
+        .unwrap_or(table.len() - 2);
+    let ([x0, y0], [x1, y1]) = (table[i], table[i + 1]);
+    y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+}
+
+/// 标定文件：按传感器 ID（不含 `SENSOR_ID_PREFIX`）配置，未列出的传感器按摄氏度原样输出
+///
+/// ```yaml
+/// sensors:
+///   sensor0: { unit: fahrenheit }
+///   sensor1: { offset: -0.35, gain: 1.02 }
+///   adc0: { unit: raw, table: [[120, -10], [512, 25], [900, 80]] }
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CalibrationFile {
+    pub sensors: BTreeMap<String, SensorCalibration>,
+}
+
+impl CalibrationFile {
+    pub fn parse(yaml: &str) -> Result<Self, Box<dyn Error>> {
+        let file: Self = serde_yaml::from_str(yaml)?;
+        for (sensor_id, calibration) in &file.sensors {
+            calibration
+                .validate()
+                .map_err(|e| format!("传感器 {sensor_id}: {e}"))?;
+        }
+        Ok(file)
+    }
+
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let yaml = fs::read_to_string(path).map_err(|e| format!("读取 {path} 失败: {e}"))?;
+        Self::parse(&yaml).map_err(|e| format!("{path}: {e}").into())
+    }
+}
+
+/// 包装数据源，把各传感器的读数标定并换算为摄氏度
+pub struct Calibrated {
+    inner: Box<dyn TemperatureSource>,
+    path: String,
+    file: CalibrationFile,
+}
+
+impl Calibrated {
+    pub fn new(inner: Box<dyn TemperatureSource>, path: &str, file: CalibrationFile) -> Self {
+        Self {
+            inner,
+            path: path.to_owned(),
+            file,
+        }
+    }
+}
+
+impl TemperatureSource for Calibrated {
+    fn name(&self) -> String {
+        format!("{} + 标定 {}", self.inner.name(), self.path)
+    }
+
+    fn read(&mut self, elapsed: Duration) -> Result<Vec<(String, f32)>, Box<dyn Error>> {
+        let mut readings = self.inner.read(elapsed)?;
+        for (sensor_id, value) in &mut readings {
+            if let Some(calibration) = self.file.sensors.get(sensor_id) {
+                *value = calibration.apply(*value);
+            }
+        }
+        Ok(readings)
+    }
+
+    fn source_units(&self) -> Vec<(String, Unit)> {
+        self.file
+            .sensors
+            .iter()
+            .filter(|(_, c)| c.unit != Unit::Celsius)
+            .map(|(sensor_id, c)| (sensor_id.clone(), c.unit))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calibration(yaml: &str) -> SensorCalibration {
+        let file = CalibrationFile::parse(&format!("sensors:\n  s: {yaml}\n")).unwrap();
+        file.sensors["s"].clone()
+    }
+
+    #[test]
+    fn applies_each_calibration_kind() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-4;
+        assert!(close(
+            calibration("{ unit: fahrenheit }").apply(212.0),
+            100.0
+        ));
+        assert!(close(calibration("{ unit: kelvin }").apply(300.0), 26.85));
+        assert!(close(
+            calibration("{ gain: 2, offset: -1 }").apply(10.0),
+            19.0
+        ));
+        // 华氏度下先修正 +2°F 再换算
+        assert!(close(
+            calibration("{ unit: f, offset: 2 }").apply(30.0),
+            0.0
+        ));
+        assert!(close(
+            calibration("{ unit: raw, polynomial: [-40, 0.1, 0.0001] }").apply(100.0),
+            -29.0
+        ));
+
+        let table = calibration("{ unit: raw, table: [[0, -40], [500, 20], [1000, 120]] }");
+        assert!(close(table.apply(250.0), -10.0));
+        assert!(close(table.apply(750.0), 70.0));
+        // 表外按端点段外推
+        assert!(close(table.apply(1100.0), 140.0));
+        assert!(close(table.apply(-100.0), -52.0));
+    }
+
+    #[test]
+    fn rejects_inconsistent_calibrations() {
+        let parse = |yaml: &str| CalibrationFile::parse(&format!("sensors:\n  s: {yaml}\n"));
+        assert!(parse("{ unit: raw }").is_err());
+        assert!(parse("{ polynomial: [1], table: [[0, 0], [1, 1]] }").is_err());
+        assert!(parse("{ gain: 2, polynomial: [0, 1] }").is_err());
+        assert!(parse("{ table: [[1, 0], [1, 1]] }").is_err());
+        assert!(parse("{ unit: rankine }").is_err());
+    }
+
+    #[test]
+    fn bundled_calibration_is_valid() {
+        let file = CalibrationFile::parse(include_str!("../../calibration.yml")).unwrap();
+        assert_eq!(file.sensors["adc0"].apply(512.0), 25.0);
+    }
+}
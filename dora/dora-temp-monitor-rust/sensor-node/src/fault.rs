@@ -0,0 +1,374 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::time::Duration;
+use temp_common::config::parse_duration;
+use temp_common::units::Unit;
+
+use crate::source::TemperatureSource;
+
+/// 故障类型
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultKind {
+    /// 单点尖峰：读数加上 `magnitude`
+    Spike,
+    /// 阶跃：故障期间读数整体偏移 `magnitude`
+    Step,
+    /// 缓慢漂移：偏移量从 0 起按 `rate` (°C/s) 增长
+    Drift,
+    /// 卡死：保持故障开始时的读数不变
+    Stuck,
+    /// 断流：不发送该传感器的读数
+    Dropout,
+    /// 读数变为 NaN
+    Nan,
+}
+
+/// 场景文件中的一条故障
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FaultSpec {
+    pub kind: FaultKind,
+    /// 目标传感器 ID（不含 `SENSOR_ID_PREFIX`），省略时作用于全部传感器
+    #[serde(default)]
+    pub sensor: Option<String>,
+    /// 自节点启动起的开始时间 (s)
+    #[serde(deserialize_with = "seconds")]
+    pub start: f64,
+    /// 持续时间 (s)，省略时一直持续
+    #[serde(default, deserialize_with = "optional_seconds")]
+    pub duration: Option<f64>,
+    /// 重复周期 (s)，省略时只发生一次
+    #[serde(default, deserialize_with = "optional_seconds")]
+    pub every: Option<f64>,
+    /// `spike` / `step` 的幅度 (°C)
+    #[serde(default)]
+    pub magnitude: f32,
+    /// `drift` 的速率 (°C/s)
+    #[serde(default)]
+    pub rate: f32,
+    /// 故障期间每个读数受影响的概率，用于随机尖峰、间歇断流和 NaN 突发
+    #[serde(default = "always")]
+    pub probability: f64,
+}
+
+fn always() -> f64 {
+    1.0
+}
+
+/// YAML 中的时长既可以写数字（秒），也可以写 `500ms`、`2m` 这样的字符串
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Seconds {
+    Number(f64),
+    Text(String),
+}
+
+fn seconds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    match Seconds::deserialize(deserializer)? {
+        Seconds::Number(secs) => Ok(secs),
+        Seconds::Text(text) => parse_duration(&text)
+            .map(|d| d.as_secs_f64())
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+fn optional_seconds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    seconds(deserializer).map(Some)
+}
+
+/// 故障注入场景
+///
+/// ```yaml
+/// seed: 42
+/// faults:
+///   - { kind: spike, sensor: sensor0, start: 10s, duration: 5s, magnitude: 20, probability: 0.3 }
+///   - { kind: dropout, start: 1m, duration: 3s, every: 2m }
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// 随机数种子，省略时每次运行不同
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub faults: Vec<FaultSpec>,
+}
+
+impl Scenario {
+    pub fn parse(yaml: &str) -> Result<Self, Box<dyn Error>> {
+        let scenario: Self = serde_yaml::from_str(yaml)?;
+        for (index, fault) in scenario.faults.iter().enumerate() {
+            fault
+                .validate()
+                .map_err(|e| format!("第 {} 条故障 ({:?}): {e}", index + 1, fault.kind))?;
+        }
+        Ok(scenario)
+    }
+
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let yaml = fs::read_to_string(path).map_err(|e| format!("读取 {path} 失败: {e}"))?;
+        Self::parse(&yaml).map_err(|e| format!("{path}: {e}").into())
+    }
+}
+
+impl FaultSpec {
+    fn validate(&self) -> Result<(), String> {
+        if self.start < 0.0 {
+            return Err("start 不能为负".into());
+        }
+        if !(0.0..=1.0).contains(&self.probability) {
+            return Err("probability 必须在 0 到 1 之间".into());
+        }
+        if let Some(every) = self.every {
+            let duration = self.duration.ok_or("设置 every 时必须同时设置 duration")?;
+            if every <= 0.0 || duration > every {
+                return Err("every 必须大于 0 且不小于 duration".into());
+            }
+        }
+        match self.kind {
+            FaultKind::Spike | FaultKind::Step if self.magnitude == 0.0 => {
+                Err("需要非零的 magnitude".into())
+            }
+            FaultKind::Drift if self.rate == 0.0 => Err("需要非零的 rate".into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// `t` 时刻处于第几次发作以及发作后经过的时间，不在故障期间时返回 `None`
+    fn phase(&self, t: f64) -> Option<(u64, f64)> {
+        let since = t - self.start;
+        if since < 0.0 {
+            return None;
+        }
+        let (cycle, offset) = match self.every {
+            Some(every) => ((since / every).floor() as u64, since % every),
+            None => (0, since),
+        };
+        let active = self.duration.is_none_or(|duration| offset < duration);
+        active.then_some((cycle, offset))
+    }
+
+    fn applies_to(&self, sensor_id: &str) -> bool {
+        self.sensor.as_deref().is_none_or(|s| s == sensor_id)
+    }
+}
+
+/// 单条故障的运行状态
+struct ActiveFault {
+    spec: FaultSpec,
+    /// `stuck` 各传感器在每次发作开始时保持的读数
+    held: HashMap<String, (u64, f32)>,
+    /// 上一次读取时是否处于故障期间，用于打印开始/结束日志
+    active: bool,
+}
+
+/// 包装任意数据源，按场景在读数上叠加故障
+///
+/// 故障按场景文件中的顺序依次作用，时间与内部数据源一样以节点启动为零点
+pub struct FaultInjector {
+    inner: Box<dyn TemperatureSource>,
+    path: String,
+    faults: Vec<ActiveFault>,
+    rng: StdRng,
+}
+
+impl FaultInjector {
+    pub fn new(inner: Box<dyn TemperatureSource>, path: &str, scenario: Scenario) -> Self {
+        let rng = match scenario.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+        let faults = scenario
+            .faults
+            .into_iter()
+            .map(|spec| ActiveFault {
+                spec,
+                held: HashMap::new(),
+                active: false,
+            })
+            .collect();
+        Self {
+            inner,
+            path: path.to_owned(),
+            faults,
+            rng,
+        }
+    }
+
+    /// 依次应用所有故障；返回 `None` 表示该读数被丢弃
+    fn apply(&mut self, t: f64, sensor_id: &str, mut value: f32) -> Option<f32> {
+        for fault in &mut self.faults {
+            let spec = &fault.spec;
+            if !spec.applies_to(sensor_id) {
+                continue;
+            }
+            let Some((cycle, offset)) = spec.phase(t) else {
+                continue;
+            };
+            match spec.kind {
+                FaultKind::Step => value += spec.magnitude,
+                FaultKind::Drift => value += spec.rate * offset as f32,
+                FaultKind::Stuck => {
+                    let held = fault
+                        .held
+                        .entry(sensor_id.to_owned())
+                        .or_insert((cycle, value));
+                    if held.0 != cycle {
+                        *held = (cycle, value);
+                    }
+                    value = held.1;
+                }
+                // 其余故障按概率逐个读数触发
+                _ if !self.rng.random_bool(spec.probability) => {}
+                FaultKind::Spike => value += spec.magnitude,
+                FaultKind::Nan => value = f32::NAN,
+                FaultKind::Dropout => return None,
+            }
+        }
+        Some(value)
+    }
+
+    fn log_transitions(&mut self, t: f64) {
+        for fault in &mut self.faults {
+            let active = fault.spec.phase(t).is_some();
+            if active != fault.active {
+                let target = fault.spec.sensor.as_deref().unwrap_or("全部传感器");
+                if active {
+                    println!("💥 注入故障 {:?} ({target}) t={t:.1}s", fault.spec.kind);
+                } else {
+                    println!("🩹 故障结束 {:?} ({target}) t={t:.1}s", fault.spec.kind);
+                }
+                fault.active = active;
+            }
+        }
+    }
+}
+
+impl TemperatureSource for FaultInjector {
+    fn name(&self) -> String {
+        format!(
+            "{} + 故障场景 {} ({} 条)",
+            self.inner.name(),
+            self.path,
+            self.faults.len()
+        )
+    }
+
+    fn read(&mut self, elapsed: Duration) -> Result<Vec<(String, f32)>, Box<dyn Error>> {
+        let t = elapsed.as_secs_f64();
+        let readings = self.inner.read(elapsed)?;
+        self.log_transitions(t);
+        Ok(readings
+            .into_iter()
+            .filter_map(|(sensor_id, value)| {
+                self.apply(t, &sensor_id, value)
+                    .map(|value| (sensor_id, value))
+            })
+            .collect())
+    }
+
+    fn source_units(&self) -> Vec<(String, Unit)> {
+        self.inner.source_units()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synthetic::{Profile, SyntheticSource};
+
+    fn injector(yaml: &str) -> FaultInjector {
+        let ids = vec!["sensor0".to_owned(), "sensor1".to_owned()];
+        let source = SyntheticSource::new(Profile::Noise { base: 25.0 }, 0.0, Some(1), ids);
+        FaultInjector::new(Box::new(source), "test.yml", Scenario::parse(yaml).unwrap())
+    }
+
+    fn read_at(source: &mut FaultInjector, secs: f64) -> Vec<(String, f32)> {
+        source.read(Duration::from_secs_f64(secs)).unwrap()
+    }
+
+    #[test]
+    fn faults_follow_schedule_and_target_sensor() {
+        let mut source = injector(
+            "
+faults:
+  - { kind: step, sensor: sensor0, start: 1s, duration: 1s, magnitude: 10 }
+  - { kind: drift, sensor: sensor1, start: 2, rate: 2 }
+  - { kind: dropout, sensor: sensor1, start: 10s, duration: 500ms, every: 5s }
+",
+        );
+        let base = |t: f64| 25.0 + (t as f32 * 0.01).sin() * 3.0;
+
+        assert_eq!(read_at(&mut source, 0.5)[0].1, base(0.5));
+        assert_eq!(read_at(&mut source, 1.5)[0].1, base(1.5) + 10.0);
+        assert_eq!(read_at(&mut source, 2.5)[0].1, base(2.5));
+        let readings = read_at(&mut source, 4.0);
+        assert!((readings[1].1 - (base(4.0) + 4.0)).abs() < 1e-4);
+
+        // 断流每 5s 重复一次，只影响 sensor1
+        for t in [10.2, 15.2] {
+            let ids: Vec<String> = read_at(&mut source, t).into_iter().map(|r| r.0).collect();
+            assert_eq!(ids, ["sensor0"]);
+        }
+        assert_eq!(read_at(&mut source, 12.0).len(), 2);
+    }
+
+    #[test]
+    fn stuck_holds_value_for_each_occurrence() {
+        let mut source = injector(
+            "
+faults:
+  - { kind: stuck, sensor: sensor0, start: 100s, duration: 50s, every: 200s }
+",
+        );
+        let first = read_at(&mut source, 100.0)[0].1;
+        assert_eq!(read_at(&mut source, 140.0)[0].1, first);
+        assert_ne!(read_at(&mut source, 160.0)[0].1, first);
+        let second = read_at(&mut source, 300.0)[0].1;
+        assert_ne!(second, first);
+        assert_eq!(read_at(&mut source, 320.0)[0].1, second);
+    }
+
+    #[test]
+    fn random_faults_are_reproducible_with_seed() {
+        let yaml = "
+seed: 7
+faults:
+  - { kind: spike, start: 0, magnitude: 20, probability: 0.3 }
+  - { kind: nan, start: 0, probability: 0.1 }
+";
+        let run = |yaml: &str| -> Vec<String> {
+            let mut source = injector(yaml);
+            (0..200)
+                .flat_map(|i| read_at(&mut source, i as f64 * 0.1))
+                .map(|(id, value)| format!("{id}={value}"))
+                .collect()
+        };
+        let first = run(yaml);
+        assert_eq!(first, run(yaml));
+        assert!(first.iter().any(|r| r.ends_with("NaN")));
+        assert!(first.iter().any(|r| r.contains("=4")));
+    }
+
+    #[test]
+    fn rejects_invalid_scenarios() {
+        let parse = |fault: &str| Scenario::parse(&format!("faults:\n  - {fault}\n"));
+        assert!(parse("{ kind: spike, start: 0 }").is_err());
+        assert!(parse("{ kind: drift, start: 0, rate: 0 }").is_err());
+        assert!(parse("{ kind: nan, start: 0, probability: 2 }").is_err());
+        assert!(parse("{ kind: stuck, start: 0, every: 10s }").is_err());
+        assert!(parse("{ kind: melt, start: 0 }").is_err());
+        assert!(parse("{ kind: nan, start: 0, lenght: 1 }").is_err());
+    }
+
+    #[test]
+    fn bundled_scenario_is_valid() {
+        let scenario = Scenario::parse(include_str!("../../scenarios/faults.yml")).unwrap();
+        assert!(scenario.seed.is_some());
+        assert!(!scenario.faults.is_empty());
+    }
+}
@@ -1,37 +1,83 @@
-use dora_node_api::{arrow::array::Float32Array, dora_core::config::DataId, DoraNode, Event};
-use rand::Rng;
+use dora_node_api::{dora_core::config::DataId, DoraNode, Event, Parameter};
 use std::error::Error;
 use std::time::Instant;
+use temp_common::config::env_or;
+use temp_common::reading::readings_to_arrow;
+use temp_common::units::{set_source_units, Unit, UNIT_PARAM};
+
+mod calibration;
+mod fault;
+mod replay;
+mod sampler;
+mod source;
+mod synthetic;
+mod sysfs;
+
+use sampler::{Acquisition, Sampler};
 
 fn main() -> Result<(), Box<dyn Error>> {
+    temp_common::config::load_file("sensor")?;
     let (mut node, mut events) = DoraNode::init_from_env()?;
-    let output = DataId::from("temp_raw".to_owned());
-    println!("🌡️ 传感器节点启动 (M1 Pro优化版)");
+    let output = DataId::from(temp_common::TEMP_RAW.to_owned());
+
+    let source = source::from_env()?;
+    // 多个传感器节点接入同一数据流时，用前缀区分各自的传感器 ID
+    let id_prefix = env_or("SENSOR_ID_PREFIX", String::new())?;
+    println!("🌡️ 传感器节点启动 (数据源: {})", source.name());
+    let source_units: Vec<(String, Unit)> = source
+        .source_units()
+        .into_iter()
+        .map(|(sensor_id, unit)| (format!("{id_prefix}{sensor_id}"), unit))
+        .collect();
 
-    let mut rng = rand::rng();
     let start = Instant::now();
+    // 设置 SAMPLE_RATE 后在后台线程高频采样，每个 tick 把积累的样本合并为一个批次发送
+    let sample_rate: f64 = env_or("SAMPLE_RATE", 0.0)?;
+    let mut acquisition = if sample_rate > 0.0 {
+        let capacity = env_or("SAMPLE_BUFFER", 10_000usize)?.max(1);
+        println!("⏱️ 以 {sample_rate} Hz 采样，按 tick 批量发送");
+        Acquisition::Sampled(Sampler::spawn(source, sample_rate, capacity, start)?)
+    } else {
+        Acquisition::PerTick(source)
+    };
+    let mut exhausted = false;
 
     while let Some(event) = events.recv() {
         // println!("Received event: {:?}", event);
-        match event {
-            Event::Input {
-                id,
-                metadata,
-                data: _,
-            } => match id.as_str() {
-                "tick" => {
-                    // 模拟带噪声的温度数据
-                    let temp = 25.0
-                        + rng.random_range(-5.0..5.0)
-                        + (start.elapsed().as_secs_f32() * 0.01).sin() * 3.0; // 添加正弦趋势
-
-                    let temp_array = Float32Array::from(vec![temp]);
-
-                    node.send_output(output.clone(), metadata.parameters, temp_array)?;
-                }
+        if let Event::Input {
+            id,
+            metadata,
+            data: _,
+        } = event
+        {
+            match id.as_str() {
+                "tick" => match acquisition.collect(start) {
+                    Ok(None) => {
+                        if !exhausted {
+                            println!("📭 数据源已无更多数据");
+                            exhausted = true;
+                        }
+                    }
+                    // 两次 tick 之间还没有新样本
+                    Ok(Some(readings)) if readings.is_empty() => {}
+                    Ok(Some(mut readings)) => {
+                        for reading in &mut readings {
+                            reading.sensor_id = format!("{id_prefix}{}", reading.sensor_id);
+                        }
+                        // 读数已换算为摄氏度，原始单位随元数据传给下游
+                        let mut parameters = metadata.parameters;
+                        parameters.insert(
+                            UNIT_PARAM.to_owned(),
+                            Parameter::String(Unit::Celsius.as_str().to_owned()),
+                        );
+                        set_source_units(&mut parameters, &source_units);
+                        node.send_output(output.clone(), parameters, readings_to_arrow(&readings))?;
+                    }
+                    // 读取失败（例如传感器暂时不可用）不终止节点，跳过本次 tick
+                    Err(e) => eprintln!("读取温度失败: {e}"),
+                },
                 other => eprintln!("Received input `{other}`"),
-            },
-            _ => {}
+            }
         }
     }
 
@@ -0,0 +1,182 @@
+use std::error::Error;
+use std::fs;
+use std::time::Duration;
+
+use crate::source::TemperatureSource;
+
+/// 单个传感器的回放序列
+struct Series {
+    sensor_id: String,
+    rows: Vec<(f64, f32)>,
+    cursor: usize,
+}
+
+impl Series {
+    /// 返回不晚于 `t` 的最后一行；第一行之前没有数据
+    fn value_at(&mut self, t: f64) -> Option<f32> {
+        if t < self.rows[self.cursor].0 {
+            self.cursor = 0;
+        }
+        while self.cursor + 1 < self.rows.len() && self.rows[self.cursor + 1].0 <= t {
+            self.cursor += 1;
+        }
+        let (ts, value) = self.rows[self.cursor];
+        (ts <= t).then_some(value)
+    }
+}
+
+/// 按时间戳回放 CSV 文件
+///
+/// 文件格式为每行 `timestamp,temperature` 或 `timestamp,sensor_id,temperature`，
+/// 时间戳单位为秒（可为小数），允许首行表头和 `#` 开头的注释行。
+/// 两列格式的读数归属 `default_sensor_id`。回放时以全文件最早的时间戳为零点，
+/// 每次读取返回每个传感器不晚于当前时刻的最后一行数据。
+pub struct CsvReplaySource {
+    path: String,
+    series: Vec<Series>,
+    duration: f64,
+    looped: bool,
+}
+
+impl CsvReplaySource {
+    pub fn open(path: &str, default_sensor_id: &str, looped: bool) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path).map_err(|e| format!("读取 {path} 失败: {e}"))?;
+        let rows = parse_rows(&content, default_sensor_id).map_err(|e| format!("{path}: {e}"))?;
+
+        let mut series: Vec<Series> = Vec::new();
+        for (sensor_id, ts, value) in rows {
+            match series.iter_mut().find(|s| s.sensor_id == sensor_id) {
+                Some(s) => s.rows.push((ts, value)),
+                None => series.push(Series {
+                    sensor_id,
+                    rows: vec![(ts, value)],
+                    cursor: 0,
+                }),
+            }
+        }
+        let duration = series
+            .iter()
+            .map(|s| s.rows[s.rows.len() - 1].0)
+            .fold(0.0, f64::max);
+
+        Ok(Self {
+            path: path.to_owned(),
+            series,
+            duration,
+            looped,
+        })
+    }
+}
+
+/// CSV 中的一行：`(传感器 ID, 时间戳, 温度)`
+type Row = (String, f64, f32);
+
+/// 解析所有数据行，时间戳已平移到以 0 为起点
+fn parse_rows(content: &str, default_sensor_id: &str) -> Result<Vec<Row>, Box<dyn Error>> {
+    let mut rows = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let (ts, sensor_id, value) = match fields.as_slice() {
+            [ts, value] => (*ts, default_sensor_id, *value),
+            [ts, sensor_id, value] => (*ts, *sensor_id, *value),
+            _ => return Err(format!("第 {} 行字段数不正确: `{line}`", index + 1).into()),
+        };
+        let Ok(ts) = ts.parse::<f64>() else {
+            // 首行允许是表头
+            if rows.is_empty() {
+                continue;
+            }
+            return Err(format!("第 {} 行时间戳无效: `{ts}`", index + 1).into());
+        };
+        if sensor_id.is_empty() {
+            return Err(format!("第 {} 行传感器 ID 为空", index + 1).into());
+        }
+        let value: f32 = value
+            .parse()
+            .map_err(|e| format!("第 {} 行温度无效 `{value}`: {e}", index + 1))?;
+        rows.push((sensor_id.to_owned(), ts, value));
+    }
+
+    if rows.is_empty() {
+        return Err("没有任何数据行".into());
+    }
+    if rows.windows(2).any(|w| w[1].1 < w[0].1) {
+        return Err("时间戳必须单调不减".into());
+    }
+
+    // 时间戳统一平移到以 0 为起点
+    let t0 = rows[0].1;
+    for row in &mut rows {
+        row.1 -= t0;
+    }
+    Ok(rows)
+}
+
+impl TemperatureSource for CsvReplaySource {
+    fn name(&self) -> String {
+        let rows: usize = self.series.iter().map(|s| s.rows.len()).sum();
+        format!(
+            "csv replay {} ({} rows, {} sensors)",
+            self.path,
+            rows,
+            self.series.len()
+        )
+    }
+
+    fn read(&mut self, elapsed: Duration) -> Result<Vec<(String, f32)>, Box<dyn Error>> {
+        let mut t = elapsed.as_secs_f64();
+        if t > self.duration {
+            if !self.looped {
+                return Ok(Vec::new());
+            }
+            // 循环回放：按总时长取模，回绕时各序列游标复位
+            t = if self.duration > 0.0 {
+                t % self.duration
+            } else {
+                0.0
+            };
+        }
+
+        Ok(self
+            .series
+            .iter_mut()
+            .filter_map(|s| s.value_at(t).map(|value| (s.sensor_id.clone(), value)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn three_column_rows_are_grouped_by_sensor() {
+        let rows = parse_rows("t,sensor,temp\n10,a,20\n10,b,30\n11,a,21\n", "x").unwrap();
+        assert_eq!(rows[0], ("a".to_owned(), 0.0, 20.0));
+        assert_eq!(rows[1], ("b".to_owned(), 0.0, 30.0));
+
+        let rows = parse_rows("0,25\n1,26\n", "sensor0").unwrap();
+        assert!(rows.iter().all(|(id, _, _)| id == "sensor0"));
+    }
+
+    #[test]
+    fn each_sensor_replays_its_own_latest_value() {
+        let path = std::env::temp_dir().join(format!("replay-{}.csv", std::process::id()));
+        fs::write(&path, "0,a,20\n0,b,30\n1,a,21\n2,b,32\n").unwrap();
+        let mut source = CsvReplaySource::open(path.to_str().unwrap(), "x", false).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let at =
+            |s: &mut CsvReplaySource, secs: f64| s.read(Duration::from_secs_f64(secs)).unwrap();
+        assert_eq!(
+            at(&mut source, 1.5),
+            vec![("a".to_owned(), 21.0), ("b".to_owned(), 30.0)]
+        );
+        assert_eq!(at(&mut source, 2.0)[1], ("b".to_owned(), 32.0));
+        assert!(at(&mut source, 3.0).is_empty());
+    }
+}
@@ -0,0 +1,240 @@
+use std::error::Error;
+use std::sync::mpsc::{self, Receiver, TryRecvError, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+use temp_common::reading::Reading;
+
+use crate::source::TemperatureSource;
+
+/// 读数的采集方式
+pub enum Acquisition {
+    /// 每个 tick 读取一次数据源，同一批次的读数共用读取时刻的时间戳
+    PerTick(Box<dyn TemperatureSource>),
+    /// 后台线程以更高的频率采样，tick 时把积累的样本合并为一个批次
+    Sampled(Sampler),
+}
+
+impl Acquisition {
+    /// 取出本次 tick 要发送的读数（传感器 ID 不含前缀），数据源已无更多数据时返回 `None`
+    pub fn collect(&mut self, start: Instant) -> Result<Option<Vec<Reading>>, Box<dyn Error>> {
+        match self {
+            Acquisition::PerTick(source) => {
+                let values = source.read(start.elapsed())?;
+                if values.is_empty() {
+                    return Ok(None);
+                }
+                let timestamp_ns = temp_common::now_ns();
+                Ok(Some(stamp(timestamp_ns, values)))
+            }
+            Acquisition::Sampled(sampler) => {
+                let readings = sampler.drain();
+                if readings.is_empty() && sampler.exhausted {
+                    return Ok(None);
+                }
+                Ok(Some(readings))
+            }
+        }
+    }
+}
+
+fn stamp(timestamp_ns: u64, values: Vec<(String, f32)>) -> Vec<Reading> {
+    values
+        .into_iter()
+        .map(|(sensor_id, value)| Reading {
+            timestamp_ns,
+            sensor_id,
+            value,
+        })
+        .collect()
+}
+
+/// 在后台线程中以固定频率读取数据源，每次读取的样本带各自的采样时间戳
+pub struct Sampler {
+    samples: Receiver<Vec<Reading>>,
+    /// 采样线程已退出（数据源无更多数据）
+    exhausted: bool,
+}
+
+impl Sampler {
+    /// 以 `rate_hz` 的频率采样，最多缓存 `capacity` 次采样，缓存满时丢弃新样本；
+    /// 频率不是正的有限值或小到采样周期超出 `Duration` 范围时返回错误
+    pub fn spawn(
+        mut source: Box<dyn TemperatureSource>,
+        rate_hz: f64,
+        capacity: usize,
+        start: Instant,
+    ) -> Result<Self, Box<dyn Error>> {
+        if !rate_hz.is_finite() || rate_hz <= 0.0 {
+            return Err(format!("SAMPLE_RATE 必须为正数: {rate_hz}").into());
+        }
+        let period = Duration::try_from_secs_f64(1.0 / rate_hz)
+            .map_err(|e| format!("SAMPLE_RATE={rate_hz} 过小: {e}"))?;
+        let (tx, rx) = mpsc::sync_channel(capacity);
+        thread::spawn(move || {
+            let mut deadline = Instant::now();
+            let mut dropped = 0usize;
+            loop {
+                match source.read(start.elapsed()) {
+                    Ok(values) if values.is_empty() => break,
+                    Ok(values) => match tx.try_send(stamp(temp_common::now_ns(), values)) {
+                        Ok(()) => {
+                            if dropped > 0 {
+                                eprintln!("采样缓冲区已满，丢弃了 {dropped} 次采样");
+                                dropped = 0;
+                            }
+                        }
+                        Err(TrySendError::Full(_)) => dropped += 1,
+                        Err(TrySendError::Disconnected(_)) => break,
+                    },
+                    // 读取失败不终止采样，跳过本次采样
+                    Err(e) => eprintln!("读取温度失败: {e}"),
+                }
+                // 按固定节拍采样；落后超过一个周期时不追赶，从当前时刻重新计时
+                deadline += period;
+                let now = Instant::now();
+                if deadline > now {
+                    thread::sleep(deadline - now);
+                } else {
+                    deadline = now;
+                }
+            }
+            if dropped > 0 {
+                eprintln!("采样缓冲区已满，丢弃了 {dropped} 次采样");
+            }
+        });
+        Ok(Self {
+            samples: rx,
+            exhausted: false,
+        })
+    }
+
+    /// 取出上次调用以来的全部样本，按采样顺序排列
+    pub fn drain(&mut self) -> Vec<Reading> {
+        let mut readings = Vec::new();
+        loop {
+            match self.samples.try_recv() {
+                Ok(batch) => readings.extend(batch),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.exhausted = true;
+                    break;
+                }
+            }
+        }
+        readings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synthetic::{Profile, SyntheticSource};
+
+    /// 只能读取固定次数的数据源，读完时通过 `done` 通知测试
+    struct Finite {
+        left: usize,
+        done: Option<mpsc::Sender<()>>,
+    }
+
+    fn finite(left: usize) -> Finite {
+        Finite { left, done: None }
+    }
+
+    impl TemperatureSource for Finite {
+        fn name(&self) -> String {
+            "finite".to_owned()
+        }
+
+        fn read(&mut self, _elapsed: Duration) -> Result<Vec<(String, f32)>, Box<dyn Error>> {
+            if self.left == 0 {
+                if let Some(done) = self.done.take() {
+                    let _ = done.send(());
+                }
+                return Ok(Vec::new());
+            }
+            self.left -= 1;
+            Ok(vec![("sensor0".to_owned(), self.left as f32)])
+        }
+    }
+
+    #[test]
+    fn samples_are_batched_with_individual_timestamps() {
+        let ids = vec!["sensor0".to_owned(), "sensor1".to_owned()];
+        let source = SyntheticSource::new(
+            Profile::Ramp {
+                base: 25.0,
+                rate: 0.0,
+            },
+            0.0,
+            Some(1),
+            ids,
+        );
+        let sampler = Sampler::spawn(Box::new(source), 1000.0, 10_000, Instant::now()).unwrap();
+        let mut acquisition = Acquisition::Sampled(sampler);
+
+        // 不依赖调度器在固定时间内完成多少次采样，只等到有足够的样本
+        let mut readings = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while readings.len() < 10 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+            readings.extend(acquisition.collect(Instant::now()).unwrap().unwrap());
+        }
+        assert!(readings.len() >= 10, "{}", readings.len());
+
+        // 每次采样包含两个传感器，共用该次采样的时间戳
+        for sample in readings.chunks(2) {
+            assert_eq!(sample[0].sensor_id, "sensor0");
+            assert_eq!(sample[1].sensor_id, "sensor1");
+            assert_eq!(sample[0].timestamp_ns, sample[1].timestamp_ns);
+        }
+        // 样本按时间排序，相邻采样至少间隔约一个 1ms 周期（首次采样的时间戳允许一个周期的误差）
+        let sensor0: Vec<u64> = readings.iter().step_by(2).map(|r| r.timestamp_ns).collect();
+        assert!(sensor0.windows(2).all(|w| w[0] < w[1]));
+        let span = sensor0[sensor0.len() - 1] - sensor0[0];
+        assert!(
+            span >= (sensor0.len() as u64 - 2) * 1_000_000,
+            "{} samples in {span}ns",
+            sensor0.len()
+        );
+    }
+
+    #[test]
+    fn sample_rate_must_give_a_representable_period() {
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY, f64::MIN_POSITIVE / 4.0] {
+            assert!(
+                Sampler::spawn(Box::new(finite(1)), rate, 1, Instant::now()).is_err(),
+                "{rate}"
+            );
+        }
+    }
+
+    #[test]
+    fn exhausted_source_ends_after_remaining_samples() {
+        let (done, source_exhausted) = mpsc::channel();
+        let source = Finite {
+            left: 3,
+            done: Some(done),
+        };
+        let sampler = Sampler::spawn(Box::new(source), 1000.0, 2, Instant::now()).unwrap();
+        let mut acquisition = Acquisition::Sampled(sampler);
+        // 数据源读完时三次采样都已尝试写入缓冲区
+        source_exhausted
+            .recv_timeout(Duration::from_secs(10))
+            .unwrap();
+
+        // 采样线程退出前 collect 可能返回空批次，一直取到 `None`
+        let mut values = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while let Some(readings) = acquisition.collect(Instant::now()).unwrap() {
+            values.extend(readings.iter().map(|r| r.value));
+            assert!(Instant::now() < deadline, "采样线程没有退出");
+            thread::sleep(Duration::from_millis(1));
+        }
+        // 缓冲区只能容纳两次采样，第三次被丢弃
+        assert_eq!(values, [2.0, 1.0]);
+
+        let mut per_tick = Acquisition::PerTick(Box::new(finite(1)));
+        assert_eq!(per_tick.collect(Instant::now()).unwrap().unwrap().len(), 1);
+        assert!(per_tick.collect(Instant::now()).unwrap().is_none());
+    }
+}
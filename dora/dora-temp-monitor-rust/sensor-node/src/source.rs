@@ -0,0 +1,103 @@
+use std::error::Error;
+use std::time::Duration;
+use temp_common::config::{self, env_or};
+use temp_common::units::Unit;
+
+use crate::calibration::{Calibrated, CalibrationFile};
+use crate::fault::{FaultInjector, Scenario};
+use crate::replay::CsvReplaySource;
+use crate::synthetic::{Profile, SyntheticSource};
+use crate::sysfs::SysfsSource;
+
+/// 温度数据源：传感器节点每个 tick 从这里读取所有传感器的当前读数
+pub trait TemperatureSource: Send {
+    /// 数据源名称，用于启动日志
+    fn name(&self) -> String;
+
+    /// 读取 `elapsed`（自节点启动以来的时间）时刻各传感器的温度 (°C)
+    ///
+    /// 返回 `(传感器 ID, 温度)` 列表；空列表表示当前没有可用数据（例如回放结束），本次 tick 不发送
+    fn read(&mut self, elapsed: Duration) -> Result<Vec<(String, f32)>, Box<dyn Error>>;
+
+    /// 换算为摄氏度之前各传感器的原始单位，未列出的传感器本来就是摄氏度
+    fn source_units(&self) -> Vec<(String, Unit)> {
+        Vec::new()
+    }
+}
+
+/// 逗号分隔的列表，忽略空项
+pub fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// 根据 `TEMP_SOURCE` 环境变量（dataflow.yml 中的 `env:`）创建数据源
+///
+/// - `synthetic`（默认）：脚本化的合成温度曲线，见 `TEMP_PROFILE`
+/// - `thermal`：Linux `/sys/class/thermal/thermal_zone*/temp`
+/// - `hwmon`：Linux `/sys/class/hwmon/hwmon*/temp*_input`
+/// - `csv`：回放带时间戳的 CSV 文件，见 `TEMP_CSV_PATH`
+///
+/// 设置 `CALIBRATION_FILE` 时，先按其中的 YAML 配置把各传感器的读数标定并换算为摄氏度；
+/// 设置 `FAULT_SCENARIO` 时，按其中的 YAML 场景在上述数据源的读数上注入故障
+pub fn from_env() -> Result<Box<dyn TemperatureSource>, Box<dyn Error>> {
+    let kind = env_or("TEMP_SOURCE", "synthetic".to_owned())?;
+    let sysfs_root = env_or("TEMP_SYSFS_ROOT", "/sys/class".to_owned())?;
+
+    let mut source: Box<dyn TemperatureSource> = match kind.as_str() {
+        "synthetic" => {
+            let profile = Profile::from_env()?;
+            let noise = env_or("TEMP_NOISE", profile.default_noise())?;
+            let seed = match config::var("TEMP_SEED") {
+                Some(_) => Some(env_or("TEMP_SEED", 0u64)?),
+                None => None,
+            };
+            let count = env_or("TEMP_SENSOR_COUNT", 1usize)?;
+            let sensor_ids = (0..count).map(|i| format!("sensor{i}")).collect();
+            Box::new(SyntheticSource::new(profile, noise, seed, sensor_ids))
+        }
+        "thermal" => {
+            let zones = env_or("TEMP_THERMAL_ZONE", "all".to_owned())?;
+            Box::new(SysfsSource::thermal(&sysfs_root, &split_list(&zones))?)
+        }
+        "hwmon" => {
+            let chips = env_or("TEMP_HWMON_CHIP", "all".to_owned())?;
+            let inputs = env_or("TEMP_HWMON_INPUT", "all".to_owned())?;
+            Box::new(SysfsSource::hwmon(
+                &sysfs_root,
+                &split_list(&chips),
+                &split_list(&inputs),
+            )?)
+        }
+        "csv" => {
+            let path =
+                config::var("TEMP_CSV_PATH").ok_or("TEMP_SOURCE=csv 需要设置 TEMP_CSV_PATH")?;
+            let looped = env_or("TEMP_CSV_LOOP", false)?;
+            Box::new(CsvReplaySource::open(
+                &path,
+                temp_common::DEFAULT_SENSOR_ID,
+                looped,
+            )?)
+        }
+        other => return Err(format!("未知的温度数据源 TEMP_SOURCE={other}").into()),
+    };
+
+    if let Some(path) = config::var("CALIBRATION_FILE") {
+        if !path.trim().is_empty() {
+            let file = CalibrationFile::load(path.trim())?;
+            source = Box::new(Calibrated::new(source, path.trim(), file));
+        }
+    }
+
+    match config::var("FAULT_SCENARIO") {
+        Some(path) if !path.trim().is_empty() => {
+            let scenario = Scenario::load(path.trim())?;
+            Ok(Box::new(FaultInjector::new(source, path.trim(), scenario)))
+        }
+        _ => Ok(source),
+    }
+}
@@ -0,0 +1,229 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::error::Error;
+use std::time::Duration;
+use temp_common::config::env_or;
+
+use crate::source::TemperatureSource;
+
+/// 合成温度曲线
+#[derive(Debug, Clone, PartialEq)]
+pub enum Profile {
+    /// 原始演示曲线：基准值 + 正弦趋势
+    Noise { base: f32 },
+    /// 线性升温/降温，`rate` 单位 °C/s
+    Ramp { base: f32, rate: f32 },
+    /// 在 `at` 秒处发生一次阶跃 `delta`
+    Step { base: f32, at: f32, delta: f32 },
+    /// 每 `period` 秒出现一次持续 `width` 秒、幅度为 `amplitude` 的尖峰
+    Spike {
+        base: f32,
+        period: f32,
+        width: f32,
+        amplitude: f32,
+    },
+    /// 缓慢漂移：线性趋势 `rate` (°C/s) 叠加随机游走
+    Drift { base: f32, rate: f32 },
+}
+
+impl Profile {
+    /// 从 `TEMP_PROFILE` 及相关环境变量解析曲线参数
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let base = env_or("TEMP_BASE", 25.0)?;
+        let profile = match env_or("TEMP_PROFILE", "noise".to_owned())?.as_str() {
+            "noise" => Profile::Noise { base },
+            "ramp" => Profile::Ramp {
+                base,
+                rate: env_or("TEMP_RATE", 0.1)?,
+            },
+            "step" => Profile::Step {
+                base,
+                at: env_or("TEMP_STEP_AT", 10.0)?,
+                delta: env_or("TEMP_STEP_DELTA", 10.0)?,
+            },
+            "spike" => Profile::Spike {
+                base,
+                period: env_or("TEMP_SPIKE_PERIOD", 10.0)?,
+                width: env_or("TEMP_SPIKE_WIDTH", 0.5)?,
+                amplitude: env_or("TEMP_SPIKE_AMPLITUDE", 15.0)?,
+            },
+            "drift" => Profile::Drift {
+                base,
+                rate: env_or("TEMP_RATE", 0.01)?,
+            },
+            other => return Err(format!("未知的合成曲线 TEMP_PROFILE={other}").into()),
+        };
+        Ok(profile)
+    }
+
+    /// 不含噪声的曲线值
+    fn value_at(&self, t: f32) -> f32 {
+        match *self {
+            Profile::Noise { base } => base + (t * 0.01).sin() * 3.0,
+            Profile::Ramp { base, rate } => base + rate * t,
+            Profile::Step { base, at, delta } => {
+                if t >= at {
+                    base + delta
+                } else {
+                    base
+                }
+            }
+            Profile::Spike {
+                base,
+                period,
+                width,
+                amplitude,
+            } => {
+                if period > 0.0 && t % period < width {
+                    base + amplitude
+                } else {
+                    base
+                }
+            }
+            Profile::Drift { base, rate } => base + rate * t,
+        }
+    }
+
+    /// 各曲线默认的噪声幅度：演示曲线保持原来的 ±5°C，其余默认无噪声
+    pub fn default_noise(&self) -> f32 {
+        match self {
+            Profile::Noise { .. } => 5.0,
+            _ => 0.0,
+        }
+    }
+}
+
+/// 单个合成传感器的独立随机状态
+struct Channel {
+    sensor_id: String,
+    rng: StdRng,
+    walk: f32,
+}
+
+/// 合成数据源；设置 `TEMP_SEED` 后输出完全可复现
+///
+/// 多个传感器共用同一条曲线，但噪声相互独立
+pub struct SyntheticSource {
+    profile: Profile,
+    noise: f32,
+    channels: Vec<Channel>,
+}
+
+impl SyntheticSource {
+    pub fn new(profile: Profile, noise: f32, seed: Option<u64>, sensor_ids: Vec<String>) -> Self {
+        let channels = sensor_ids
+            .into_iter()
+            .enumerate()
+            .map(|(i, sensor_id)| Channel {
+                sensor_id,
+                rng: match seed {
+                    Some(seed) => StdRng::seed_from_u64(seed.wrapping_add(i as u64)),
+                    None => StdRng::from_os_rng(),
+                },
+                walk: 0.0,
+            })
+            .collect();
+        Self {
+            profile,
+            noise: noise.abs(),
+            channels,
+        }
+    }
+}
+
+impl Channel {
+    fn noise(&mut self, amplitude: f32) -> f32 {
+        if amplitude > 0.0 {
+            self.rng.random_range(-amplitude..amplitude)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl TemperatureSource for SyntheticSource {
+    fn name(&self) -> String {
+        format!("synthetic {:?} x{}", self.profile, self.channels.len())
+    }
+
+    fn read(&mut self, elapsed: Duration) -> Result<Vec<(String, f32)>, Box<dyn Error>> {
+        let t = elapsed.as_secs_f32();
+        let base = self.profile.value_at(t);
+        let readings = self
+            .channels
+            .iter_mut()
+            .map(|channel| {
+                let temp = match self.profile {
+                    // 漂移曲线的噪声累积为随机游走，而不是逐点独立
+                    Profile::Drift { .. } => {
+                        channel.walk += channel.noise(self.noise);
+                        base + channel.walk
+                    }
+                    _ => base + channel.noise(self.noise),
+                };
+                (channel.sensor_id.clone(), temp)
+            })
+            .collect();
+        Ok(readings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("sensor{i}")).collect()
+    }
+
+    fn read_at(source: &mut SyntheticSource, secs: f32) -> f32 {
+        source.read(Duration::from_secs_f32(secs)).unwrap()[0].1
+    }
+
+    #[test]
+    fn step_profile_jumps_at_configured_time() {
+        let profile = Profile::Step {
+            base: 20.0,
+            at: 5.0,
+            delta: 8.0,
+        };
+        let mut source = SyntheticSource::new(profile, 0.0, Some(1), ids(1));
+        assert_eq!(read_at(&mut source, 4.9), 20.0);
+        assert_eq!(read_at(&mut source, 5.0), 28.0);
+    }
+
+    #[test]
+    fn spike_profile_repeats_every_period() {
+        let profile = Profile::Spike {
+            base: 25.0,
+            period: 10.0,
+            width: 1.0,
+            amplitude: 15.0,
+        };
+        let mut source = SyntheticSource::new(profile, 0.0, Some(1), ids(1));
+        assert_eq!(read_at(&mut source, 0.5), 40.0);
+        assert_eq!(read_at(&mut source, 5.0), 25.0);
+        assert_eq!(read_at(&mut source, 20.5), 40.0);
+    }
+
+    #[test]
+    fn same_seed_gives_same_sequence() {
+        let profile = Profile::Noise { base: 25.0 };
+        let mut a = SyntheticSource::new(profile.clone(), 5.0, Some(42), ids(1));
+        let mut b = SyntheticSource::new(profile, 5.0, Some(42), ids(1));
+        for i in 0..20 {
+            let t = i as f32 * 0.1;
+            assert_eq!(read_at(&mut a, t), read_at(&mut b, t));
+        }
+    }
+
+    #[test]
+    fn sensors_get_independent_noise() {
+        let profile = Profile::Noise { base: 25.0 };
+        let mut source = SyntheticSource::new(profile, 5.0, Some(7), ids(3));
+        let readings = source.read(Duration::from_secs(1)).unwrap();
+        let names: Vec<&str> = readings.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(names, ["sensor0", "sensor1", "sensor2"]);
+        assert_ne!(readings[0].1, readings[1].1);
+    }
+}
@@ -0,0 +1,149 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::source::TemperatureSource;
+
+/// 读取 sysfs 温度文件，内核以毫摄氏度 (m°C) 为单位输出整数
+fn read_millidegrees(path: &Path) -> Result<f32, Box<dyn Error>> {
+    let raw = fs::read_to_string(path).map_err(|e| format!("读取 {} 失败: {e}", path.display()))?;
+    let millis: i64 = raw
+        .trim()
+        .parse()
+        .map_err(|e| format!("{} 内容无效 `{}`: {e}", path.display(), raw.trim()))?;
+    Ok(millis as f32 / 1000.0)
+}
+
+/// 列出 `dir` 下名称以 `prefix` 开头的条目，按名称排序
+fn list_prefixed(dir: &Path, prefix: &str) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("无法读取 {}: {e}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| file_name(path).starts_with(prefix))
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+}
+
+/// 按目录名或目录下 `label_file` 的内容查找设备目录
+///
+/// `selectors` 中每一项可以是目录名（如 `thermal_zone1`、`hwmon2`），
+/// 也可以是 `type`/`name` 文件中的设备名（如 `x86_pkg_temp`、`coretemp`）；
+/// `all` 表示该类下的全部设备
+fn find_devices(
+    class_dir: &Path,
+    prefix: &str,
+    label_file: &str,
+    selectors: &[String],
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let devices = list_prefixed(class_dir, prefix)?;
+    if selectors.iter().any(|s| s == "all") {
+        return Ok(devices);
+    }
+
+    selectors
+        .iter()
+        .map(|selector| {
+            devices
+                .iter()
+                .find(|dir| {
+                    file_name(dir) == selector
+                        || fs::read_to_string(dir.join(label_file))
+                            .is_ok_and(|label| label.trim() == selector)
+                })
+                .cloned()
+                .ok_or_else(|| {
+                    format!("在 {} 下找不到设备 `{selector}`", class_dir.display()).into()
+                })
+        })
+        .collect()
+}
+
+/// Linux sysfs 温度传感器，每个温度文件对应一个传感器
+///
+/// - thermal：`/sys/class/thermal/thermal_zone*/temp`，传感器 ID 为目录名，如 `thermal_zone0`
+/// - hwmon：`/sys/class/hwmon/hwmon*/temp*_input`，传感器 ID 如 `hwmon1/temp2`
+pub struct SysfsSource {
+    kind: &'static str,
+    sensors: Vec<(String, PathBuf)>,
+}
+
+impl SysfsSource {
+    pub fn thermal(sysfs_root: &str, zones: &[String]) -> Result<Self, Box<dyn Error>> {
+        let class_dir = Path::new(sysfs_root).join("thermal");
+        let sensors = find_devices(&class_dir, "thermal_zone", "type", zones)?
+            .into_iter()
+            .map(|dir| (file_name(&dir).to_owned(), dir.join("temp")))
+            .collect();
+        Self::open("thermal", sensors)
+    }
+
+    /// `inputs` 为 `temp1_input` 这样的文件名列表，`all` 表示芯片下的全部温度输入
+    pub fn hwmon(
+        sysfs_root: &str,
+        chips: &[String],
+        inputs: &[String],
+    ) -> Result<Self, Box<dyn Error>> {
+        let class_dir = Path::new(sysfs_root).join("hwmon");
+        let mut sensors = Vec::new();
+        for chip in find_devices(&class_dir, "hwmon", "name", chips)? {
+            let files = if inputs.iter().any(|i| i == "all") {
+                list_prefixed(&chip, "temp")?
+                    .into_iter()
+                    .filter(|path| file_name(path).ends_with("_input"))
+                    .collect()
+            } else {
+                inputs
+                    .iter()
+                    .map(|input| chip.join(input))
+                    .collect::<Vec<_>>()
+            };
+            for file in files {
+                let channel = file_name(&file).trim_end_matches("_input").to_owned();
+                sensors.push((format!("{}/{channel}", file_name(&chip)), file));
+            }
+        }
+        Self::open("hwmon", sensors)
+    }
+
+    fn open(kind: &'static str, sensors: Vec<(String, PathBuf)>) -> Result<Self, Box<dyn Error>> {
+        if sensors.is_empty() {
+            return Err(format!("没有找到任何 {kind} 温度传感器").into());
+        }
+        // 启动时读一次，尽早暴露权限或路径问题
+        for (_, path) in &sensors {
+            read_millidegrees(path)?;
+        }
+        Ok(Self { kind, sensors })
+    }
+}
+
+impl TemperatureSource for SysfsSource {
+    fn name(&self) -> String {
+        let ids: Vec<&str> = self.sensors.iter().map(|(id, _)| id.as_str()).collect();
+        format!("{} [{}]", self.kind, ids.join(", "))
+    }
+
+    fn read(&mut self, _elapsed: Duration) -> Result<Vec<(String, f32)>, Box<dyn Error>> {
+        // 单个传感器读取失败不影响其余传感器
+        let readings = self
+            .sensors
+            .iter()
+            .filter_map(|(id, path)| match read_millidegrees(path) {
+                Ok(temp) => Some((id.clone(), temp)),
+                Err(e) => {
+                    eprintln!("{e}");
+                    None
+                }
+            })
+            .collect();
+        Ok(readings)
+    }
+}
@@ -0,0 +1,307 @@
+//! 温度历史查询工具：读取存储节点写入的 SQLite 数据库
+//!
+//! ```text
+//! temp-query sensors
+//! temp-query range --sensor sensor0 --from -10m
+//! temp-query export --bucket 1m --from 2024-05-01T00:00:00Z --format csv > history.csv
+//! temp-query alerts --severity warning --limit 20
+//! ```
+
+use chrono::{DateTime, SecondsFormat};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::error::Error;
+use std::path::PathBuf;
+use storage_node::store::{Query, Store};
+use temp_common::alert::Severity;
+use temp_common::config::parse_duration;
+
+#[derive(Parser)]
+#[command(name = "temp-query", about = "查询温度历史、降采样导出和告警记录")]
+struct Cli {
+    /// 数据库路径，与存储节点的 STORE_PATH 一致
+    #[arg(long, env = "STORE_PATH", default_value = "temp_history.db")]
+    db: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 列出各传感器的记录数和时间范围
+    Sensors,
+    /// 时间范围内的原始读数
+    Range {
+        #[command(flatten)]
+        filter: Filter,
+        /// 最多输出的行数，0 表示不限制
+        #[arg(long, default_value_t = 1000)]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+    },
+    /// 按时间桶降采样后导出最小 / 最大 / 平均值
+    Export {
+        #[command(flatten)]
+        filter: Filter,
+        /// 时间桶长度，例如 10s、1m、1h
+        #[arg(long, default_value = "1m")]
+        bucket: String,
+        #[arg(long, value_enum, default_value_t = Format::Csv)]
+        format: Format,
+    },
+    /// 告警历史，最新的在前
+    Alerts {
+        #[command(flatten)]
+        filter: Filter,
+        /// 只显示不低于该级别的事件
+        #[arg(long, value_parser = ["info", "warning", "critical"])]
+        severity: Option<String>,
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+    },
+}
+
+#[derive(Args)]
+struct Filter {
+    /// 只查询该传感器
+    #[arg(long)]
+    sensor: Option<String>,
+    /// 起始时间（含）：`now`、`-10m` 等相对时间、RFC 3339 或纳秒时间戳
+    #[arg(long)]
+    from: Option<String>,
+    /// 结束时间（不含），格式同 --from
+    #[arg(long)]
+    to: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Format {
+    Table,
+    Csv,
+}
+
+impl Filter {
+    fn query(&self, now_ns: u64, limit: usize) -> Result<Query, Box<dyn Error>> {
+        let time = |text: &Option<String>, default| match text {
+            Some(text) => parse_time(text, now_ns),
+            None => Ok(default),
+        };
+        Ok(Query {
+            sensor_id: self.sensor.clone(),
+            from_ns: time(&self.from, 0)?,
+            to_ns: time(&self.to, u64::MAX)?,
+            limit,
+        })
+    }
+}
+
+/// 解析 `now`、`-10m`（相对 `now_ns`）、RFC 3339 或纳秒时间戳
+fn parse_time(text: &str, now_ns: u64) -> Result<u64, Box<dyn Error>> {
+    let text = text.trim();
+    if text == "now" {
+        return Ok(now_ns);
+    }
+    if let Some(ago) = text.strip_prefix('-') {
+        return Ok(now_ns.saturating_sub(parse_duration(ago)?.as_nanos() as u64));
+    }
+    if let Ok(ns) = text.parse::<u64>() {
+        return Ok(ns);
+    }
+    let time =
+        DateTime::parse_from_rfc3339(text).map_err(|e| format!("无法解析时间 `{text}`: {e}"))?;
+    time.timestamp_nanos_opt()
+        .and_then(|ns| u64::try_from(ns).ok())
+        .ok_or_else(|| format!("时间 `{text}` 超出范围").into())
+}
+
+/// UTC 的 RFC 3339 时间，精确到毫秒
+fn format_time(ns: u64) -> String {
+    DateTime::from_timestamp_nanos(ns as i64).to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// 按格式输出表头和各行，表格模式下列宽取各列最长值
+fn print_rows(format: Format, header: &[&str], rows: Vec<Vec<String>>) {
+    match format {
+        Format::Csv => {
+            println!("{}", header.join(","));
+            for row in rows {
+                let escaped: Vec<String> = row.iter().map(|cell| csv_escape(cell)).collect();
+                println!("{}", escaped.join(","));
+            }
+        }
+        Format::Table => {
+            let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+            for row in &rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.chars().count());
+                }
+            }
+            let line = |cells: Vec<&str>| {
+                let padded: Vec<String> = cells
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, &width)| format!("{cell:<width$}"))
+                    .collect();
+                println!("{}", padded.join("  ").trim_end());
+            };
+            line(header.to_vec());
+            for row in &rows {
+                line(row.iter().map(String::as_str).collect());
+            }
+        }
+    }
+}
+
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_owned()
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    let store = Store::open_read_only(&cli.db)?;
+    let now_ns = temp_common::now_ns();
+
+    match cli.command {
+        Command::Sensors => {
+            let rows = store
+                .sensors()?
+                .into_iter()
+                .map(|s| {
+                    vec![
+                        s.sensor_id,
+                        s.count.to_string(),
+                        format_time(s.first_ns),
+                        format_time(s.last_ns),
+                    ]
+                })
+                .collect();
+            print_rows(
+                Format::Table,
+                &["sensor_id", "count", "first", "last"],
+                rows,
+            );
+        }
+        Command::Range {
+            filter,
+            limit,
+            format,
+        } => {
+            let rows = store
+                .readings(&filter.query(now_ns, limit)?)?
+                .into_iter()
+                .map(|r| {
+                    vec![
+                        format_time(r.timestamp_ns),
+                        r.timestamp_ns.to_string(),
+                        r.sensor_id,
+                        format!("{:.3}", r.value),
+                    ]
+                })
+                .collect();
+            print_rows(
+                format,
+                &["time", "timestamp_ns", "sensor_id", "value"],
+                rows,
+            );
+        }
+        Command::Export {
+            filter,
+            bucket,
+            format,
+        } => {
+            let bucket_ns = parse_duration(&bucket)?.as_nanos() as u64;
+            let rows = store
+                .downsample(&filter.query(now_ns, 0)?, bucket_ns)?
+                .into_iter()
+                .map(|b| {
+                    vec![
+                        format_time(b.start_ns),
+                        b.start_ns.to_string(),
+                        b.sensor_id,
+                        b.count.to_string(),
+                        format!("{:.3}", b.min),
+                        format!("{:.3}", b.max),
+                        format!("{:.3}", b.mean),
+                    ]
+                })
+                .collect();
+            print_rows(
+                format,
+                &[
+                    "time",
+                    "timestamp_ns",
+                    "sensor_id",
+                    "count",
+                    "min",
+                    "max",
+                    "mean",
+                ],
+                rows,
+            );
+        }
+        Command::Alerts {
+            filter,
+            severity,
+            limit,
+            format,
+        } => {
+            let severity: Option<Severity> = severity.map(|s| s.parse()).transpose()?;
+            let rows = store
+                .alerts(&filter.query(now_ns, limit)?, severity)?
+                .into_iter()
+                .map(|a| {
+                    vec![
+                        format_time(a.timestamp_ns),
+                        a.sensor_id,
+                        a.kind.as_str().to_owned(),
+                        a.severity.as_str().to_owned(),
+                        a.state.as_str().to_owned(),
+                        format!("{:.2}", a.value),
+                        a.message,
+                    ]
+                })
+                .collect();
+            print_rows(
+                format,
+                &[
+                    "time",
+                    "sensor_id",
+                    "kind",
+                    "severity",
+                    "state",
+                    "value",
+                    "message",
+                ],
+                rows,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_relative_absolute_and_raw_times() {
+        let now = 1_700_000_000_000_000_000;
+        assert_eq!(parse_time("now", now).unwrap(), now);
+        assert_eq!(parse_time("-10m", now).unwrap(), now - 600_000_000_000);
+        assert_eq!(parse_time("42", now).unwrap(), 42);
+        assert_eq!(
+            parse_time("2023-11-14T22:13:20Z", now).unwrap(),
+            1_700_000_000_000_000_000
+        );
+        assert_eq!(format_time(now), "2023-11-14T22:13:20.000Z");
+        assert!(parse_time("yesterday", now).is_err());
+    }
+}
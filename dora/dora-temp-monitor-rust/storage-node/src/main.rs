@@ -0,0 +1,63 @@
+use dora_node_api::{arrow::array::StructArray, DoraNode, Event};
+use std::error::Error;
+use std::path::PathBuf;
+use storage_node::store::Store;
+use temp_common::alert::arrow_to_alerts;
+use temp_common::config::{env_or, parse_duration};
+use temp_common::reading::arrow_to_readings;
+
+/// 两次清理过期数据之间的最短间隔
+const PRUNE_INTERVAL_NS: u64 = 60_000_000_000;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    temp_common::config::load_file("storage")?;
+    let (mut _node, mut events) = DoraNode::init_from_env()?;
+
+    let path = PathBuf::from(env_or("STORE_PATH", "temp_history.db".to_owned())?);
+    // 数据保留时长，0 表示永久保留
+    let retention_ns =
+        parse_duration(&env_or("STORE_RETENTION", "0".to_owned())?)?.as_nanos() as u64;
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut store = Store::open(&path)?;
+    println!("🗄️ 存储节点启动 ({})", path.display());
+
+    let mut last_prune_ns = 0;
+    while let Some(event) = events.recv() {
+        if let Event::Input {
+            id,
+            metadata: _,
+            data,
+        } = event
+        {
+            let array = data
+                .as_any()
+                .downcast_ref::<StructArray>()
+                .ok_or("数据不是 StructArray")?;
+            // 单个批次写入失败只丢弃这一批，不让存储节点退出
+            let stored = match id.as_str() {
+                "smoothed" => arrow_to_readings(array).and_then(|r| store.insert_readings(&r)),
+                "alert" => arrow_to_alerts(array).and_then(|a| store.insert_alerts(&a)),
+                other => {
+                    eprintln!("Received input `{other}`");
+                    Ok(())
+                }
+            };
+            if let Err(e) = stored {
+                eprintln!("⚠️ 跳过无法写入的 `{id}` 批次: {e}");
+            }
+
+            let now_ns = temp_common::now_ns();
+            if retention_ns > 0 && now_ns.saturating_sub(last_prune_ns) >= PRUNE_INTERVAL_NS {
+                let removed = store.prune_before(now_ns.saturating_sub(retention_ns))?;
+                if removed > 0 {
+                    println!("🧹 清理了 {removed} 条过期记录");
+                }
+                last_prune_ns = now_ns;
+            }
+        }
+    }
+
+    Ok(())
+}
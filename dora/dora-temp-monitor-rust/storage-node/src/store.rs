@@ -0,0 +1,405 @@
+//! 温度历史的 SQLite 存储，存储节点写入，`temp-query` 只读查询
+
+use rusqlite::{params, Connection, OpenFlags};
+use std::error::Error;
+use std::path::Path;
+use temp_common::alert::{Alert, Severity};
+use temp_common::reading::Reading;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS readings (
+    timestamp_ns INTEGER NOT NULL,
+    sensor_id    TEXT    NOT NULL,
+    value        REAL    NOT NULL
+);
+CREATE INDEX IF NOT EXISTS readings_sensor_time ON readings (sensor_id, timestamp_ns);
+CREATE INDEX IF NOT EXISTS readings_time ON readings (timestamp_ns);
+
+CREATE TABLE IF NOT EXISTS alerts (
+    timestamp_ns INTEGER NOT NULL,
+    sensor_id    TEXT    NOT NULL,
+    kind         TEXT    NOT NULL,
+    severity     TEXT    NOT NULL,
+    state        TEXT    NOT NULL,
+    value        REAL,
+    baseline     REAL,
+    deviation    REAL,
+    message      TEXT    NOT NULL
+);
+CREATE INDEX IF NOT EXISTS alerts_sensor_time ON alerts (sensor_id, timestamp_ns);
+CREATE INDEX IF NOT EXISTS alerts_time ON alerts (timestamp_ns);
+";
+
+/// 查询条件：传感器为 `None` 时不过滤，时间范围为 `[from_ns, to_ns)`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    pub sensor_id: Option<String>,
+    pub from_ns: u64,
+    pub to_ns: u64,
+    /// 最多返回的行数，0 表示不限制
+    pub limit: usize,
+}
+
+/// 一个传感器的存储概况
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensorSummary {
+    pub sensor_id: String,
+    pub count: u64,
+    pub first_ns: u64,
+    pub last_ns: u64,
+}
+
+/// 降采样后的一个时间桶
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bucket {
+    pub start_ns: u64,
+    pub sensor_id: String,
+    pub count: u64,
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+}
+
+pub struct Store {
+    conn: Connection,
+}
+
+/// SQLite 的 INTEGER 是有符号 64 位，纳秒时间戳在 2262 年之前都不会溢出
+fn sql_ts(ns: u64) -> i64 {
+    ns.min(i64::MAX as u64) as i64
+}
+
+/// SQLite 把 NaN 当作 NULL，这里显式映射，读回时再还原为 NaN
+fn sql_real(v: f32) -> Option<f32> {
+    v.is_finite().then_some(v)
+}
+
+fn sql_limit(limit: usize) -> i64 {
+    if limit == 0 {
+        -1
+    } else {
+        limit as i64
+    }
+}
+
+impl Store {
+    /// 打开或创建数据库并建表，使用 WAL 以便写入时 `temp-query` 仍可读取
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        Self::init(conn)
+    }
+
+    /// 只读打开已有的数据库
+    pub fn open_read_only(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| format!("无法打开数据库: {e}"))?;
+        Ok(Self { conn })
+    }
+
+    pub fn open_in_memory() -> Result<Self, Box<dyn Error>> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, Box<dyn Error>> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// 一批读数在一个事务中写入
+    pub fn insert_readings(&mut self, readings: &[Reading]) -> Result<(), Box<dyn Error>> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO readings (timestamp_ns, sensor_id, value) VALUES (?1, ?2, ?3)",
+            )?;
+            for r in readings.iter().filter(|r| r.value.is_finite()) {
+                stmt.execute(params![sql_ts(r.timestamp_ns), r.sensor_id, r.value])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn insert_alerts(&mut self, alerts: &[Alert]) -> Result<(), Box<dyn Error>> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO alerts (timestamp_ns, sensor_id, kind, severity, state, value, baseline, deviation, message)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            for a in alerts {
+                stmt.execute(params![
+                    sql_ts(a.timestamp_ns),
+                    a.sensor_id,
+                    a.kind.as_str(),
+                    a.severity.as_str(),
+                    a.state.as_str(),
+                    sql_real(a.value),
+                    sql_real(a.baseline),
+                    sql_real(a.deviation),
+                    a.message,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 删除 `before_ns` 之前的读数和告警，返回删除的行数
+    pub fn prune_before(&mut self, before_ns: u64) -> Result<usize, Box<dyn Error>> {
+        let before = sql_ts(before_ns);
+        let tx = self.conn.transaction()?;
+        let removed = tx.execute("DELETE FROM readings WHERE timestamp_ns < ?1", [before])?
+            + tx.execute("DELETE FROM alerts WHERE timestamp_ns < ?1", [before])?;
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    pub fn sensors(&self) -> Result<Vec<SensorSummary>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT sensor_id, COUNT(*), MIN(timestamp_ns), MAX(timestamp_ns)
+             FROM readings GROUP BY sensor_id ORDER BY sensor_id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(SensorSummary {
+                sensor_id: row.get(0)?,
+                count: row.get::<_, i64>(1)? as u64,
+                first_ns: row.get::<_, i64>(2)? as u64,
+                last_ns: row.get::<_, i64>(3)? as u64,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// 时间范围内的原始读数，按时间排序
+    pub fn readings(&self, query: &Query) -> Result<Vec<Reading>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT timestamp_ns, sensor_id, value FROM readings
+             WHERE (?1 IS NULL OR sensor_id = ?1) AND timestamp_ns >= ?2 AND timestamp_ns < ?3
+             ORDER BY timestamp_ns, sensor_id LIMIT ?4",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                query.sensor_id,
+                sql_ts(query.from_ns),
+                sql_ts(query.to_ns),
+                sql_limit(query.limit)
+            ],
+            |row| {
+                Ok(Reading {
+                    timestamp_ns: row.get::<_, i64>(0)? as u64,
+                    sensor_id: row.get(1)?,
+                    value: row.get(2)?,
+                })
+            },
+        )?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// 按 `bucket_ns` 对齐的时间桶降采样，每个传感器每个桶一行
+    pub fn downsample(&self, query: &Query, bucket_ns: u64) -> Result<Vec<Bucket>, Box<dyn Error>> {
+        if bucket_ns == 0 {
+            return Err("降采样的时间桶必须大于 0".into());
+        }
+        let mut stmt = self.conn.prepare(
+            "SELECT (timestamp_ns / ?5) * ?5 AS bucket, sensor_id, COUNT(*), MIN(value), MAX(value), AVG(value)
+             FROM readings
+             WHERE (?1 IS NULL OR sensor_id = ?1) AND timestamp_ns >= ?2 AND timestamp_ns < ?3
+             GROUP BY bucket, sensor_id ORDER BY bucket, sensor_id LIMIT ?4",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                query.sensor_id,
+                sql_ts(query.from_ns),
+                sql_ts(query.to_ns),
+                sql_limit(query.limit),
+                sql_ts(bucket_ns)
+            ],
+            |row| {
+                Ok(Bucket {
+                    start_ns: row.get::<_, i64>(0)? as u64,
+                    sensor_id: row.get(1)?,
+                    count: row.get::<_, i64>(2)? as u64,
+                    min: row.get::<_, f64>(3)? as f32,
+                    max: row.get::<_, f64>(4)? as f32,
+                    mean: row.get::<_, f64>(5)? as f32,
+                })
+            },
+        )?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// 告警历史，最新的在前；`min_severity` 过滤掉更低级别的事件
+    pub fn alerts(
+        &self,
+        query: &Query,
+        min_severity: Option<Severity>,
+    ) -> Result<Vec<Alert>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT timestamp_ns, sensor_id, kind, severity, state, value, baseline, deviation, message
+             FROM alerts
+             WHERE (?1 IS NULL OR sensor_id = ?1) AND timestamp_ns >= ?2 AND timestamp_ns < ?3
+             ORDER BY timestamp_ns DESC",
+        )?;
+        let mut rows = stmt.query(params![
+            query.sensor_id,
+            sql_ts(query.from_ns),
+            sql_ts(query.to_ns)
+        ])?;
+
+        let mut alerts = Vec::new();
+        while let Some(row) = rows.next()? {
+            let alert = Alert {
+                timestamp_ns: row.get::<_, i64>(0)? as u64,
+                sensor_id: row.get(1)?,
+                kind: row.get::<_, String>(2)?.parse()?,
+                severity: row.get::<_, String>(3)?.parse()?,
+                state: row.get::<_, String>(4)?.parse()?,
+                value: row.get::<_, Option<f32>>(5)?.unwrap_or(f32::NAN),
+                baseline: row.get::<_, Option<f32>>(6)?.unwrap_or(f32::NAN),
+                deviation: row.get::<_, Option<f32>>(7)?.unwrap_or(f32::NAN),
+                message: row.get(8)?,
+            };
+            if min_severity.is_some_and(|min| alert.severity < min) {
+                continue;
+            }
+            alerts.push(alert);
+            if alerts.len() == query.limit {
+                break;
+            }
+        }
+        Ok(alerts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use temp_common::alert::{AlertKind, AlertState};
+
+    const SEC: u64 = 1_000_000_000;
+
+    fn all() -> Query {
+        Query {
+            to_ns: u64::MAX,
+            ..Query::default()
+        }
+    }
+
+    fn alert(ts: u64, severity: Severity, state: AlertState) -> Alert {
+        Alert {
+            timestamp_ns: ts,
+            sensor_id: "sensor0".to_owned(),
+            kind: AlertKind::High,
+            severity,
+            state,
+            value: 36.0,
+            baseline: 35.0,
+            deviation: 1.0,
+            message: "温度过高".to_owned(),
+        }
+    }
+
+    #[test]
+    fn range_queries_and_downsampling() {
+        let mut store = Store::open_in_memory().unwrap();
+        let readings: Vec<Reading> = (0..20u64)
+            .flat_map(|s| {
+                [
+                    Reading::new(s * SEC, "sensor0", s as f32),
+                    Reading::new(s * SEC, "sensor1", 30.0),
+                ]
+            })
+            .collect();
+        store.insert_readings(&readings).unwrap();
+        store
+            .insert_readings(&[Reading::new(20 * SEC, "sensor0", f32::NAN)])
+            .unwrap();
+
+        let sensors = store.sensors().unwrap();
+        assert_eq!(sensors.len(), 2);
+        assert_eq!(
+            (sensors[0].count, sensors[0].first_ns, sensors[0].last_ns),
+            (20, 0, 19 * SEC)
+        );
+
+        let range = store
+            .readings(&Query {
+                sensor_id: Some("sensor0".to_owned()),
+                from_ns: 5 * SEC,
+                to_ns: 8 * SEC,
+                limit: 0,
+            })
+            .unwrap();
+        let values: Vec<f32> = range.iter().map(|r| r.value).collect();
+        assert_eq!(values, [5.0, 6.0, 7.0]);
+        assert_eq!(
+            store.readings(&Query { limit: 3, ..all() }).unwrap().len(),
+            3
+        );
+
+        let buckets = store.downsample(&all(), 10 * SEC).unwrap();
+        assert_eq!(buckets.len(), 4);
+        assert_eq!(
+            buckets[2],
+            Bucket {
+                start_ns: 10 * SEC,
+                sensor_id: "sensor0".to_owned(),
+                count: 10,
+                min: 10.0,
+                max: 19.0,
+                mean: 14.5,
+            }
+        );
+    }
+
+    #[test]
+    fn alert_history_is_newest_first_and_filterable() {
+        let mut store = Store::open_in_memory().unwrap();
+        store
+            .insert_alerts(&[
+                alert(SEC, Severity::Warning, AlertState::Raised),
+                alert(2 * SEC, Severity::Critical, AlertState::Raised),
+                alert(3 * SEC, Severity::Info, AlertState::Cleared),
+            ])
+            .unwrap();
+
+        let history = store.alerts(&all(), None).unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].state, AlertState::Cleared);
+        assert_eq!(
+            history[2],
+            alert(SEC, Severity::Warning, AlertState::Raised)
+        );
+
+        let serious = store.alerts(&all(), Some(Severity::Warning)).unwrap();
+        assert_eq!(serious.len(), 2);
+        let latest = store
+            .alerts(&Query { limit: 1, ..all() }, Some(Severity::Warning))
+            .unwrap();
+        assert_eq!(latest[0].severity, Severity::Critical);
+
+        assert_eq!(store.prune_before(2 * SEC).unwrap(), 1);
+        assert_eq!(store.alerts(&all(), None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn non_finite_alert_fields_are_stored_as_null() {
+        let mut store = Store::open_in_memory().unwrap();
+        let mut no_forecast = alert(SEC, Severity::Warning, AlertState::Raised);
+        no_forecast.baseline = f32::NAN;
+        no_forecast.deviation = f32::NAN;
+        let mut no_value = alert(2 * SEC, Severity::Info, AlertState::Cleared);
+        no_value.value = f32::INFINITY;
+        store.insert_alerts(&[no_forecast, no_value]).unwrap();
+
+        let history = store.alerts(&all(), None).unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history[0].value.is_nan());
+        assert_eq!(history[0].baseline, 35.0);
+        assert_eq!(history[1].value, 36.0);
+        assert!(history[1].baseline.is_nan() && history[1].deviation.is_nan());
+    }
+}
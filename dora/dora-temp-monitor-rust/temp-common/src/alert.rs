@@ -0,0 +1,295 @@
+//! `temp_alert` 输出的告警记录及其 Arrow 编解码
+
+use dora_node_api::arrow::array::{
+    Array, ArrayRef, Float32Array, StringArray, StructArray, UInt64Array,
+};
+use dora_node_api::arrow::datatypes::{DataType, Field, Fields};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::column;
+
+/// 告警级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// 提示，例如告警解除
+    Info,
+    Warning,
+    Critical,
+}
+
+/// 告警类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertKind {
+    /// 平滑温度超过上限
+    High,
+    /// 平滑温度低于下限
+    Low,
+    /// 平滑温度变化率过大
+    RateOfChange,
+    /// 原始读数偏离平滑基线
+    Deviation,
+    /// 预测温度将越过上下限，实际温度尚未越过
+    Predicted,
+}
+
+/// 告警事件是触发还是解除
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertState {
+    Raised,
+    Cleared,
+}
+
+/// 一条告警记录，对应 `temp_alert` StructArray 中的一行
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    /// 触发时刻（Unix 纪元起的纳秒数）
+    pub timestamp_ns: u64,
+    pub sensor_id: String,
+    pub kind: AlertKind,
+    pub severity: Severity,
+    pub state: AlertState,
+    /// 触发判断的温度 (°C)
+    pub value: f32,
+    /// 比较基准，偏离告警为平滑均值，其余为对应阈值
+    pub baseline: f32,
+    /// 判断指标：超限量（预测告警为预测值的超限量）、变化率 (°C/s) 或偏离量
+    pub deviation: f32,
+    pub message: String,
+}
+
+impl Severity {
+    /// Arrow 中使用的机器可读名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+impl AlertKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertKind::High => "high",
+            AlertKind::Low => "low",
+            AlertKind::RateOfChange => "rate_of_change",
+            AlertKind::Deviation => "deviation",
+            AlertKind::Predicted => "predicted",
+        }
+    }
+}
+
+impl AlertState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertState::Raised => "raised",
+            AlertState::Cleared => "cleared",
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "info" => Ok(Severity::Info),
+            "warning" => Ok(Severity::Warning),
+            "critical" => Ok(Severity::Critical),
+            other => Err(format!("unknown severity `{other}`")),
+        }
+    }
+}
+
+impl FromStr for AlertKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "high" => Ok(AlertKind::High),
+            "low" => Ok(AlertKind::Low),
+            "rate_of_change" => Ok(AlertKind::RateOfChange),
+            "deviation" => Ok(AlertKind::Deviation),
+            "predicted" => Ok(AlertKind::Predicted),
+            other => Err(format!("unknown alert kind `{other}`")),
+        }
+    }
+}
+
+impl FromStr for AlertState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raised" => Ok(AlertState::Raised),
+            "cleared" => Ok(AlertState::Cleared),
+            other => Err(format!("unknown alert state `{other}`")),
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Severity::Info => "提示",
+            Severity::Warning => "警告",
+            Severity::Critical => "严重",
+        };
+        f.write_str(name)
+    }
+}
+
+impl fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AlertKind::High => "温度过高",
+            AlertKind::Low => "温度过低",
+            AlertKind::RateOfChange => "温度变化过快",
+            AlertKind::Deviation => "温度突变",
+            AlertKind::Predicted => "预计超限",
+        };
+        f.write_str(name)
+    }
+}
+
+/// `temp_alert` 的 Arrow 字段定义
+pub fn alert_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("timestamp_ns", DataType::UInt64, false),
+        Field::new("sensor_id", DataType::Utf8, false),
+        Field::new("kind", DataType::Utf8, false),
+        Field::new("severity", DataType::Utf8, false),
+        Field::new("state", DataType::Utf8, false),
+        Field::new("value", DataType::Float32, false),
+        Field::new("baseline", DataType::Float32, false),
+        Field::new("deviation", DataType::Float32, false),
+        Field::new("message", DataType::Utf8, false),
+    ])
+}
+
+/// 将告警记录编码为 Arrow StructArray
+pub fn alerts_to_arrow(alerts: &[Alert]) -> StructArray {
+    let arrays: Vec<ArrayRef> = vec![
+        Arc::new(UInt64Array::from_iter_values(
+            alerts.iter().map(|a| a.timestamp_ns),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            alerts.iter().map(|a| a.sensor_id.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            alerts.iter().map(|a| a.kind.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            alerts.iter().map(|a| a.severity.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            alerts.iter().map(|a| a.state.as_str()),
+        )),
+        Arc::new(Float32Array::from_iter_values(
+            alerts.iter().map(|a| a.value),
+        )),
+        Arc::new(Float32Array::from_iter_values(
+            alerts.iter().map(|a| a.baseline),
+        )),
+        Arc::new(Float32Array::from_iter_values(
+            alerts.iter().map(|a| a.deviation),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            alerts.iter().map(|a| a.message.as_str()),
+        )),
+    ];
+    StructArray::new(alert_fields(), arrays, None)
+}
+
+/// 将 Arrow StructArray 解码为告警记录
+pub fn arrow_to_alerts(array: &StructArray) -> Result<Vec<Alert>, Box<dyn Error>> {
+    let timestamp = column::<UInt64Array>(array, "timestamp_ns")?;
+    let sensor_id = column::<StringArray>(array, "sensor_id")?;
+    let kind = column::<StringArray>(array, "kind")?;
+    let severity = column::<StringArray>(array, "severity")?;
+    let state = column::<StringArray>(array, "state")?;
+    let value = column::<Float32Array>(array, "value")?;
+    let baseline = column::<Float32Array>(array, "baseline")?;
+    let deviation = column::<Float32Array>(array, "deviation")?;
+    let message = column::<StringArray>(array, "message")?;
+
+    let mut alerts = Vec::with_capacity(array.len());
+    for i in 0..array.len() {
+        alerts.push(Alert {
+            timestamp_ns: timestamp.value(i),
+            sensor_id: sensor_id.value(i).to_owned(),
+            kind: kind.value(i).parse()?,
+            severity: severity.value(i).parse()?,
+            state: state.value(i).parse()?,
+            value: value.value(i),
+            baseline: baseline.value(i),
+            deviation: deviation.value(i),
+            message: message.value(i).to_owned(),
+        });
+    }
+    Ok(alerts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alerts_round_trip_through_arrow() {
+        let alerts = vec![
+            Alert {
+                timestamp_ns: 1_700_000_000_000_000_000,
+                sensor_id: "rack1/cpu0".to_owned(),
+                kind: AlertKind::High,
+                severity: Severity::Critical,
+                state: AlertState::Raised,
+                value: 41.5,
+                baseline: 40.0,
+                deviation: 1.5,
+                message: "🔥 [严重] 温度过高: 41.5°C (阈值 40.0°C)".to_owned(),
+            },
+            Alert {
+                timestamp_ns: 1_700_000_001_000_000_000,
+                sensor_id: "rack1/cpu0".to_owned(),
+                kind: AlertKind::RateOfChange,
+                severity: Severity::Info,
+                state: AlertState::Cleared,
+                value: 30.0,
+                baseline: 1.0,
+                deviation: 0.2,
+                message: String::new(),
+            },
+        ];
+
+        let array = alerts_to_arrow(&alerts);
+        assert_eq!(array.len(), 2);
+        assert_eq!(arrow_to_alerts(&array).unwrap(), alerts);
+    }
+
+    #[test]
+    fn decoding_rejects_unknown_severity() {
+        let mut alert = Alert {
+            timestamp_ns: 0,
+            sensor_id: "s".to_owned(),
+            kind: AlertKind::Low,
+            severity: Severity::Warning,
+            state: AlertState::Raised,
+            value: 0.0,
+            baseline: 0.0,
+            deviation: 0.0,
+            message: String::new(),
+        };
+        let array = alerts_to_arrow(std::slice::from_ref(&alert));
+        let mut columns: Vec<ArrayRef> = array.columns().to_vec();
+        columns[3] = Arc::new(StringArray::from(vec!["panic"]));
+        let broken = StructArray::new(alert_fields(), columns, None);
+        assert!(arrow_to_alerts(&broken).is_err());
+
+        alert.severity = Severity::Info;
+        assert!(arrow_to_alerts(&alerts_to_arrow(&[alert])).is_ok());
+    }
+}
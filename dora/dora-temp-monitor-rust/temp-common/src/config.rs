@@ -0,0 +1,282 @@
+//! 节点配置：dataflow.yml 中的 `env:` 和可选的 TOML 配置文件
+//!
+//! 设置 `CONFIG_FILE` 时，节点启动时调用 [`load_file`] 读取文件顶层的共享配置和本节点的
+//! `[section]` 表，键名与环境变量相同（不区分大小写）。文件中的值优先于 `env:`，
+//! 修改文件后节点可以通过 [`reload_if_changed`] 热加载，无需重启数据流
+
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime};
+use toml::{Table, Value};
+
+/// 已加载的配置文件
+struct ConfigFile {
+    path: PathBuf,
+    section: String,
+    modified: Option<SystemTime>,
+    /// 大写键名 → 文本形式的值
+    values: HashMap<String, String>,
+}
+
+static FILE: RwLock<Option<ConfigFile>> = RwLock::new(None);
+
+/// 把 TOML 标量转换为与环境变量相同的文本，数组以逗号连接
+fn to_text(key: &str, value: &Value) -> Result<String, Box<dyn Error>> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Integer(n) => Ok(n.to_string()),
+        Value::Float(x) => Ok(x.to_string()),
+        Value::Boolean(b) => Ok(b.to_string()),
+        Value::Array(items) => Ok(items
+            .iter()
+            .map(|item| to_text(key, item))
+            .collect::<Result<Vec<_>, _>>()?
+            .join(",")),
+        other => Err(format!("{key} 的值 {other} 不是字符串、数字、布尔值或数组").into()),
+    }
+}
+
+/// 解析配置文件，取出顶层的共享配置和 `[section]` 表，后者覆盖前者
+fn parse_file(text: &str, section: &str) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let table: Table = text.parse()?;
+    let mut values = HashMap::new();
+    for (key, value) in &table {
+        if !value.is_table() {
+            values.insert(key.to_ascii_uppercase(), to_text(key, value)?);
+        }
+    }
+    match table.get(section) {
+        Some(Value::Table(own)) => {
+            for (key, value) in own {
+                let text = to_text(&format!("{section}.{key}"), value)?;
+                values.insert(key.to_ascii_uppercase(), text);
+            }
+        }
+        Some(_) => return Err(format!("[{section}] 必须是表").into()),
+        None => {}
+    }
+    Ok(values)
+}
+
+fn read_file(path: &Path, section: &str) -> Result<ConfigFile, Box<dyn Error>> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("无法读取配置文件 {}: {e}", path.display()))?;
+    let values =
+        parse_file(&text, section).map_err(|e| format!("配置文件 {} 无效: {e}", path.display()))?;
+    Ok(ConfigFile {
+        path: path.to_owned(),
+        section: section.to_owned(),
+        modified: fs::metadata(path).and_then(|m| m.modified()).ok(),
+        values,
+    })
+}
+
+/// 加载 `path` 中的共享配置和 `[section]` 表，替换之前加载的配置文件
+pub fn load_file_from(path: &Path, section: &str) -> Result<(), Box<dyn Error>> {
+    let file = read_file(path, section)?;
+    *FILE.write().expect("config lock poisoned") = Some(file);
+    Ok(())
+}
+
+/// 设置了 `CONFIG_FILE` 环境变量时加载其中的共享配置和 `[section]` 表，返回是否加载
+pub fn load_file(section: &str) -> Result<bool, Box<dyn Error>> {
+    match env::var("CONFIG_FILE") {
+        Ok(path) if !path.trim().is_empty() => {
+            let path = Path::new(path.trim());
+            load_file_from(path, section)?;
+            println!("⚙️ 已加载配置文件 {} [{section}]", path.display());
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// 配置文件的修改时间变化后重新读取，返回是否重新加载
+///
+/// 文件无效时返回错误并保留之前的配置
+pub fn reload_if_changed() -> Result<bool, Box<dyn Error>> {
+    let (path, section, modified) = match &*FILE.read().expect("config lock poisoned") {
+        Some(file) => (file.path.clone(), file.section.clone(), file.modified),
+        None => return Ok(false),
+    };
+    let current = fs::metadata(&path).and_then(|m| m.modified()).ok();
+    if current == modified {
+        return Ok(false);
+    }
+    let result = read_file(&path, &section);
+    let mut guard = FILE.write().expect("config lock poisoned");
+    match result {
+        Ok(file) => {
+            *guard = Some(file);
+            Ok(true)
+        }
+        Err(e) => {
+            // 记下修改时间，文件再次修改前不重复报告同一个错误
+            if let Some(file) = guard.as_mut() {
+                file.modified = current;
+            }
+            Err(e)
+        }
+    }
+}
+
+/// 读取配置项：配置文件中的值优先，其次是环境变量
+pub fn var(key: &str) -> Option<String> {
+    let file = FILE.read().expect("config lock poisoned");
+    file.as_ref()
+        .and_then(|file| file.values.get(key).cloned())
+        .or_else(|| env::var(key).ok())
+}
+
+/// 读取配置项（配置文件或 dataflow.yml 中的 `env:`），不存在时使用默认值
+pub fn env_or<T>(key: &str, default: T) -> Result<T, Box<dyn Error>>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    let from_file = {
+        let file = FILE.read().expect("config lock poisoned");
+        file.as_ref()
+            .and_then(|file| Some((file.path.clone(), file.values.get(key)?.clone())))
+    };
+    if let Some((path, value)) = from_file {
+        return value
+            .trim()
+            .parse()
+            .map_err(|e| format!("配置文件 {} 中 {key}={value} 无效: {e}", path.display()).into());
+    }
+    match env::var(key) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map_err(|e| format!("环境变量 {key}={value} 无效: {e}").into()),
+        Err(_) => Ok(default),
+    }
+}
+
+/// 解析 `500ms`、`10s`、`5m`、`1h` 形式的时长，不带单位时按秒计
+pub fn parse_duration(text: &str) -> Result<Duration, Box<dyn Error>> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|e| format!("无效的时长 `{text}`: {e}"))?;
+    let secs = match unit.trim() {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" | "min" => number * 60.0,
+        "h" => number * 3600.0,
+        other => return Err(format!("时长 `{text}` 的单位 `{other}` 无效").into()),
+    };
+    Duration::try_from_secs_f64(secs).map_err(|e| format!("时长 `{text}` 超出范围: {e}").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试结束时（包括断言失败）移除设置过的环境变量并卸载配置文件
+    #[derive(Default)]
+    struct GlobalState {
+        vars: Vec<&'static str>,
+    }
+
+    impl GlobalState {
+        fn set_var(&mut self, key: &'static str, value: &str) {
+            env::set_var(key, value);
+            self.vars.push(key);
+        }
+    }
+
+    impl Drop for GlobalState {
+        fn drop(&mut self) {
+            for key in &self.vars {
+                env::remove_var(key);
+            }
+            *FILE.write().unwrap_or_else(|e| e.into_inner()) = None;
+        }
+    }
+
+    #[test]
+    fn parses_durations_with_units() {
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("10").unwrap(), Duration::from_secs(10));
+        assert_eq!(parse_duration(" 5m ").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("1.5h").unwrap(), Duration::from_secs(5400));
+        assert!(parse_duration("1d").is_err());
+        assert!(parse_duration("m").is_err());
+        // 超出 Duration 范围时报错而不是 panic
+        let error = parse_duration("99999999999999999999h")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("超出范围"), "{error}");
+    }
+
+    #[test]
+    fn example_config_file_parses() {
+        let text = include_str!("../../config.toml");
+        let processor = parse_file(text, "processor").unwrap();
+        assert_eq!(processor["WINDOW_SIZE"], "10");
+        assert_eq!(processor["THRESHOLD"], "3");
+        assert!(!processor.contains_key("BAR_SCALE"));
+        assert_eq!(
+            parse_file(text, "downsample").unwrap()["DOWNSAMPLE_RANGES"],
+            "10m,1h,6h"
+        );
+        assert!(parse_file(text, "sensor").unwrap().is_empty());
+    }
+
+    #[test]
+    fn config_file_overrides_env_and_reloads_on_change() {
+        // 只有这个测试修改进程全局的配置文件和环境变量
+        let mut state = GlobalState::default();
+        let dir = env::temp_dir().join(format!("temp-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("temp.toml");
+        let write = |text: &str| fs::write(&path, text).unwrap();
+        // 本测试只使用 TEST_CONFIG_* 键，不影响其他测试读取的配置
+        state.set_var("TEST_CONFIG_ENV_ONLY", "7");
+        state.set_var("TEST_CONFIG_THRESHOLD", "1.0");
+        write(
+            "test_config_shared = \"1m, 1h\"\ntest_config_threshold = 2.0\n\n\
+             [processor]\ntest_config_threshold = 3.5\ntest_config_ranges = [\"10m\", \"6h\"]\n\n\
+             [logger]\ntest_config_threshold = 9.0\n",
+        );
+        load_file_from(&path, "processor").unwrap();
+
+        assert_eq!(env_or("TEST_CONFIG_THRESHOLD", 0.0).unwrap(), 3.5);
+        assert_eq!(env_or("TEST_CONFIG_ENV_ONLY", 0).unwrap(), 7);
+        assert_eq!(env_or("TEST_CONFIG_MISSING", 4).unwrap(), 4);
+        assert_eq!(var("TEST_CONFIG_SHARED").unwrap(), "1m, 1h");
+        assert_eq!(var("TEST_CONFIG_RANGES").unwrap(), "10m,6h");
+        assert!(!reload_if_changed().unwrap());
+
+        // 修改时间变化后重新读取；无效的文件报错并保留之前的配置
+        std::thread::sleep(Duration::from_millis(20));
+        write("[processor]\ntest_config_threshold = \"high\"\n");
+        assert!(reload_if_changed().unwrap());
+        let error = env_or("TEST_CONFIG_THRESHOLD", 0.0)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("TEST_CONFIG_THRESHOLD=high"), "{error}");
+
+        std::thread::sleep(Duration::from_millis(20));
+        write("[processor\n");
+        assert!(reload_if_changed().is_err());
+        assert!(!reload_if_changed().unwrap());
+        assert!(env_or::<f64>("TEST_CONFIG_THRESHOLD", 0.0).is_err());
+        assert!(parse_file("[processor]\nx = { a = 1 }\n", "processor").is_err());
+
+        *FILE.write().unwrap() = None;
+        assert_eq!(env_or("TEST_CONFIG_THRESHOLD", 0.0).unwrap(), 1.0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
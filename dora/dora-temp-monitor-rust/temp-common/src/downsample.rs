@@ -0,0 +1,124 @@
+//! Largest-Triangle-Three-Buckets (LTTB) 降采样：把长时间序列压缩为固定点数，
+//! 保留峰谷等曲线形状，供图表前端显示数小时以上的数据
+//!
+//! 首尾两点总是保留；其余点均分为 `threshold - 2` 个桶，每个桶选出与前一个选中点、
+//! 下一个桶的平均点构成的三角形面积最大的点
+
+use crate::reading::Reading;
+
+/// 元数据参数：`temp_series` 消息覆盖的时间范围，即配置或请求中的时长原文，例如 `1h`
+pub const RANGE_PARAM: &str = "range";
+/// 元数据参数：每个传感器最多的点数
+pub const POINTS_PARAM: &str = "points";
+
+/// 从按 x 升序排列的 `points` 中选出至多 `threshold` 个点，返回其下标（升序）
+///
+/// `threshold` 不小于点数时返回全部下标
+pub fn lttb_indices(points: &[(f64, f64)], threshold: usize) -> Vec<usize> {
+    let n = points.len();
+    if threshold >= n {
+        return (0..n).collect();
+    }
+    match threshold {
+        0 => return Vec::new(),
+        1 => return vec![0],
+        2 => return vec![0, n - 1],
+        _ => {}
+    }
+
+    // 第 i 个桶的起始下标：除首尾外的 n - 2 个点均分为 threshold - 2 个桶
+    let bucket_start = |i: usize| i * (n - 2) / (threshold - 2) + 1;
+
+    let mut selected = Vec::with_capacity(threshold);
+    selected.push(0);
+    let mut a = 0;
+    for i in 0..threshold - 2 {
+        let (start, end) = (bucket_start(i), bucket_start(i + 1));
+        // 下一个桶的平均点；最后一个桶之后只有末尾点
+        let next = &points[end..bucket_start(i + 2).min(n)];
+        let count = next.len() as f64;
+        let avg_x = next.iter().map(|p| p.0).sum::<f64>() / count;
+        let avg_y = next.iter().map(|p| p.1).sum::<f64>() / count;
+
+        let (ax, ay) = points[a];
+        let mut max_area = -1.0;
+        let mut max_index = start;
+        for (j, &(x, y)) in points.iter().enumerate().take(end).skip(start) {
+            // 三角形面积的两倍，只用于比较
+            let area = ((ax - avg_x) * (y - ay) - (ax - x) * (avg_y - ay)).abs();
+            if area > max_area {
+                max_area = area;
+                max_index = j;
+            }
+        }
+        selected.push(max_index);
+        a = max_index;
+    }
+    selected.push(n - 1);
+    selected
+}
+
+/// 把一个传感器按时间升序排列的读数降采样为至多 `threshold` 个
+pub fn lttb(readings: &[Reading], threshold: usize) -> Vec<Reading> {
+    let Some(first) = readings.first() else {
+        return Vec::new();
+    };
+    // 以首个读数为原点换算为秒，避免纳秒时间戳转为 f64 时丢失精度
+    let points: Vec<(f64, f64)> = readings
+        .iter()
+        .map(|r| {
+            let t = r.timestamp_ns.saturating_sub(first.timestamp_ns) as f64 / 1e9;
+            (t, r.value as f64)
+        })
+        .collect();
+    lttb_indices(&points, threshold)
+        .into_iter()
+        .map(|i| readings[i].clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_endpoints_and_peaks_within_bound() {
+        // 平稳的 25°C 中有一个尖峰和一个低谷
+        let readings: Vec<Reading> = (0..1000u64)
+            .map(|i| {
+                let value = match i {
+                    300 => 40.0,
+                    700 => 10.0,
+                    _ => 25.0,
+                };
+                Reading::new(i * 100_000_000, "sensor0", value)
+            })
+            .collect();
+
+        let sampled = lttb(&readings, 50);
+        assert_eq!(sampled.len(), 50);
+        assert_eq!(sampled[0], readings[0]);
+        assert_eq!(sampled[49], readings[999]);
+        assert!(sampled
+            .windows(2)
+            .all(|w| w[0].timestamp_ns < w[1].timestamp_ns));
+        assert!(sampled.iter().any(|r| r.value == 40.0));
+        assert!(sampled.iter().any(|r| r.value == 10.0));
+
+        assert_eq!(lttb(&readings[..10], 50).len(), 10);
+        assert_eq!(
+            lttb(&readings, 2),
+            [readings[0].clone(), readings[999].clone()]
+        );
+        assert!(lttb(&[], 10).is_empty());
+    }
+
+    #[test]
+    fn picks_largest_triangle_in_each_bucket() {
+        // 手算：8 个中间点分为 [1, 5) 和 [5, 9) 两个桶
+        let ys = [0.0, 1.0, 0.0, 5.0, 0.0, 1.0, 0.0, 2.0, 0.0, 1.0];
+        let points: Vec<(f64, f64)> = ys.iter().enumerate().map(|(i, &y)| (i as f64, y)).collect();
+        assert_eq!(lttb_indices(&points, 4), [0, 3, 6, 9]);
+        assert_eq!(lttb_indices(&points, 10).len(), 10);
+    }
+}
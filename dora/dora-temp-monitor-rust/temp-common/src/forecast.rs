@@ -0,0 +1,103 @@
+//! `temp_forecast` 输出的温度预测记录及其 Arrow 编解码
+
+use dora_node_api::arrow::array::{
+    Array, ArrayRef, Float32Array, StringArray, StructArray, UInt64Array,
+};
+use dora_node_api::arrow::datatypes::{DataType, Field, Fields};
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::column;
+
+/// 一个传感器在 `horizon_s` 秒后的温度预测
+#[derive(Debug, Clone, PartialEq)]
+pub struct Forecast {
+    /// 做出预测的时刻，即最新读数的时间（Unix 纪元起的纳秒数）
+    pub timestamp_ns: u64,
+    pub sensor_id: String,
+    /// 预测提前量（秒）
+    pub horizon_s: f32,
+    /// 预测温度 (°C)
+    pub value: f32,
+    /// 置信区间下界 (°C)
+    pub lower: f32,
+    /// 置信区间上界 (°C)
+    pub upper: f32,
+}
+
+/// `temp_forecast` 的 Arrow 字段定义
+pub fn forecast_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("timestamp_ns", DataType::UInt64, false),
+        Field::new("sensor_id", DataType::Utf8, false),
+        Field::new("horizon_s", DataType::Float32, false),
+        Field::new("value", DataType::Float32, false),
+        Field::new("lower", DataType::Float32, false),
+        Field::new("upper", DataType::Float32, false),
+    ])
+}
+
+/// 将预测记录编码为 Arrow StructArray
+pub fn forecasts_to_arrow(forecasts: &[Forecast]) -> StructArray {
+    let arrays: Vec<ArrayRef> = vec![
+        Arc::new(UInt64Array::from_iter_values(
+            forecasts.iter().map(|f| f.timestamp_ns),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            forecasts.iter().map(|f| f.sensor_id.as_str()),
+        )),
+        Arc::new(Float32Array::from_iter_values(
+            forecasts.iter().map(|f| f.horizon_s),
+        )),
+        Arc::new(Float32Array::from_iter_values(
+            forecasts.iter().map(|f| f.value),
+        )),
+        Arc::new(Float32Array::from_iter_values(
+            forecasts.iter().map(|f| f.lower),
+        )),
+        Arc::new(Float32Array::from_iter_values(
+            forecasts.iter().map(|f| f.upper),
+        )),
+    ];
+    StructArray::new(forecast_fields(), arrays, None)
+}
+
+/// 将 Arrow StructArray 解码为预测记录
+pub fn arrow_to_forecasts(array: &StructArray) -> Result<Vec<Forecast>, Box<dyn Error>> {
+    let timestamp = column::<UInt64Array>(array, "timestamp_ns")?;
+    let sensor_id = column::<StringArray>(array, "sensor_id")?;
+    let horizon = column::<Float32Array>(array, "horizon_s")?;
+    let value = column::<Float32Array>(array, "value")?;
+    let lower = column::<Float32Array>(array, "lower")?;
+    let upper = column::<Float32Array>(array, "upper")?;
+
+    Ok((0..array.len())
+        .map(|i| Forecast {
+            timestamp_ns: timestamp.value(i),
+            sensor_id: sensor_id.value(i).to_owned(),
+            horizon_s: horizon.value(i),
+            value: value.value(i),
+            lower: lower.value(i),
+            upper: upper.value(i),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forecasts_round_trip_through_arrow() {
+        let forecasts = vec![Forecast {
+            timestamp_ns: 1_700_000_000_000_000_000,
+            sensor_id: "rack1/cpu0".to_owned(),
+            horizon_s: 30.0,
+            value: 38.2,
+            lower: 36.9,
+            upper: 39.5,
+        }];
+        let array = forecasts_to_arrow(&forecasts);
+        assert_eq!(arrow_to_forecasts(&array).unwrap(), forecasts);
+    }
+}
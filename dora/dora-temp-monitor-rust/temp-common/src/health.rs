@@ -0,0 +1,192 @@
+//! `sensor_health` 输出的数据质量事件及其 Arrow 编解码
+
+use dora_node_api::arrow::array::{
+    Array, ArrayRef, Float32Array, StringArray, StructArray, UInt64Array,
+};
+use dora_node_api::arrow::datatypes::{DataType, Field, Fields};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::alert::{AlertState, Severity};
+use crate::column;
+
+/// 批次级问题（空批次、格式错误）使用的传感器 ID
+pub const BATCH_SENSOR_ID: &str = "*";
+
+/// 数据质量问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HealthIssue {
+    /// 连续若干个读数几乎不变，传感器可能卡死
+    Stuck,
+    /// 超过预期间隔没有收到读数
+    Dropout,
+    /// 超出物理上可能的温度范围
+    OutOfRange,
+    /// NaN 或无穷大
+    NonFinite,
+    /// 收到不含任何读数的批次
+    EmptyBatch,
+    /// 批次不是读数 StructArray 或缺少字段
+    Malformed,
+}
+
+/// 一条数据质量事件，问题出现时 `Raised`，恢复时 `Cleared`
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthEvent {
+    /// 发现问题的时刻（Unix 纪元起的纳秒数）
+    pub timestamp_ns: u64,
+    /// 传感器 ID，批次级问题为 [`BATCH_SENSOR_ID`]
+    pub sensor_id: String,
+    pub issue: HealthIssue,
+    pub severity: Severity,
+    pub state: AlertState,
+    /// 相关数值：异常读数 (°C)、卡死时的读数或断流时长 (s)，没有时为 NaN
+    pub value: f32,
+    pub message: String,
+}
+
+impl HealthIssue {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthIssue::Stuck => "stuck",
+            HealthIssue::Dropout => "dropout",
+            HealthIssue::OutOfRange => "out_of_range",
+            HealthIssue::NonFinite => "non_finite",
+            HealthIssue::EmptyBatch => "empty_batch",
+            HealthIssue::Malformed => "malformed",
+        }
+    }
+}
+
+impl FromStr for HealthIssue {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stuck" => Ok(HealthIssue::Stuck),
+            "dropout" => Ok(HealthIssue::Dropout),
+            "out_of_range" => Ok(HealthIssue::OutOfRange),
+            "non_finite" => Ok(HealthIssue::NonFinite),
+            "empty_batch" => Ok(HealthIssue::EmptyBatch),
+            "malformed" => Ok(HealthIssue::Malformed),
+            other => Err(format!("unknown health issue `{other}`")),
+        }
+    }
+}
+
+impl fmt::Display for HealthIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HealthIssue::Stuck => "读数卡死",
+            HealthIssue::Dropout => "数据中断",
+            HealthIssue::OutOfRange => "读数越界",
+            HealthIssue::NonFinite => "非有限值",
+            HealthIssue::EmptyBatch => "空批次",
+            HealthIssue::Malformed => "格式错误",
+        };
+        f.write_str(name)
+    }
+}
+
+/// `sensor_health` 的 Arrow 字段定义
+pub fn health_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("timestamp_ns", DataType::UInt64, false),
+        Field::new("sensor_id", DataType::Utf8, false),
+        Field::new("issue", DataType::Utf8, false),
+        Field::new("severity", DataType::Utf8, false),
+        Field::new("state", DataType::Utf8, false),
+        Field::new("value", DataType::Float32, false),
+        Field::new("message", DataType::Utf8, false),
+    ])
+}
+
+/// 将数据质量事件编码为 Arrow StructArray
+pub fn health_to_arrow(events: &[HealthEvent]) -> StructArray {
+    let arrays: Vec<ArrayRef> = vec![
+        Arc::new(UInt64Array::from_iter_values(
+            events.iter().map(|e| e.timestamp_ns),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            events.iter().map(|e| e.sensor_id.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            events.iter().map(|e| e.issue.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            events.iter().map(|e| e.severity.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            events.iter().map(|e| e.state.as_str()),
+        )),
+        Arc::new(Float32Array::from_iter_values(
+            events.iter().map(|e| e.value),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            events.iter().map(|e| e.message.as_str()),
+        )),
+    ];
+    StructArray::new(health_fields(), arrays, None)
+}
+
+/// 将 Arrow StructArray 解码为数据质量事件
+pub fn arrow_to_health(array: &StructArray) -> Result<Vec<HealthEvent>, Box<dyn Error>> {
+    let timestamp = column::<UInt64Array>(array, "timestamp_ns")?;
+    let sensor_id = column::<StringArray>(array, "sensor_id")?;
+    let issue = column::<StringArray>(array, "issue")?;
+    let severity = column::<StringArray>(array, "severity")?;
+    let state = column::<StringArray>(array, "state")?;
+    let value = column::<Float32Array>(array, "value")?;
+    let message = column::<StringArray>(array, "message")?;
+
+    let mut events = Vec::with_capacity(array.len());
+    for i in 0..array.len() {
+        events.push(HealthEvent {
+            timestamp_ns: timestamp.value(i),
+            sensor_id: sensor_id.value(i).to_owned(),
+            issue: issue.value(i).parse()?,
+            severity: severity.value(i).parse()?,
+            state: state.value(i).parse()?,
+            value: value.value(i),
+            message: message.value(i).to_owned(),
+        });
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn health_events_round_trip_through_arrow() {
+        let events = vec![
+            HealthEvent {
+                timestamp_ns: 1_700_000_000_000_000_000,
+                sensor_id: "rack1/cpu0".to_owned(),
+                issue: HealthIssue::Stuck,
+                severity: Severity::Warning,
+                state: AlertState::Raised,
+                value: 25.0,
+                message: "连续 50 个读数不变".to_owned(),
+            },
+            HealthEvent {
+                timestamp_ns: 1_700_000_001_000_000_000,
+                sensor_id: BATCH_SENSOR_ID.to_owned(),
+                issue: HealthIssue::Malformed,
+                severity: Severity::Critical,
+                state: AlertState::Raised,
+                value: f32::NAN,
+                message: String::new(),
+            },
+        ];
+
+        let decoded = arrow_to_health(&health_to_arrow(&events)).unwrap();
+        assert_eq!(decoded[0], events[0]);
+        assert_eq!(decoded[1].issue, HealthIssue::Malformed);
+        assert!(decoded[1].value.is_nan());
+        assert!("unplugged".parse::<HealthIssue>().is_err());
+    }
+}
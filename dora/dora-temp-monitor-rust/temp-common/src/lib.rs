@@ -0,0 +1,64 @@
+//! 温度监控数据流各节点共享的消息格式与工具函数
+
+use dora_node_api::arrow::array::{Array, StructArray};
+use dora_node_api::Metadata;
+use std::error::Error;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub mod alert;
+pub mod config;
+pub mod downsample;
+pub mod forecast;
+pub mod health;
+pub mod reading;
+pub mod stats;
+pub mod units;
+
+/// processor → logger 等下游节点的输出 ID，须与 dataflow.yml 保持一致
+pub const TEMP_RAW: &str = "temp_raw";
+pub const TEMP_SMOOTHED: &str = "temp_smoothed";
+pub const TEMP_ALERT: &str = "temp_alert";
+pub const TEMP_FORECAST: &str = "temp_forecast";
+pub const SENSOR_HEALTH: &str = "sensor_health";
+pub const TEMP_STATS: &str = "temp_stats";
+pub const TEMP_SERIES: &str = "temp_series";
+
+/// 单传感器场景下使用的默认传感器 ID
+pub const DEFAULT_SENSOR_ID: &str = "sensor0";
+
+/// 消息的发送时间（Unix 纪元起的纳秒数），取自 dora 的 HLC 时间戳
+pub fn timestamp_ns(metadata: &Metadata) -> u64 {
+    metadata.timestamp().get_time().to_duration().as_nanos() as u64
+}
+
+/// 当前系统时间（Unix 纪元起的纳秒数）
+pub fn now_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+/// 把 Unix 纳秒时间戳格式化为 UTC 的 `HH:MM:SS.mmm`
+pub fn format_time(timestamp_ns: u64) -> String {
+    let millis = timestamp_ns / 1_000_000;
+    let secs = millis / 1000;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60,
+        millis % 1000
+    )
+}
+
+/// 按列名取出 StructArray 中指定类型的子数组
+pub(crate) fn column<'a, T: Array + 'static>(
+    array: &'a StructArray,
+    name: &str,
+) -> Result<&'a T, Box<dyn Error>> {
+    array
+        .column_by_name(name)
+        .and_then(|column| column.as_any().downcast_ref::<T>())
+        .ok_or_else(|| format!("Missing or incorrect `{name}` column").into())
+}
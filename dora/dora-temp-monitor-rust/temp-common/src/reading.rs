@@ -0,0 +1,83 @@
+//! `temp_raw` / `temp_smoothed` 输出的带传感器 ID 和时间戳的读数批次
+
+use dora_node_api::arrow::array::{
+    Array, ArrayRef, Float32Array, StringArray, StructArray, UInt64Array,
+};
+use dora_node_api::arrow::datatypes::{DataType, Field, Fields};
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::column;
+
+/// 一个传感器在某一时刻的温度读数
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reading {
+    /// 采样时刻（Unix 纪元起的纳秒数）
+    pub timestamp_ns: u64,
+    pub sensor_id: String,
+    /// 温度 (°C)
+    pub value: f32,
+}
+
+impl Reading {
+    pub fn new(timestamp_ns: u64, sensor_id: &str, value: f32) -> Self {
+        Self {
+            timestamp_ns,
+            sensor_id: sensor_id.to_owned(),
+            value,
+        }
+    }
+}
+
+/// 读数批次的 Arrow 字段定义
+pub fn reading_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("timestamp_ns", DataType::UInt64, false),
+        Field::new("sensor_id", DataType::Utf8, false),
+        Field::new("value", DataType::Float32, false),
+    ])
+}
+
+/// 将一批读数编码为 Arrow StructArray
+pub fn readings_to_arrow(readings: &[Reading]) -> StructArray {
+    let arrays: Vec<ArrayRef> = vec![
+        Arc::new(UInt64Array::from_iter_values(
+            readings.iter().map(|r| r.timestamp_ns),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            readings.iter().map(|r| r.sensor_id.as_str()),
+        )),
+        Arc::new(Float32Array::from_iter_values(
+            readings.iter().map(|r| r.value),
+        )),
+    ];
+    StructArray::new(reading_fields(), arrays, None)
+}
+
+/// 将 Arrow StructArray 解码为读数批次
+pub fn arrow_to_readings(array: &StructArray) -> Result<Vec<Reading>, Box<dyn Error>> {
+    let timestamp = column::<UInt64Array>(array, "timestamp_ns")?;
+    let sensor_id = column::<StringArray>(array, "sensor_id")?;
+    let value = column::<Float32Array>(array, "value")?;
+
+    Ok((0..array.len())
+        .map(|i| Reading::new(timestamp.value(i), sensor_id.value(i), value.value(i)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readings_round_trip_through_arrow() {
+        let readings = vec![
+            Reading::new(1, "rack1/cpu0", 41.5),
+            Reading::new(1, "rack1/cpu1", 39.0),
+            Reading::new(2, "rack2/cpu0", f32::NAN),
+        ];
+        let decoded = arrow_to_readings(&readings_to_arrow(&readings)).unwrap();
+        assert_eq!(decoded[..2], readings[..2]);
+        assert!(decoded[2].value.is_nan());
+    }
+}
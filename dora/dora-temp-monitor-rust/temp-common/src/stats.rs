@@ -0,0 +1,176 @@
+//! `temp_stats` 输出的窗口统计记录及其 Arrow 编解码
+
+use dora_node_api::arrow::array::{
+    Array, ArrayRef, Float32Array, StringArray, StructArray, UInt64Array,
+};
+use dora_node_api::arrow::datatypes::{DataType, Field, Fields};
+use std::error::Error;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::column;
+
+/// 窗口类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WindowMode {
+    /// 首尾相接、互不重叠的窗口，每个读数只计入一个窗口
+    Tumbling,
+    /// 按固定步长滑动、相互重叠的窗口
+    Sliding,
+}
+
+impl WindowMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WindowMode::Tumbling => "tumbling",
+            WindowMode::Sliding => "sliding",
+        }
+    }
+}
+
+impl FromStr for WindowMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tumbling" => Ok(WindowMode::Tumbling),
+            "sliding" => Ok(WindowMode::Sliding),
+            other => Err(format!("unknown window mode `{other}`")),
+        }
+    }
+}
+
+/// 一个传感器在一个时间窗口 `[window_start_ns, window_end_ns)` 内的统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowStats {
+    pub window_start_ns: u64,
+    pub window_end_ns: u64,
+    pub sensor_id: String,
+    /// 窗口名称，即配置中的时长，例如 `1m`、`1h`
+    pub window: String,
+    pub mode: WindowMode,
+    pub count: u64,
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    /// 样本标准差，只有一个样本时为 0
+    pub stddev: f32,
+    pub p50: f32,
+    pub p95: f32,
+    pub p99: f32,
+}
+
+/// `temp_stats` 的 Arrow 字段定义
+pub fn stats_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("window_start_ns", DataType::UInt64, false),
+        Field::new("window_end_ns", DataType::UInt64, false),
+        Field::new("sensor_id", DataType::Utf8, false),
+        Field::new("window", DataType::Utf8, false),
+        Field::new("mode", DataType::Utf8, false),
+        Field::new("count", DataType::UInt64, false),
+        Field::new("min", DataType::Float32, false),
+        Field::new("max", DataType::Float32, false),
+        Field::new("mean", DataType::Float32, false),
+        Field::new("stddev", DataType::Float32, false),
+        Field::new("p50", DataType::Float32, false),
+        Field::new("p95", DataType::Float32, false),
+        Field::new("p99", DataType::Float32, false),
+    ])
+}
+
+/// 将窗口统计编码为 Arrow StructArray
+pub fn stats_to_arrow(stats: &[WindowStats]) -> StructArray {
+    let float = |f: fn(&WindowStats) -> f32| -> ArrayRef {
+        Arc::new(Float32Array::from_iter_values(stats.iter().map(f)))
+    };
+    let arrays: Vec<ArrayRef> = vec![
+        Arc::new(UInt64Array::from_iter_values(
+            stats.iter().map(|s| s.window_start_ns),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            stats.iter().map(|s| s.window_end_ns),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            stats.iter().map(|s| s.sensor_id.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            stats.iter().map(|s| s.window.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            stats.iter().map(|s| s.mode.as_str()),
+        )),
+        Arc::new(UInt64Array::from_iter_values(stats.iter().map(|s| s.count))),
+        float(|s| s.min),
+        float(|s| s.max),
+        float(|s| s.mean),
+        float(|s| s.stddev),
+        float(|s| s.p50),
+        float(|s| s.p95),
+        float(|s| s.p99),
+    ];
+    StructArray::new(stats_fields(), arrays, None)
+}
+
+/// 将 Arrow StructArray 解码为窗口统计
+pub fn arrow_to_stats(array: &StructArray) -> Result<Vec<WindowStats>, Box<dyn Error>> {
+    let start = column::<UInt64Array>(array, "window_start_ns")?;
+    let end = column::<UInt64Array>(array, "window_end_ns")?;
+    let sensor_id = column::<StringArray>(array, "sensor_id")?;
+    let window = column::<StringArray>(array, "window")?;
+    let mode = column::<StringArray>(array, "mode")?;
+    let count = column::<UInt64Array>(array, "count")?;
+    let min = column::<Float32Array>(array, "min")?;
+    let max = column::<Float32Array>(array, "max")?;
+    let mean = column::<Float32Array>(array, "mean")?;
+    let stddev = column::<Float32Array>(array, "stddev")?;
+    let p50 = column::<Float32Array>(array, "p50")?;
+    let p95 = column::<Float32Array>(array, "p95")?;
+    let p99 = column::<Float32Array>(array, "p99")?;
+
+    let mut stats = Vec::with_capacity(array.len());
+    for i in 0..array.len() {
+        stats.push(WindowStats {
+            window_start_ns: start.value(i),
+            window_end_ns: end.value(i),
+            sensor_id: sensor_id.value(i).to_owned(),
+            window: window.value(i).to_owned(),
+            mode: mode.value(i).parse()?,
+            count: count.value(i),
+            min: min.value(i),
+            max: max.value(i),
+            mean: mean.value(i),
+            stddev: stddev.value(i),
+            p50: p50.value(i),
+            p95: p95.value(i),
+            p99: p99.value(i),
+        });
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_round_trip_through_arrow() {
+        let stats = vec![WindowStats {
+            window_start_ns: 1_700_000_040_000_000_000,
+            window_end_ns: 1_700_000_100_000_000_000,
+            sensor_id: "rack1/cpu0".to_owned(),
+            window: "1m".to_owned(),
+            mode: WindowMode::Sliding,
+            count: 600,
+            min: 24.1,
+            max: 27.9,
+            mean: 25.6,
+            stddev: 0.8,
+            p50: 25.5,
+            p95: 27.1,
+            p99: 27.7,
+        }];
+        let array = stats_to_arrow(&stats);
+        assert_eq!(arrow_to_stats(&array).unwrap(), stats);
+    }
+}
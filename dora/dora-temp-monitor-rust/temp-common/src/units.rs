@@ -0,0 +1,168 @@
+//! 温度单位及其在消息元数据参数中的表示
+//!
+//! 数据流中 `value` 列统一为摄氏度；`unit` 参数声明该列的单位，
+//! `source_units` 参数以 `传感器ID=单位` 列表记录各传感器换算前的原始单位
+
+use dora_node_api::{MetadataParameters, Parameter};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// 元数据参数：`value` 列的单位，缺省视为摄氏度
+pub const UNIT_PARAM: &str = "unit";
+/// 元数据参数：各传感器的原始单位，`["sensor0=fahrenheit", ...]`，未列出的为摄氏度
+pub const SOURCE_UNITS_PARAM: &str = "source_units";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+    /// 未经标定的 ADC 原始计数，不能直接换算为温度
+    Raw,
+}
+
+impl Unit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Unit::Celsius => "celsius",
+            Unit::Fahrenheit => "fahrenheit",
+            Unit::Kelvin => "kelvin",
+            Unit::Raw => "raw",
+        }
+    }
+
+    /// 显示用的单位符号
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Celsius => "°C",
+            Unit::Fahrenheit => "°F",
+            Unit::Kelvin => "K",
+            Unit::Raw => "",
+        }
+    }
+
+    /// 换算为摄氏度；原始计数需要先标定，返回 `None`
+    pub fn to_celsius(&self, value: f64) -> Option<f64> {
+        match self {
+            Unit::Celsius => Some(value),
+            Unit::Fahrenheit => Some((value - 32.0) * 5.0 / 9.0),
+            Unit::Kelvin => Some(value - 273.15),
+            Unit::Raw => None,
+        }
+    }
+
+    /// 摄氏度换算为本单位，用于显示
+    pub fn from_celsius(&self, celsius: f64) -> f64 {
+        match self {
+            Unit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+            Unit::Kelvin => celsius + 273.15,
+            Unit::Celsius | Unit::Raw => celsius,
+        }
+    }
+
+    /// 温差（如标准差、阈值）换算为本单位，不含零点偏移
+    pub fn from_celsius_delta(&self, delta: f64) -> f64 {
+        match self {
+            Unit::Fahrenheit => delta * 9.0 / 5.0,
+            _ => delta,
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Unit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "celsius" | "c" | "°c" => Ok(Unit::Celsius),
+            "fahrenheit" | "f" | "°f" => Ok(Unit::Fahrenheit),
+            "kelvin" | "k" => Ok(Unit::Kelvin),
+            "raw" | "adc" | "counts" => Ok(Unit::Raw),
+            other => Err(format!("未知的温度单位 `{other}`")),
+        }
+    }
+}
+
+/// 读取 `unit` 参数，缺省为摄氏度
+pub fn unit_param(parameters: &MetadataParameters) -> Result<Unit, Box<dyn Error>> {
+    match parameters.get(UNIT_PARAM) {
+        None => Ok(Unit::Celsius),
+        Some(Parameter::String(unit)) => Ok(unit.parse()?),
+        Some(other) => Err(format!("参数 `{UNIT_PARAM}` 类型错误: {other:?}").into()),
+    }
+}
+
+/// 读取 `source_units` 参数
+pub fn source_units_param(
+    parameters: &MetadataParameters,
+) -> Result<Vec<(String, Unit)>, Box<dyn Error>> {
+    let Some(param) = parameters.get(SOURCE_UNITS_PARAM) else {
+        return Ok(Vec::new());
+    };
+    let Parameter::ListString(entries) = param else {
+        return Err(format!("参数 `{SOURCE_UNITS_PARAM}` 类型错误: {param:?}").into());
+    };
+    entries
+        .iter()
+        .map(|entry| {
+            let (sensor_id, unit) = entry.split_once('=').ok_or_else(|| {
+                format!("`{SOURCE_UNITS_PARAM}` 项 `{entry}` 应为 `传感器ID=单位`")
+            })?;
+            Ok((sensor_id.to_owned(), unit.parse()?))
+        })
+        .collect()
+}
+
+/// 写入 `source_units` 参数，列表为空时不写
+pub fn set_source_units(parameters: &mut MetadataParameters, units: &[(String, Unit)]) {
+    if units.is_empty() {
+        return;
+    }
+    let entries = units
+        .iter()
+        .map(|(sensor_id, unit)| format!("{sensor_id}={unit}"))
+        .collect();
+    parameters.insert(
+        SOURCE_UNITS_PARAM.to_owned(),
+        Parameter::ListString(entries),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_between_units() {
+        let f = Unit::Fahrenheit;
+        assert!((f.to_celsius(212.0).unwrap() - 100.0).abs() < 1e-9);
+        assert!((Unit::Kelvin.to_celsius(273.15).unwrap()).abs() < 1e-9);
+        assert_eq!(Unit::Raw.to_celsius(512.0), None);
+        assert!((f.from_celsius(f.to_celsius(98.6).unwrap()) - 98.6).abs() < 1e-9);
+        assert_eq!(f.from_celsius_delta(5.0), 9.0);
+        assert_eq!("°F".parse::<Unit>().unwrap(), Unit::Fahrenheit);
+        assert!("rankine".parse::<Unit>().is_err());
+    }
+
+    #[test]
+    fn units_round_trip_through_parameters() {
+        let mut parameters = MetadataParameters::new();
+        assert_eq!(unit_param(&parameters).unwrap(), Unit::Celsius);
+
+        let units = vec![
+            ("sensor0".to_owned(), Unit::Fahrenheit),
+            ("adc0".to_owned(), Unit::Raw),
+        ];
+        set_source_units(&mut parameters, &units);
+        parameters.insert(UNIT_PARAM.to_owned(), Parameter::String("kelvin".into()));
+        assert_eq!(source_units_param(&parameters).unwrap(), units);
+        assert_eq!(unit_param(&parameters).unwrap(), Unit::Kelvin);
+    }
+}
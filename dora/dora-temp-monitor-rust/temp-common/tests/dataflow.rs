@@ -0,0 +1,212 @@
+//! 校验 dataflow.yml 的连线：dora 会静默丢弃未在 `outputs` 中声明的输出，
+//! 输出 ID 拼写错误时下游永远收不到数据，因此在测试中检查每条连线。
+
+use serde_yaml::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+fn load_dataflow() -> Value {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../dataflow.yml");
+    let content = std::fs::read_to_string(&path).expect("dataflow.yml should be readable");
+    serde_yaml::from_str(&content).expect("dataflow.yml should be valid YAML")
+}
+
+/// 节点 ID → 声明的输出
+fn declared_outputs(dataflow: &Value) -> HashMap<String, HashSet<String>> {
+    dataflow["nodes"]
+        .as_sequence()
+        .expect("nodes should be a list")
+        .iter()
+        .map(|node| {
+            let id = node["id"].as_str().unwrap().to_owned();
+            let outputs = node["outputs"]
+                .as_sequence()
+                .map(|outputs| {
+                    outputs
+                        .iter()
+                        .map(|o| o.as_str().unwrap().to_owned())
+                        .collect()
+                })
+                .unwrap_or_default();
+            (id, outputs)
+        })
+        .collect()
+}
+
+/// 节点 ID → (输入 ID → 数据来源 `node/output`)
+fn inputs(dataflow: &Value) -> HashMap<String, HashMap<String, String>> {
+    dataflow["nodes"]
+        .as_sequence()
+        .unwrap()
+        .iter()
+        .map(|node| {
+            let id = node["id"].as_str().unwrap().to_owned();
+            let inputs = node["inputs"]
+                .as_mapping()
+                .map(|inputs| {
+                    inputs
+                        .iter()
+                        .map(|(input, source)| {
+                            // 输入既可以写成 `node/output`，也可以写成带 `source` 的映射
+                            let source = source
+                                .as_str()
+                                .or_else(|| source["source"].as_str())
+                                .unwrap();
+                            (input.as_str().unwrap().to_owned(), source.to_owned())
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            (id, inputs)
+        })
+        .collect()
+}
+
+#[test]
+fn every_input_refers_to_a_declared_output() {
+    let dataflow = load_dataflow();
+    let outputs = declared_outputs(&dataflow);
+
+    for (node, inputs) in inputs(&dataflow) {
+        for (input, source) in inputs {
+            let (sender, output) = source.split_once('/').unwrap();
+            if sender == "dora" {
+                continue;
+            }
+            let declared = outputs
+                .get(sender)
+                .unwrap_or_else(|| panic!("{node}/{input}: unknown node `{sender}`"));
+            assert!(
+                declared.contains(output),
+                "{node}/{input}: `{sender}` does not declare output `{output}`"
+            );
+        }
+    }
+}
+
+/// 各节点之间必须存在的连线：(接收节点, 输入, 发送节点, 输出)
+const WIRING: &[(&str, &str, &str, &str)] = &[
+    (
+        "data_processor",
+        "temp",
+        "temp_sensor",
+        temp_common::TEMP_RAW,
+    ),
+    (
+        "logger",
+        "smoothed",
+        "data_processor",
+        temp_common::TEMP_SMOOTHED,
+    ),
+    ("logger", "alert", "data_processor", temp_common::TEMP_ALERT),
+    (
+        "logger",
+        "health",
+        "data_processor",
+        temp_common::SENSOR_HEALTH,
+    ),
+    ("logger", "stats", "aggregator", temp_common::TEMP_STATS),
+    (
+        "storage",
+        "smoothed",
+        "data_processor",
+        temp_common::TEMP_SMOOTHED,
+    ),
+    (
+        "storage",
+        "alert",
+        "data_processor",
+        temp_common::TEMP_ALERT,
+    ),
+    (
+        "notifier",
+        "alert",
+        "data_processor",
+        temp_common::TEMP_ALERT,
+    ),
+    (
+        "aggregator",
+        "reading",
+        "data_processor",
+        temp_common::TEMP_SMOOTHED,
+    ),
+    (
+        "downsampler",
+        "reading",
+        "data_processor",
+        temp_common::TEMP_SMOOTHED,
+    ),
+    (
+        "dashboard",
+        "reading",
+        "data_processor",
+        temp_common::TEMP_SMOOTHED,
+    ),
+    (
+        "dashboard",
+        "forecast",
+        "data_processor",
+        temp_common::TEMP_FORECAST,
+    ),
+    (
+        "dashboard",
+        "alert",
+        "data_processor",
+        temp_common::TEMP_ALERT,
+    ),
+    (
+        "metrics",
+        "reading",
+        "data_processor",
+        temp_common::TEMP_SMOOTHED,
+    ),
+    (
+        "metrics",
+        "alert",
+        "data_processor",
+        temp_common::TEMP_ALERT,
+    ),
+    (
+        "metrics",
+        "health",
+        "data_processor",
+        temp_common::SENSOR_HEALTH,
+    ),
+];
+
+/// 数据流内没有接收方、留给外部节点订阅的输出：(发送节点, 输出)
+const EXTERNAL_OUTPUTS: &[(&str, &str)] = &[("downsampler", temp_common::TEMP_SERIES)];
+
+/// 定时检查配置文件、支持热加载的节点
+const RELOADING_NODES: &[&str] = &["data_processor", "logger"];
+
+#[test]
+fn nodes_are_wired_as_expected() {
+    let dataflow = load_dataflow();
+    let outputs = declared_outputs(&dataflow);
+    let inputs = inputs(&dataflow);
+
+    for &(receiver, input, sender, output) in WIRING {
+        assert!(
+            outputs[sender].contains(output),
+            "`{sender}` does not declare output `{output}`"
+        );
+        assert_eq!(
+            inputs[receiver].get(input).map(String::as_str),
+            Some(format!("{sender}/{output}").as_str()),
+            "{receiver}/{input}"
+        );
+    }
+    for &(sender, output) in EXTERNAL_OUTPUTS {
+        assert!(
+            outputs[sender].contains(output),
+            "`{sender}` does not declare output `{output}`"
+        );
+    }
+    for node in RELOADING_NODES {
+        assert!(
+            inputs[*node]["reload"].starts_with("dora/timer/"),
+            "{node}/reload"
+        );
+    }
+}
@@ -2,6 +2,7 @@
 
 #!/usr/bin/env python3
 # visualizer.py - 新增节点：实时温度曲线图
+import os
 import threading
 from collections import deque
 
@@ -12,24 +13,59 @@ from dora import Node
 
 
 class TempVisualizer:
-    def __init__(self, max_points=100):
+    def __init__(self, max_points=100, series_range=None):
         self.max_points = max_points
-        self.timestamps = deque(maxlen=max_points)
-        self.temperatures = deque(maxlen=max_points)
+        # 每个传感器一条曲线：sensor_id -> 最近的温度
+        self.temperatures = {}
+        # 降采样节点输出的长时间序列：sensor_id -> [(Unix 秒, 温度)]，收到后按时间轴绘制
+        self.series = {}
+        self.series_range = series_range
+        self.lines = {}
         self.fig, self.ax = plt.subplots(figsize=(10, 6))
-        (self.line,) = self.ax.plot([], [], "b-", label="Temperature Curve")
         self.ax.set_ylim(15, 40)
         self.ax.set_xlim(0, max_points)
         self.ax.set_xlabel("Timestamp")
         self.ax.set_ylabel("Temperature (°C)")
         self.ax.set_title("Real Time Temperature Monitoring (M1 Pro)")
-        self.ax.legend()
         self.ax.grid(True)
 
+    def append(self, sensor_id, temp):
+        if sensor_id not in self.temperatures:
+            self.temperatures[sensor_id] = deque(maxlen=self.max_points)
+        self.temperatures[sensor_id].append(temp)
+
+    def set_series(self, rows):
+        series = {}
+        for row in rows:
+            point = (row["timestamp_ns"] / 1e9, row["value"])
+            series.setdefault(row["sensor_id"], []).append(point)
+        # 整体替换，绘图线程不会看到一半的数据
+        self.series = series
+
     def update_plot(self, frame):
-        self.line.set_data(range(len(self.temperatures)), self.temperatures)
-        self.ax.set_xlim(0, max(len(self.temperatures), self.max_points))
-        return (self.line,)
+        series = self.series
+        sensors = series if series else self.temperatures
+        # 新传感器出现时添加曲线（数据线程只写数据，绘图对象只在这里创建）
+        for sensor_id in list(sensors):
+            if sensor_id not in self.lines:
+                (self.lines[sensor_id],) = self.ax.plot([], [], label=sensor_id)
+                self.ax.legend()
+        if series:
+            # 横轴为距最新读数的分钟数
+            latest = max(points[-1][0] for points in series.values())
+            for sensor_id, line in self.lines.items():
+                points = series.get(sensor_id, [])
+                line.set_data(
+                    [(t - latest) / 60 for t, _ in points], [v for _, v in points]
+                )
+            earliest = min(points[0][0] for points in series.values())
+            self.ax.set_xlim(min((earliest - latest) / 60, -1), 0)
+            self.ax.set_xlabel(f"Minutes (last {self.series_range})")
+        else:
+            for sensor_id, line in self.lines.items():
+                temps = list(self.temperatures[sensor_id])
+                line.set_data(range(len(temps)), temps)
+        return tuple(self.lines.values())
 
 
 def data_receiver(visualizer):
@@ -38,14 +74,17 @@ def data_receiver(visualizer):
     node = Node()
     for event in node:
         if event["type"] == "INPUT" and event["id"] == "data":
-            array = event["value"]
-            temp = array[0].as_py()
-            visualizer.temperatures.append(temp)
-            visualizer.timestamps.append(len(visualizer.timestamps))
+            # 每行是 {timestamp_ns, sensor_id, value}
+            for row in event["value"].to_pylist():
+                visualizer.append(row["sensor_id"], row["value"])
+        elif event["type"] == "INPUT" and event["id"] == "series":
+            # 降采样节点按多个范围输出，只显示选定的范围
+            if event["metadata"].get("range") == visualizer.series_range:
+                visualizer.set_series(event["value"].to_pylist())
 
 
 def main():
-    visualizer = TempVisualizer()
+    visualizer = TempVisualizer(series_range=os.getenv("VISUALIZER_RANGE", "1h"))
     # 启动数据接收线程（非阻塞）
     data_thread = threading.Thread(target=data_receiver, args=(visualizer,))
     data_thread.daemon = True
@@ -56,7 +95,7 @@ def main():
         visualizer.fig,
         visualizer.update_plot,
         interval=100,  # 100ms刷新一次
-        blit=True,
+        blit=False,  # 曲线数量会随新传感器增加
     )
     plt.show()
 
@@ -1,17 +1,18 @@
 use anyhow::Context;
 use dora_node_api::{arrow::array::UInt8Array, DoraNode, Event};
-use opencv::{core::Vector, highgui, imgcodecs, prelude::*};
+use image_frame::FrameInfo;
 use std::error::Error;
 
+use webcam_core::sink::FrameSink;
+
 fn main() -> Result<(), Box<dyn Error>> {
     let (mut _node, mut events) = DoraNode::init_from_env()?;
-    // 创建一个用于显示的窗口
-    highgui::named_window("Dora Webcam Viewer (Rust)", highgui::WINDOW_NORMAL)
-        .context("Failed to create highgui window")?;
-    println!("Viewer operator initialized.");
+    // 窗口，或无显示器的服务器上写入视频文件 / PNG 序列，由 dataflow.yml 中的 VIEWER_OUTPUT 选择
+    let mut sink = FrameSink::from_env("Dora Webcam Viewer (Rust)")?;
+    println!("Viewer operator initialized, output: {}", sink.describe());
     while let Some(event) = events.recv() {
         match event {
-            Event::Input { id, metadata: _, data } => match id.as_str() {
+            Event::Input { id, metadata, data } => match id.as_str() {
                 "frame" => {
                     // 将接收到的字节数据转换为 OpenCV Vector
                     // 1. 将 Arrow trait 对象强转为具体的 UInt8Array
@@ -23,25 +24,19 @@ fn main() -> Result<(), Box<dyn Error>> {
                     // 2. 提取 UInt8Array 的字节切片
                     let byte_slice = uint8_array.values(); // 返回 &[u8]
 
-                    // 3. 转换为 OpenCV Vector<u8>（from_slice 接收 &[u8]）
-                    let buffer = Vector::from_slice(byte_slice);
-
-                    // 解码 JPEG 数据成 Mat
-                    let frame = imgcodecs::imdecode(&buffer, imgcodecs::IMREAD_COLOR)
-                        .context("Failed to decode image from buffer")?;
-
-                    if frame
-                        .size()
-                        .context("Failed to get decoded frame size")?
-                        .width
-                        > 0
+                    // 3. 按元数据中的编码（JPEG 或原始像素）解码为 BGR Mat，无法解码的帧跳过
+                    let frame = match FrameInfo::from_parameters(&metadata.parameters)
+                        .and_then(|info| image_frame::to_bgr(&info, byte_slice))
                     {
-                        // 显示图像
-                        highgui::imshow("Dora Webcam Viewer (Rust)", &frame)
-                            .context("Failed to imshow frame")?;
-                        // 必须调用 wait_key 来处理 GUI 事件
-                        highgui::wait_key(1).context("Failed to wait_key")?;
-                    }
+                        Ok(frame) => frame,
+                        Err(e) => {
+                            eprintln!("Skipping frame: {e}");
+                            continue;
+                        }
+                    };
+
+                    // 显示或写入图像
+                    sink.show(&frame)?;
                 }
                 other => eprintln!("Received input `{other}`"),
             },
@@ -49,5 +44,5 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
     }
 
-    Ok(())
+    sink.finish()
 }
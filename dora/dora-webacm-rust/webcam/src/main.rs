@@ -1,31 +1,32 @@
 use dora_node_api::{DoraNode, Event, dora_core::config::DataId, arrow::array::UInt8Array};
 use std::error::Error;
-use std::thread;
-use std::time::Duration;
-use anyhow::Context;
-use opencv::{
-    core::{Vector}, imgcodecs, prelude::*, videoio::{self, VideoCapture}
-};
+use std::time::Instant;
 
-const CAMERA_INDEX: i32 = 0; // 默认使用第一个摄像头
+use webcam_core::encoder::{self, EncodeStats};
+use webcam_core::source::FrameSource;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let (mut node, mut events) = DoraNode::init_from_env()?;
-    let mut camera = VideoCapture::new(CAMERA_INDEX, videoio::CAP_ANY)
-        .context("Failed to create video capture")?;
-    let output = DataId::from("frame".to_owned());
-    // 尝试打开摄像头
-    if !VideoCapture::is_opened(&camera).context("Failed to check if camera is open")? {
-        // 在 Mac M1 上，有时需要延迟以确保摄像头初始化完成
-        thread::sleep(Duration::from_millis(500));
-        if !VideoCapture::is_opened(&camera).context("Camera still not open after delay")? {
-            return Err("Could not open camera 0 or check its status.".into());
-        }
+    // 摄像头、视频文件、图片目录或网络流，由 dataflow.yml 中的 VIDEO_SOURCE 选择
+    let mut source = FrameSource::from_env()?;
+    println!("📷 帧来源: {}", source.describe());
+    if let Some(format) = source.format() {
+        println!("🎞️ 采集格式: {format}");
     }
-
-    // 尝试设置分辨率 (可选，可以提高性能或稳定性)
-    // camera.set(videoio::CAP_PROP_FRAME_WIDTH, 640.0)?;
-    // camera.set(videoio::CAP_PROP_FRAME_HEIGHT, 480.0)?;
+    // 输出编码：jpeg（默认）、png、webp 或 bgr8/bgra8/rgb8 原始像素，元数据约定见 image_frame
+    let options = encoder::options_from_env()?;
+    let quality = options
+        .quality
+        .map(|q| format!("，质量 {q}"))
+        .unwrap_or_default();
+    println!(
+        "🖼️ 帧编码: {}{quality}，缩放 {}",
+        options.encoding, options.scale
+    );
+    let mut stats = EncodeStats::from_env()?;
+    let output = DataId::from("frame".to_owned());
+    let mut finished = false;
+    let mut seq = 0;
 
     while let Some(event) = events.recv() {
         // println!("Received event: {:?}", event);
@@ -36,29 +37,34 @@ fn main() -> Result<(), Box<dyn Error>> {
                 data: _,
             } => match id.as_str() {
                 "tick" => {
-                    let mut frame = Mat::default();
-                    
                     // 读取帧
-                    if camera
-                        .read(&mut frame)
-                        .context("Failed to read frame from camera")?
-                    {
-                        if frame.size().context("Failed to get frame size")?.width > 0 {
-                            // 将帧编码为 JPEG 格式的字节向量
-                            let mut buffer = Vector::new();
-                            imgcodecs::imencode(".jpg", &frame, &mut buffer, &Vector::new())
-                                .context("Failed to encode frame to JPEG")?;
-
-                            // 发送原始帧数据
-                            let std_buffer: Vec<u8> = buffer.into_iter().collect();
+                    match source.read()? {
+                        Some(frame) => {
+                            // 时间戳取读到帧的时刻，不含编码耗时
+                            let captured_ns = image_frame::now_ns();
+                            // 按设置缩放后编码为压缩图像或原始像素字节
+                            let started = Instant::now();
+                            let (bytes, info) =
+                                image_frame::encode(&frame, &options, captured_ns, seq)?;
+                            seq += 1;
+                            if let Some(summary) = stats.record(bytes.len(), started.elapsed()) {
+                                println!("📦 {summary}");
+                            }
 
-                            // 3. 再转为 Arrow 数组
-                            let arrow_array = UInt8Array::from(std_buffer);
-                            node.send_output(
-                                output.clone(),
-                                metadata.parameters,
-                                arrow_array,
-                            )?;
+                            // 再转为 Arrow 数组，尺寸、编码、时间戳和序号写入元数据
+                            let arrow_array = UInt8Array::from(bytes);
+                            let mut parameters = metadata.parameters;
+                            info.insert_into(&mut parameters);
+                            if let Some(format) = source.format() {
+                                format.insert_into(&mut parameters);
+                            }
+                            node.send_output(output.clone(), parameters, arrow_array)?;
+                        }
+                        None => {
+                            if source.finished() && !finished {
+                                println!("📭 视频已播放完，不再发送帧");
+                                finished = true;
+                            }
                         }
                     }
                 }
@@ -1,5 +1,7 @@
-use dora_node_api::{arrow::array::Float32Array, dora_core::config::DataId, DoraNode, Event};
+use dora_node_api::{arrow::array::Float32Array, ArrowData, MetadataParameters};
+use node_harness::{NodeHandler, Outputs};
 use std::error::Error;
+use std::sync::Arc;
 
 struct PIDController {
     k_p: f32,
@@ -32,108 +34,242 @@ impl PIDController {
     }
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let (mut node, mut events) = DoraNode::init_from_env()?;
-    let mut speed_pid = PIDController::new(0.2, 0.005, 0.1);
-    let wheel_base = 2.94;
-    let steering_ratio = 25.0;
-
-    let mut current_pose = [0.0f32; 6];
-    let mut current_speed = 0.0f32;
-    let mut planned_path: Vec<[f32; 3]> = Vec::new();
-
-    while let Some(event) = events.recv() {
-        match event {
-            Event::Input { id, data, metadata } => match id.as_str() {
-                "position" => {
-                    let array = data.as_any().downcast_ref::<Float32Array>().unwrap();
-                    current_pose.copy_from_slice(array.values());
-                }
-                "speed" => {
-                    let array = data.as_any().downcast_ref::<Float32Array>().unwrap();
-                    current_speed = array.values()[0].abs();
-                }
-                "waypoints" => {
-                    let array = data.as_any().downcast_ref::<Float32Array>().unwrap();
-                    planned_path = array
-                        .values()
-                        .chunks_exact(3)
-                        .map(|c| [c[0], c[1], c[2]])
-                        .collect();
-                }
-                "tick" => {
-                    if planned_path.is_empty() {
-                        continue;
-                    }
-
-                    let x = current_pose[0];
-                    let y = current_pose[1];
-                    let yaw = current_pose[5];
-
-                    // --- 1. 寻找最近路径点索引 ---
-                    let mut min_dist = f32::MAX;
-                    let mut closest_idx = 0;
-                    for (i, p) in planned_path.iter().enumerate() {
-                        let dist = ((p[0] - x).powi(2) + (p[1] - y).powi(2)).sqrt();
-                        if dist < min_dist {
-                            min_dist = dist;
-                            closest_idx = i;
-                        }
-                    }
-
-                    // --- 2. 动态预瞄 (根据当前位置计算) ---
-                    let lookahead_dist = (current_speed * 0.5).max(5.0).min(15.0);
-                    let target_pt = planned_path[closest_idx..]
-                        .iter()
-                        .find(|p| ((p[0] - x).powi(2) + (p[1] - y).powi(2)).sqrt() > lookahead_dist)
-                        .unwrap_or(&planned_path[planned_path.len() - 1]);
-
-                    // --- 3. 转向逻辑优化 ---
-                    let dx = target_pt[0] - x;
-                    let dy = target_pt[1] - y;
-
-                    // 标准右手系转换 (Z-up)
-                    let local_x = dx * yaw.cos() + dy * yaw.sin();
-                    let local_y = -dx * yaw.sin() + dy * yaw.cos();
-
-                    let angle_to_target = local_y.atan2(local_x);
-
-                    // 增加防震荡系数：如果偏离过远（min_dist > 10m），降低转向增益
-                    let gain_scale = if min_dist > 10.0 { 0.5 } else { 1.0 };
-                    let steer_wheel_angle =
-                        (2.0 * wheel_base * angle_to_target.sin() / lookahead_dist).atan();
-                    let mut final_steering = steer_wheel_angle * steering_ratio * gain_scale;
-
-                    final_steering = final_steering.clamp(-10.4, 10.4);
-
-                    // --- 4. 纵向速度控制 (解决油门制动冲突) ---
-                    let target_speed = target_pt[2];
-                    let control_effort = speed_pid.compute(target_speed, current_speed, 0.05);
-
-                    // 转向补偿油门
-                    let steering_resistance = (final_steering.abs() / 10.4) * 0.2;
-                    let (throttle, brake) = if control_effort > 0.05 {
-                        (
-                            (control_effort + 0.15 + steering_resistance).clamp(0.0, 0.6),
-                            0.0,
-                        )
-                    } else if control_effort < -0.05 {
-                        (0.0, (-control_effort).clamp(0.0, 1.0))
-                    } else {
-                        (0.0, 0.0) // 死区保护
-                    };
-
-                    // --- 5. 输出 ---
-                    node.send_output(
-                        DataId::from("control_command".to_owned()),
-                        metadata.parameters,
-                        Float32Array::from(vec![final_steering, throttle, brake]),
+/// 路径跟踪控制节点：纯追踪算法计算转向，PID 计算油门和制动，每个 tick 输出一次控制指令
+struct Controller {
+    speed_pid: PIDController,
+    wheel_base: f32,
+    steering_ratio: f32,
+    current_pose: [f32; 6],
+    current_speed: f32,
+    planned_path: Vec<[f32; 3]>,
+}
+
+impl Controller {
+    fn new() -> Self {
+        Self {
+            speed_pid: PIDController::new(0.2, 0.005, 0.1),
+            wheel_base: 2.94,
+            steering_ratio: 25.0,
+            current_pose: [0.0; 6],
+            current_speed: 0.0,
+            planned_path: Vec::new(),
+        }
+    }
+
+    /// 根据当前位姿、速度和规划路径计算 `[转向, 油门, 制动]`，没有路径时返回 `None`
+    fn command(&mut self) -> Option<[f32; 3]> {
+        let planned_path = &self.planned_path;
+        if planned_path.is_empty() {
+            return None;
+        }
+
+        let x = self.current_pose[0];
+        let y = self.current_pose[1];
+        let yaw = self.current_pose[5];
+
+        // --- 1. 寻找最近路径点索引 ---
+        let mut min_dist = f32::MAX;
+        let mut closest_idx = 0;
+        for (i, p) in planned_path.iter().enumerate() {
+            let dist = ((p[0] - x).powi(2) + (p[1] - y).powi(2)).sqrt();
+            if dist < min_dist {
+                min_dist = dist;
+                closest_idx = i;
+            }
+        }
+
+        // --- 2. 动态预瞄 (根据当前位置计算) ---
+        // 不用 clamp：速度为 NaN 时 clamp 原样返回 NaN，max 则回落到最小预瞄距离
+        #[allow(clippy::manual_clamp)]
+        let lookahead_dist = (self.current_speed * 0.5).max(5.0).min(15.0);
+        let target_pt = planned_path[closest_idx..]
+            .iter()
+            .find(|p| ((p[0] - x).powi(2) + (p[1] - y).powi(2)).sqrt() > lookahead_dist)
+            .unwrap_or(&planned_path[planned_path.len() - 1]);
+
+        // --- 3. 转向逻辑优化 ---
+        let dx = target_pt[0] - x;
+        let dy = target_pt[1] - y;
+
+        // 标准右手系转换 (Z-up)
+        let local_x = dx * yaw.cos() + dy * yaw.sin();
+        let local_y = -dx * yaw.sin() + dy * yaw.cos();
+
+        let angle_to_target = local_y.atan2(local_x);
+
+        // 增加防震荡系数：如果偏离过远（min_dist > 10m），降低转向增益
+        let gain_scale = if min_dist > 10.0 { 0.5 } else { 1.0 };
+        let steer_wheel_angle =
+            (2.0 * self.wheel_base * angle_to_target.sin() / lookahead_dist).atan();
+        let mut final_steering = steer_wheel_angle * self.steering_ratio * gain_scale;
+
+        final_steering = final_steering.clamp(-10.4, 10.4);
+
+        // --- 4. 纵向速度控制 (解决油门制动冲突) ---
+        let target_speed = target_pt[2];
+        let control_effort = self
+            .speed_pid
+            .compute(target_speed, self.current_speed, 0.05);
+
+        // 转向补偿油门
+        let steering_resistance = (final_steering.abs() / 10.4) * 0.2;
+        let (throttle, brake) = if control_effort > 0.05 {
+            (
+                (control_effort + 0.15 + steering_resistance).clamp(0.0, 0.6),
+                0.0,
+            )
+        } else if control_effort < -0.05 {
+            (0.0, (-control_effort).clamp(0.0, 1.0))
+        } else {
+            (0.0, 0.0) // 死区保护
+        };
+
+        Some([final_steering, throttle, brake])
+    }
+}
+
+impl NodeHandler for Controller {
+    fn on_input(
+        &mut self,
+        id: &str,
+        parameters: MetadataParameters,
+        data: ArrowData,
+        outputs: &mut dyn Outputs,
+    ) -> Result<(), Box<dyn Error>> {
+        match id {
+            "position" => {
+                let array = data
+                    .as_any()
+                    .downcast_ref::<Float32Array>()
+                    .ok_or("position is not Float32Array")?;
+                self.current_pose = array.values()[..]
+                    .try_into()
+                    .map_err(|_| format!("position expects 6 values, got {}", array.len()))?;
+            }
+            "speed" => {
+                let array = data
+                    .as_any()
+                    .downcast_ref::<Float32Array>()
+                    .ok_or("speed is not Float32Array")?;
+                self.current_speed = array.values().first().ok_or("speed is empty")?.abs();
+            }
+            "waypoints" => {
+                let array = data
+                    .as_any()
+                    .downcast_ref::<Float32Array>()
+                    .ok_or("waypoints is not Float32Array")?;
+                self.planned_path = array
+                    .values()
+                    .chunks_exact(3)
+                    .map(|c| [c[0], c[1], c[2]])
+                    .collect();
+            }
+            "tick" => {
+                // --- 5. 输出 ---
+                if let Some(command) = self.command() {
+                    outputs.send(
+                        "control_command",
+                        parameters,
+                        Arc::new(Float32Array::from(command.to_vec())),
                     )?;
                 }
-                _ => {}
-            },
+            }
             _ => {}
         }
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    node_harness::run(&mut Controller::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use node_harness::{FakeNode, Input};
+
+    /// 从 `(0, y0)` 沿 x 轴方向的直线路径，目标速度 `v`
+    fn path(y0: f32, v: f32) -> Input {
+        let points: Vec<f32> = (0..30).flat_map(|i| [i as f32, y0, v]).collect();
+        Input::new("waypoints", Float32Array::from(points))
+    }
+
+    fn state(x: f32, yaw: f32, speed: f32) -> [Input; 2] {
+        [
+            Input::new(
+                "position",
+                Float32Array::from(vec![x, 0.0, 0.0, 0.0, 0.0, yaw]),
+            ),
+            Input::new("speed", Float32Array::from(vec![speed])),
+        ]
+    }
+
+    fn command(node: &mut FakeNode, controller: &mut Controller) -> [f32; 3] {
+        node.push(Input::tick("tick"));
+        let sent = node.run(controller).unwrap();
+        let values = sent[0].downcast::<Float32Array>().unwrap().values();
+        [values[0], values[1], values[2]]
+    }
+
+    #[test]
+    fn follows_straight_path_and_steers_towards_offset_path() {
+        let mut controller = Controller::new();
+        let mut node = FakeNode::with_outputs(&["control_command"]);
+
+        // 没有规划路径时不输出
+        node.push(Input::tick("tick"));
+        assert!(node.run(&mut controller).unwrap().is_empty());
+
+        // 停在直线路径上：不转向，加油门
+        let [position, speed] = state(0.0, 0.0, 0.0);
+        node.push(path(0.0, 2.0)).push(position).push(speed);
+        let [steering, throttle, brake] = command(&mut node, &mut controller);
+        assert_eq!(steering, 0.0);
+        assert!(throttle > 0.15 && brake == 0.0);
+
+        // 路径在车辆左侧 (y > 0) 时向左打方向，车头已经朝左时向右回正
+        node.push(path(3.0, 2.0));
+        assert!(command(&mut node, &mut controller)[0] > 0.0);
+        let [position, speed] = state(0.0, 1.0, 0.0);
+        node.push(position).push(speed);
+        assert!(command(&mut node, &mut controller)[0] < 0.0);
+    }
+
+    #[test]
+    fn brakes_when_faster_than_planned_speed() {
+        let mut controller = Controller::new();
+        let mut node = FakeNode::new();
+        let [position, speed] = state(0.0, 0.0, 8.0);
+        node.push(path(0.0, 2.0)).push(position).push(speed);
+        let [_, throttle, brake] = command(&mut node, &mut controller);
+        assert_eq!(throttle, 0.0);
+        assert!(brake > 0.0 && brake <= 1.0);
+    }
+
+    #[test]
+    fn nan_speed_falls_back_to_minimum_lookahead() {
+        let mut controller = Controller::new();
+        let mut node = FakeNode::new();
+        let [position, speed] = state(0.0, 0.0, 0.0);
+        node.push(path(3.0, 2.0)).push(position).push(speed);
+        let expected = command(&mut node, &mut controller)[0];
+
+        let mut controller = Controller::new();
+        let [position, speed] = state(0.0, 0.0, f32::NAN);
+        node.push(path(3.0, 2.0)).push(position).push(speed);
+        let [steering, throttle, brake] = command(&mut node, &mut controller);
+        assert_eq!(steering, expected);
+        assert!(throttle.is_finite() && brake.is_finite());
+    }
+
+    #[test]
+    fn malformed_state_is_rejected() {
+        let mut controller = Controller::new();
+        let mut node = FakeNode::new();
+        node.push(Input::new("position", Float32Array::from(vec![1.0, 2.0])));
+        assert!(node.run(&mut controller).is_err());
+        node.push(Input::new("speed", Float32Array::from(Vec::<f32>::new())));
+        assert!(node.run(&mut controller).is_err());
     }
-    Ok(())
 }
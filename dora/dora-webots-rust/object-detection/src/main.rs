@@ -1,13 +1,14 @@
 use anyhow::Context;
-use dora_node_api::{
-    arrow::array::UInt8Array, dora_core::config::DataId, DoraNode, Event, Parameter,
-};
+use dora_node_api::{arrow::array::UInt8Array, ArrowData, MetadataParameters};
+use image_frame::FrameInfo;
+use node_harness::{NodeHandler, Outputs};
 use opencv::{
-    core::{copy_make_border, AlgorithmHint, Rect, Scalar, Vec4b},
+    core::{copy_make_border, AlgorithmHint, Rect, Scalar},
     imgproc,
     prelude::*,
 };
 use std::error::Error;
+use std::sync::Arc;
 
 use candle_core::{DType, Device, Module, Tensor};
 use candle_nn::VarBuilder;
@@ -123,9 +124,72 @@ pub fn select_device() -> Result<Device, Box<dyn Error>> {
     Ok(Device::Cpu)
 }
 
+/// 目标检测节点：解码 `frame`，经 `model` 推理和 NMS 后处理输出 `detections`
+///
+/// 模型只需实现 candle 的 `Module`，测试中可以用返回固定预测的假模型驱动后处理
+struct DetectionNode<M: Module> {
+    model: M,
+    device: Device,
+}
+
+impl<M: Module> NodeHandler for DetectionNode<M> {
+    fn on_input(
+        &mut self,
+        id: &str,
+        parameters: MetadataParameters,
+        data: ArrowData,
+        outputs: &mut dyn Outputs,
+    ) -> Result<(), Box<dyn Error>> {
+        match id {
+            "frame" => {
+                // 1. 将 Arrow trait 对象强转为具体的 UInt8Array
+                let uint8_array = data
+                    .as_any()
+                    .downcast_ref::<UInt8Array>()
+                    .context("Arrow data is not UInt8Array (expected byte array)")?;
+
+                // 2. 提取 UInt8Array 的字节切片
+                let byte_slice = uint8_array.values(); // 返回 &[u8]
+
+                // 3. 按元数据中的编码（Webots 为 bgra8，webcam 可为 JPEG 或原始像素）解码为 BGR Mat，
+                //    尺寸与数据长度不符等无法解码的帧跳过
+                let frame = match FrameInfo::from_parameters(&parameters)
+                    .and_then(|info| image_frame::to_bgr(&info, byte_slice))
+                {
+                    Ok(frame) => frame,
+                    Err(e) => {
+                        eprintln!("Skipping frame: {e}");
+                        return Ok(());
+                    }
+                };
+
+                if frame.empty() {
+                    eprintln!("Warning: Decoded frame is empty. Skipping this iteration.");
+                    return Ok(()); // 跳过当前帧，不进入 preprocess_image
+                }
+                // --- 步骤 A: 图像预处理 (OpenCV -> Candle Tensor) ---
+                let (processed_tensor, ratio, pad_w, pad_h) =
+                    preprocess_image(&frame, &self.device)?;
+
+                // --- 步骤 B: 模型推理 ---
+                let predictions = self.model.forward(&processed_tensor)?;
+
+                // --- 步骤 C: 后处理 (NMS) ---
+                // predictions 维度通常是 (1, 84, 8400) -> (Batch, Classes+Coords, Anchors)
+                let preds = predictions.squeeze(0)?;
+                let (bboxes, _keypoints) = report_detect(&preds, &frame, ratio, pad_w, pad_h)?;
+
+                let arrow_array = bboxes_to_arrow(bboxes)?;
+
+                outputs.send("detections", parameters, Arc::new(arrow_array))?;
+            }
+            other => eprintln!("Received input `{other}`"),
+        }
+        Ok(())
+    }
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
-    let (mut node, mut events) = DoraNode::init_from_env()?;
-    let output = DataId::from("detections".to_owned());
     // 加载 YOLOv8 模型 (使用 HuggingFace 自动下载)
     println!("Loading YOLOv8 model...");
     // 优化后 (如果支持 CUDA):
@@ -154,100 +218,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     println!("Model loaded successfully.");
 
-    while let Some(event) = events.recv() {
-        // println!("Received event: {:?}", event);
-        match event {
-            Event::Input { id, metadata, data } => match id.as_str() {
-                "frame" => {
-                    // 尝试获取 width
-                    let cols = match metadata.parameters.get("width") {
-                        Some(Parameter::Integer(v)) => *v as i32,
-                        _ => 640, // 默认值
-                    };
-
-                    // 尝试获取 height
-                    let rows = match metadata.parameters.get("height") {
-                        Some(Parameter::Integer(v)) => *v as i32,
-                        _ => 480, // 默认值
-                    };
-                    // 将接收到的字节数据转换为 OpenCV Vector
-                    // 1. 将 Arrow trait 对象强转为具体的 UInt8Array
-                    let uint8_array = data
-                        .as_any()
-                        .downcast_ref::<UInt8Array>()
-                        .context("Arrow data is not UInt8Array (expected byte array)")?;
-
-                    // 2. 提取 UInt8Array 的字节切片
-                    let byte_slice = uint8_array.values(); // 返回 &[u8]
-                    if byte_slice.len() != (rows * cols * 4) as usize {
-                        eprintln!(
-                            "Data size mismatch! Expected {}, got {}",
-                            rows * cols * 4,
-                            byte_slice.len()
-                        );
-                        continue;
-                    }
-
-                    // 关键点：将 &[u8] 转换为 &[Vec4b]
-                    // Vec4b 代表一个由 4 个 u8 组成的像素点
-                    let (head, vec4_slice, tail) = unsafe { byte_slice.align_to::<Vec4b>() };
-
-                    if !head.is_empty() || !tail.is_empty() {
-                        // 如果数据不是 4 的倍数，可能会进到这里
-                        eprintln!("Warning: Byte slice alignment issue");
-                    }
-
-                    // 现在传入只有 3 个参数的函数
-                    // 因为 vec4_slice 的长度刚好是 byte_slice.len() / 4
-                    // 128 * 64 = 8192，这与 vec4_slice.len() 完美匹配！
-                    let frame_raw = Mat::new_rows_cols_with_data(
-                        rows,       // 128 (行)
-                        cols,       // 64 (列)
-                        vec4_slice, // 32768字节的 u8 切片现在变成了 8192长度的 Vec4b 切片
-                    )?;
-
-                    // let frame_raw = Mat::new_rows_cols_with_data(
-                    //     rows,       // 第1个参数: i32
-                    //     cols,       // 第2个参数: i32
-                    //     byte_slice, // 只需要传入切片本身，不要传指针和长度
-                    // )?;
-                    let mut frame = Mat::default();
-                    // 注意：Webots 的颜色顺序可能是 BGRA，如果颜色不对，请尝试 COLOR_BGRA2RGB
-                    imgproc::cvt_color(
-                        &frame_raw,
-                        &mut frame,
-                        imgproc::COLOR_BGRA2BGR,
-                        0,
-                        AlgorithmHint::ALGO_HINT_DEFAULT,
-                    )?;
-
-                    if frame.empty() {
-                        eprintln!("Warning: Decoded frame is empty. Skipping this iteration.");
-                        continue; // 跳过当前循环，不进入 preprocess_image
-                    }
-                    // --- 步骤 A: 图像预处理 (OpenCV -> Candle Tensor) ---
-                    let (processed_tensor, ratio, pad_w, pad_h) =
-                        preprocess_image(&frame, &device)?;
-
-                    // --- 步骤 B: 模型推理 ---
-                    let predictions = model.forward(&processed_tensor)?;
-
-                    // --- 步骤 C: 后处理 (NMS) ---
-                    // predictions 维度通常是 (1, 84, 8400) -> (Batch, Classes+Coords, Anchors)
-                    let preds = predictions.squeeze(0)?;
-                    let (bboxes, keypoints) = report_detect(&preds, &frame, ratio, pad_w, pad_h)?;
-
-                    let arrow_array = bboxes_to_arrow(bboxes)?;
-
-                    node.send_output(output.clone(), metadata.parameters, arrow_array)?;
-                }
-                other => eprintln!("Received input `{other}`"),
-            },
-            _ => {}
-        }
-    }
-
-    Ok(())
+    node_harness::run(&mut DetectionNode { model, device })
 }
 
 // 图像预处理：调整大小、填充、归一化、转 Tensor
@@ -374,7 +345,8 @@ fn report_detect(
     // 简单 NMS (非极大值抑制)
     // 注意：生产环境建议使用 torchvision 或 opencv 自带的 NMSBoxes
     let mut kept_results = Vec::new();
-    results.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap()); // 按置信度降序
+    // 按置信度升序，pop 先取出最高分；降序排列时 pop 会先保留重叠框中分数最低的
+    results.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap());
 
     while let Some(current) = results.pop() {
         kept_results.push(current.clone());
@@ -398,3 +370,106 @@ fn iou(box_a: &Rect, box_b: &Rect) -> f32 {
 
     inter_area / (box_a_area + box_b_area - inter_area)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dora_node_api::arrow::array::StructArray;
+    use image_frame::Encoding;
+    use node_harness::{FakeNode, Input};
+    use utils::arrow_to_bboxes;
+
+    /// 忽略输入、返回固定预测结果的假模型
+    struct FixedPredictions(Tensor);
+
+    impl Module for FixedPredictions {
+        fn forward(&self, _xs: &Tensor) -> candle_core::Result<Tensor> {
+            Ok(self.0.clone())
+        }
+    }
+
+    /// 构造 (1, 84, N) 的预测，每个候选框为 `(cx, cy, w, h, 类别, 置信度)`，坐标在原图坐标系中
+    fn predictions(boxes: &[(f32, f32, f32, f32, usize, f32)], pad_w: f32, pad_h: f32) -> Tensor {
+        let n = boxes.len();
+        let mut data = vec![0f32; 84 * n];
+        for (i, &(cx, cy, w, h, class, score)) in boxes.iter().enumerate() {
+            data[i] = cx + pad_w;
+            data[n + i] = cy + pad_h;
+            data[2 * n + i] = w;
+            data[3 * n + i] = h;
+            data[(4 + class) * n + i] = score;
+        }
+        Tensor::from_vec(data, (1, 84, n), &Device::Cpu).unwrap()
+    }
+
+    /// 按图像帧约定构造 `len` 字节的全黑原始像素帧
+    fn raw_frame(cols: usize, rows: usize, encoding: Encoding, len: usize) -> Input {
+        let mut input = Input::new("frame", UInt8Array::from(vec![0u8; len]));
+        FrameInfo::packed(cols, rows, encoding, 0, 0).insert_into(&mut input.parameters);
+        input
+    }
+
+    #[test]
+    fn overlapping_boxes_are_suppressed_and_mapped_back_to_frame() {
+        // 64x32 的帧不放大，居中填充到 640x640：左右各 288、上下各 304 像素
+        let (cols, rows) = (64, 32);
+        let model = FixedPredictions(predictions(
+            &[
+                (20.0, 10.0, 10.0, 10.0, 2, 0.6),
+                (21.0, 10.0, 10.0, 10.0, 2, 0.9),
+                (50.0, 20.0, 8.0, 8.0, 0, 0.5),
+                (40.0, 20.0, 8.0, 8.0, 0, 0.1),
+            ],
+            288.0,
+            304.0,
+        ));
+        let mut handler = DetectionNode {
+            model,
+            device: Device::Cpu,
+        };
+
+        let mut node = FakeNode::with_outputs(&["detections"]);
+        node.push(raw_frame(cols, rows, Encoding::Bgra8, cols * rows * 4));
+        // 尺寸与数据长度不符的帧被跳过
+        node.push(raw_frame(cols, rows, Encoding::Bgra8, 16));
+        // webcam 发送的其他原始编码同样可以处理
+        node.push(raw_frame(cols, rows, Encoding::Rgb8, cols * rows * 3));
+        let sent = node.run(&mut handler).unwrap();
+        assert_eq!(sent.len(), 2);
+
+        let detections = arrow_to_bboxes(sent[0].downcast::<StructArray>().unwrap()).unwrap();
+        // 重叠的两个框只保留置信度最高的一个，低于阈值的框被丢弃
+        assert_eq!(
+            detections,
+            [
+                ("car".to_owned(), Rect::new(16, 5, 10, 10), 0.9),
+                ("person".to_owned(), Rect::new(46, 16, 8, 8), 0.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn highest_scoring_overlapping_box_is_kept() {
+        let (cols, rows) = (64, 32);
+        let strong = (20.0, 10.0, 10.0, 10.0, 2, 0.9);
+        let weak = (21.0, 10.0, 10.0, 10.0, 2, 0.5);
+        // 候选框的先后顺序不影响结果
+        for candidates in [[strong, weak], [weak, strong]] {
+            let model = FixedPredictions(predictions(&candidates, 288.0, 304.0));
+            let mut handler = DetectionNode {
+                model,
+                device: Device::Cpu,
+            };
+
+            let mut node = FakeNode::with_outputs(&["detections"]);
+            node.push(raw_frame(cols, rows, Encoding::Bgr8, cols * rows * 3));
+            let sent = node.run(&mut handler).unwrap();
+
+            let detections = arrow_to_bboxes(sent[0].downcast::<StructArray>().unwrap()).unwrap();
+            assert_eq!(
+                detections,
+                [("car".to_owned(), Rect::new(15, 5, 10, 10), 0.9)]
+            );
+        }
+    }
+}
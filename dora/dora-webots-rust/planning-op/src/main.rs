@@ -1,6 +1,8 @@
-use dora_node_api::{arrow::array::Float32Array, dora_core::config::DataId, DoraNode, Event};
+use dora_node_api::{arrow::array::Float32Array, ArrowData, MetadataParameters};
 use nalgebra::{Vector2, Vector3};
+use node_harness::{NodeHandler, Outputs};
 use std::error::Error;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 struct TrajectoryPoint {
@@ -9,63 +11,82 @@ struct TrajectoryPoint {
     v: f32,
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let (mut node, mut events) = DoraNode::init_from_env()?;
-
-    let mut current_pose = Vector3::new(0.0, 0.0, 0.0);
-    let mut global_waypoints: Vec<Vector2<f32>> = Vec::new();
-    let mut obstacles: Vec<Vector3<f32>> = Vec::new();
-
-    while let Some(event) = events.recv() {
-        match event {
-            Event::Input { id, data, metadata } => match id.as_str() {
-                "position" => {
-                    let array = data.as_any().downcast_ref::<Float32Array>().unwrap();
-                    let val = array.values();
-                    // Webots: [x, y, z, rx, ry, rz] -> 平面坐标用 index 0 和 1
-                    current_pose = Vector3::new(val[0], val[1], val[5]);
+/// 轨迹规划节点：缓存最新位姿和全局路点，每个 tick 输出一段局部轨迹
+#[derive(Default)]
+struct Planner {
+    current_pose: Vector3<f32>,
+    global_waypoints: Vec<Vector2<f32>>,
+    obstacles: Vec<Vector3<f32>>,
+}
+
+impl NodeHandler for Planner {
+    fn on_input(
+        &mut self,
+        id: &str,
+        parameters: MetadataParameters,
+        data: ArrowData,
+        outputs: &mut dyn Outputs,
+    ) -> Result<(), Box<dyn Error>> {
+        match id {
+            "position" => {
+                let array = data
+                    .as_any()
+                    .downcast_ref::<Float32Array>()
+                    .ok_or("position is not Float32Array")?;
+                let val = array.values();
+                if val.len() < 6 {
+                    return Err(format!("position expects 6 values, got {}", val.len()).into());
                 }
-                "objective_waypoints" => {
-                    let array = data.as_any().downcast_ref::<Float32Array>().unwrap();
-                    // 这里应该包含 230-401 所有道路合并后的点
-                    global_waypoints = array
-                        .values()
-                        .chunks_exact(2)
-                        .map(|c| Vector2::new(c[0], c[1]))
-                        .collect();
-                    println!("Loaded {} global waypoints", global_waypoints.len());
+                // Webots: [x, y, z, rx, ry, rz] -> 平面坐标用 index 0 和 1
+                self.current_pose = Vector3::new(val[0], val[1], val[5]);
+            }
+            "objective_waypoints" => {
+                let array = data
+                    .as_any()
+                    .downcast_ref::<Float32Array>()
+                    .ok_or("objective_waypoints is not Float32Array")?;
+                // 这里应该包含 230-401 所有道路合并后的点
+                self.global_waypoints = array
+                    .values()
+                    .chunks_exact(2)
+                    .map(|c| Vector2::new(c[0], c[1]))
+                    .collect();
+                println!("Loaded {} global waypoints", self.global_waypoints.len());
+            }
+            "tick" => {
+                if self.global_waypoints.is_empty() {
+                    return Ok(());
                 }
-                "tick" => {
-                    if global_waypoints.is_empty() {
-                        continue;
-                    }
-
-                    // 执行轨迹规划
-                    let target_path = plan_trajectory(&current_pose, &global_waypoints, &obstacles);
-
-                    // 序列化发送
-                    let mut output_path = Vec::new();
-                    for p in &target_path {
-                        output_path.push(p.x);
-                        output_path.push(p.y);
-                        output_path.push(p.v);
-                    }
-
-                    // 只有当路径有效时才发送
-                    if !output_path.is_empty() {
-                        node.send_output(
-                            DataId::from("waypoints".to_owned()),
-                            metadata.parameters,
-                            Float32Array::from(output_path),
-                        )?;
-                    }
+
+                // 执行轨迹规划
+                let target_path =
+                    plan_trajectory(&self.current_pose, &self.global_waypoints, &self.obstacles);
+
+                // 序列化发送
+                let mut output_path = Vec::new();
+                for p in &target_path {
+                    output_path.push(p.x);
+                    output_path.push(p.y);
+                    output_path.push(p.v);
                 }
-                _ => {}
-            },
+
+                // 只有当路径有效时才发送
+                if !output_path.is_empty() {
+                    outputs.send(
+                        "waypoints",
+                        parameters,
+                        Arc::new(Float32Array::from(output_path)),
+                    )?;
+                }
+            }
             _ => {}
         }
+        Ok(())
     }
-    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    node_harness::run(&mut Planner::default())
 }
 
 fn plan_trajectory(
@@ -148,3 +169,67 @@ fn plan_trajectory(
 
     path
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use node_harness::{FakeNode, Input};
+
+    /// 沿 x 轴每隔 1 米一个路点，共 100 个
+    fn straight_road() -> Input {
+        let points: Vec<f32> = (0..100).flat_map(|i| [i as f32, 0.0]).collect();
+        Input::new("objective_waypoints", Float32Array::from(points))
+    }
+
+    fn position(x: f32, y: f32) -> Input {
+        Input::new(
+            "position",
+            Float32Array::from(vec![x, y, 0.0, 0.0, 0.0, 0.0]),
+        )
+    }
+
+    fn planned(node: &mut FakeNode, planner: &mut Planner) -> Vec<[f32; 3]> {
+        let sent = node.run(planner).unwrap();
+        assert_eq!(sent.len(), 1);
+        sent[0]
+            .downcast::<Float32Array>()
+            .unwrap()
+            .values()
+            .chunks_exact(3)
+            .map(|c| [c[0], c[1], c[2]])
+            .collect()
+    }
+
+    #[test]
+    fn plans_ahead_of_vehicle_once_waypoints_are_loaded() {
+        let mut planner = Planner::default();
+        let mut node = FakeNode::with_outputs(&["waypoints"]);
+
+        // 还没有全局路点时 tick 不输出
+        node.push(position(10.2, 0.3)).push(Input::tick("tick"));
+        assert!(node.run(&mut planner).unwrap().is_empty());
+
+        node.push(straight_road()).push(Input::tick("tick"));
+        let path = planned(&mut node, &mut planner);
+        assert_eq!(path[0], [10.0, 0.0, 2.0]);
+        // 预瞄 30 米且至少 20 个点
+        assert_eq!(path.len(), 32);
+        assert_eq!(path.last().unwrap()[0], 41.0);
+
+        // 接近终点时减速，最后只剩一个点时退回到末尾两点的低速路径
+        node.push(position(95.0, 0.0)).push(Input::tick("tick"));
+        let path = planned(&mut node, &mut planner);
+        assert!(path.iter().all(|p| p[2] == 1.0));
+        node.push(position(120.0, 0.0)).push(Input::tick("tick"));
+        let path = planned(&mut node, &mut planner);
+        assert_eq!(path, [[98.0, 0.0, 0.5], [99.0, 0.0, 0.5]]);
+    }
+
+    #[test]
+    fn short_position_is_rejected() {
+        let mut planner = Planner::default();
+        let mut node = FakeNode::new();
+        node.push(Input::new("position", Float32Array::from(vec![1.0, 2.0])));
+        assert!(node.run(&mut planner).is_err());
+    }
+}
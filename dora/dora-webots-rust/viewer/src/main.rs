@@ -1,14 +1,16 @@
 use anyhow::{Context, Result};
 use dora_node_api::{
     arrow::array::{Float32Array, StructArray, UInt8Array},
-    DoraNode, Event, Parameter,
+    DoraNode, Event,
 };
+use image_frame::FrameInfo;
 use opencv::{
-    core::{AlgorithmHint, Mat, Point, Rect, Scalar, Vec4b},
-    highgui, imgproc,
+    core::{Point, Rect, Scalar},
+    imgproc,
     prelude::*,
 };
 use std::error::Error;
+use webcam_core::sink::FrameSink;
 
 mod utils;
 use utils::arrow_to_bboxes;
@@ -24,12 +26,13 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut planned_path = Vec::new(); // 规划路径点 [x, z]
     let mut obstacles_3d = Vec::new(); // 障碍物世界坐标 [x, z]
 
-    // 创建 OpenCV 窗口
-    let win_name = "Dora Autonomous Driving Monitor";
-    highgui::named_window(win_name, highgui::WINDOW_NORMAL)
-        .context("Failed to create highgui window")?;
+    // 窗口，或无显示器时写入视频文件 / PNG 序列，由 dataflow.yml 中的 VIEWER_OUTPUT 选择
+    let mut sink = FrameSink::from_env("Dora Autonomous Driving Monitor")?;
 
-    println!("Plot operator initialized. Waiting for data...");
+    println!(
+        "Plot operator initialized, output: {}. Waiting for data...",
+        sink.describe()
+    );
 
     while let Some(event) = events.recv() {
         match event {
@@ -87,36 +90,19 @@ fn main() -> Result<(), Box<dyn Error>> {
 
                 // --- F. 核心渲染逻辑：处理图像输入 ---
                 "frame" => {
-                    let cols = match metadata.parameters.get("width") {
-                        Some(Parameter::Integer(v)) => *v as i32,
-                        _ => 640,
-                    };
-                    let rows = match metadata.parameters.get("height") {
-                        Some(Parameter::Integer(v)) => *v as i32,
-                        _ => 480,
-                    };
-
                     let uint8_array = data
                         .as_any()
                         .downcast_ref::<UInt8Array>()
                         .context("Expected UInt8Array for image")?;
                     let byte_slice = uint8_array.values();
 
-                    if byte_slice.len() != (rows * cols * 4) as usize {
+                    // 1. 按元数据中的编码解码为 BGR，尺寸与数据长度不符的帧跳过
+                    let Ok(mut display_frame) = FrameInfo::from_parameters(&metadata.parameters)
+                        .and_then(|info| image_frame::to_bgr(&info, byte_slice))
+                    else {
                         continue;
-                    }
-
-                    // 1. BGRA 转 BGR
-                    let (_head, vec4_slice, _tail) = unsafe { byte_slice.align_to::<Vec4b>() };
-                    let frame_raw = Mat::new_rows_cols_with_data(rows, cols, vec4_slice)?;
-                    let mut display_frame = Mat::default();
-                    imgproc::cvt_color(
-                        &frame_raw,
-                        &mut display_frame,
-                        imgproc::COLOR_BGRA2BGR,
-                        0,
-                        AlgorithmHint::ALGO_HINT_DEFAULT,
-                    )?;
+                    };
+                    let cols = display_frame.cols();
 
                     // 2. 绘制 YOLO 2D 检测框
                     for (classname, bbox, conf) in &bboxes {
@@ -232,9 +218,8 @@ fn main() -> Result<(), Box<dyn Error>> {
                     //     0,
                     // )?;
 
-                    // 5. 显示并刷新
-                    highgui::imshow(win_name, &display_frame)?;
-                    if highgui::wait_key(1)? == 27 {
+                    // 5. 显示并刷新，或写入文件
+                    if sink.show(&display_frame)? == Some(27) {
                         break;
                     } // ESC 退出
                 }
@@ -244,5 +229,5 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
     }
 
-    Ok(())
+    sink.finish()
 }
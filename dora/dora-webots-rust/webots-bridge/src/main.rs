@@ -1,8 +1,9 @@
 use dora_node_api::{
     arrow::array::{Float32Array, StringArray, UInt8Array},
     dora_core::config::DataId,
-    DoraNode, Event, Parameter,
+    DoraNode, Event,
 };
+use image_frame::{Encoding, FrameInfo};
 use std::error::Error;
 use webots_sys::WebotsRobot; // 确保你的 WebotsRobot 结构体已经按之前的建议添加了解析方法
 
@@ -50,6 +51,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         );
     }
 
+    let mut image_seq = 0;
     while let Some(event) = events.recv() {
         match event {
             Event::Input { id, metadata, data } => match id.as_str() {
@@ -58,13 +60,21 @@ fn main() -> Result<(), Box<dyn Error>> {
                         break;
                     }
 
-                    // 1. Camera Image
+                    // 1. Camera Image (Webots 相机输出紧密排列的 BGRA 像素)
                     let image = robot.get_camera_image();
+                    let captured_ns = image_frame::now_ns();
                     let w = robot.get_camera_width();
                     let h = robot.get_camera_height();
                     let mut params = metadata.parameters.clone();
-                    params.insert("width".into(), Parameter::Integer(w as i64));
-                    params.insert("height".into(), Parameter::Integer(h as i64));
+                    FrameInfo::packed(
+                        w as usize,
+                        h as usize,
+                        Encoding::Bgra8,
+                        captured_ns,
+                        image_seq,
+                    )
+                    .insert_into(&mut params);
+                    image_seq += 1;
 
                     node.send_output(
                         DataId::from("image".to_owned()),
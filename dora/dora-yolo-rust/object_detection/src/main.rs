@@ -1,8 +1,9 @@
 use anyhow::Context;
 use dora_node_api::{arrow::array::UInt8Array, dora_core::config::DataId, DoraNode, Event};
+use image_frame::FrameInfo;
 use opencv::{
-    core::{copy_make_border, AlgorithmHint, Rect, Scalar, Vector},
-    imgcodecs, imgproc,
+    core::{copy_make_border, AlgorithmHint, Rect, Scalar},
+    imgproc,
     prelude::*,
 };
 use std::error::Error;
@@ -168,12 +169,16 @@ fn main() -> Result<(), Box<dyn Error>> {
                     // 2. 提取 UInt8Array 的字节切片
                     let byte_slice = uint8_array.values(); // 返回 &[u8]
 
-                    // 3. 转换为 OpenCV Vector<u8>（from_slice 接收 &[u8]）
-                    let buffer = Vector::from_slice(byte_slice);
-
-                    // 解码 JPEG 数据成 Mat
-                    let frame = imgcodecs::imdecode(&buffer, imgcodecs::IMREAD_COLOR)
-                        .context("Failed to decode image from buffer")?;
+                    // 3. 按元数据中的编码（JPEG 或原始像素）解码为 BGR Mat，无法解码的帧跳过
+                    let frame = match FrameInfo::from_parameters(&metadata.parameters)
+                        .and_then(|info| image_frame::to_bgr(&info, byte_slice))
+                    {
+                        Ok(frame) => frame,
+                        Err(e) => {
+                            eprintln!("Skipping frame: {e}");
+                            continue;
+                        }
+                    };
 
                     // --- 步骤 A: 图像预处理 (OpenCV -> Candle Tensor) ---
                     let (processed_tensor, ratio, pad_w, pad_h) =
@@ -324,7 +329,7 @@ fn report_detect(
     // 简单 NMS (非极大值抑制)
     // 注意：生产环境建议使用 torchvision 或 opencv 自带的 NMSBoxes
     let mut kept_results = Vec::new();
-    results.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap()); // 按置信度降序
+    results.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap()); // 按置信度升序，pop 先取出最高分
 
     while let Some(current) = results.pop() {
         kept_results.push(current.clone());
@@ -348,3 +353,27 @@ fn iou(box_a: &Rect, box_b: &Rect) -> f32 {
 
     inter_area / (box_a_area + box_b_area - inter_area)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::CV_8UC3;
+
+    #[test]
+    fn highest_scoring_overlapping_box_is_kept() {
+        // 两个重叠的 car 候选框，(84, 2) 中每列为一个候选框
+        let mut data = vec![0f32; 84 * 2];
+        for (i, (cx, score)) in [(20.0, 0.9), (21.0, 0.5)].into_iter().enumerate() {
+            data[i] = cx;
+            data[2 + i] = 10.0;
+            data[4 + i] = 10.0;
+            data[6 + i] = 10.0;
+            data[(4 + 2) * 2 + i] = score;
+        }
+        let pred = Tensor::from_vec(data, (84, 2), &Device::Cpu).unwrap();
+        let frame = Mat::new_rows_cols_with_default(32, 64, CV_8UC3, Scalar::all(0.0)).unwrap();
+
+        let (detections, _) = report_detect(&pred, &frame, 1.0, 0.0, 0.0).unwrap();
+        assert_eq!(detections, [("car", Rect::new(15, 5, 10, 10), 0.9)]);
+    }
+}
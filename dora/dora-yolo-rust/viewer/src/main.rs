@@ -3,9 +3,10 @@ use dora_node_api::{
     arrow::array::{StructArray, UInt8Array},
     DoraNode, Event,
 };
+use image_frame::FrameInfo;
 use opencv::{
-    core::{Point, Scalar, Vector},
-    highgui, imgcodecs, imgproc,
+    core::{Point, Scalar},
+    imgproc,
     prelude::*,
 };
 use std::error::Error;
@@ -13,21 +14,17 @@ use std::error::Error;
 mod utils;
 
 use utils::arrow_to_bboxes;
+use webcam_core::sink::FrameSink;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let (mut _node, mut events) = DoraNode::init_from_env()?;
     let mut bboxes = Vec::new();
-    // 创建一个用于显示的窗口
-    highgui::named_window("Dora Webcam Viewer (Rust)", highgui::WINDOW_NORMAL)
-        .context("Failed to create highgui window")?;
-    println!("Viewer operator initialized.");
+    // 窗口，或无显示器的服务器上写入视频文件 / PNG 序列，由 dataflow.yml 中的 VIEWER_OUTPUT 选择
+    let mut sink = FrameSink::from_env("Dora Webcam Viewer (Rust)")?;
+    println!("Viewer operator initialized, output: {}", sink.describe());
     while let Some(event) = events.recv() {
         match event {
-            Event::Input {
-                id,
-                metadata: _,
-                data,
-            } => match id.as_str() {
+            Event::Input { id, metadata, data } => match id.as_str() {
                 "detections" => {
                     let struct_array = data
                         .as_any()
@@ -48,12 +45,16 @@ fn main() -> Result<(), Box<dyn Error>> {
                     // 2. 提取 UInt8Array 的字节切片
                     let byte_slice = uint8_array.values(); // 返回 &[u8]
 
-                    // 3. 转换为 OpenCV Vector<u8>（from_slice 接收 &[u8]）
-                    let buffer = Vector::from_slice(byte_slice);
-
-                    // 解码 JPEG 数据成 Mat
-                    let frame = imgcodecs::imdecode(&buffer, imgcodecs::IMREAD_COLOR)
-                        .context("Failed to decode image from buffer")?;
+                    // 3. 按元数据中的编码（JPEG 或原始像素）解码为 BGR Mat，无法解码的帧跳过
+                    let frame = match FrameInfo::from_parameters(&metadata.parameters)
+                        .and_then(|info| image_frame::to_bgr(&info, byte_slice))
+                    {
+                        Ok(frame) => frame,
+                        Err(e) => {
+                            eprintln!("Skipping frame: {e}");
+                            continue;
+                        }
+                    };
 
                     if frame
                         .size()
@@ -88,11 +89,8 @@ fn main() -> Result<(), Box<dyn Error>> {
                                 false,
                             )?;
                         }
-                        // 显示图像
-                        highgui::imshow("Dora Webcam Viewer (Rust)", &display_frame)
-                            .context("Failed to imshow frame")?;
-                        // 必须调用 wait_key 来处理 GUI 事件
-                        highgui::wait_key(1).context("Failed to wait_key")?;
+                        // 显示或写入标注后的图像
+                        sink.show(&display_frame)?;
                     }
                 }
                 other => eprintln!("Received input `{other}`"),
@@ -101,5 +99,5 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
     }
 
-    Ok(())
+    sink.finish()
 }
@@ -0,0 +1,344 @@
+//! 图像帧输出的元数据约定：发送方把像素字节作为 `UInt8Array` 发送，并在元数据中写入
+//!
+//! | 参数 | 类型 | 含义 |
+//! | --- | --- | --- |
+//! | `width` | Integer | 帧宽 (像素) |
+//! | `height` | Integer | 帧高 (像素) |
+//! | `encoding` | String | 原始像素 `bgr8`、`bgra8`、`rgb8`，或压缩格式 `jpeg`、`png`、`webp` |
+//! | `stride` | Integer | 原始像素每行的字节数，可大于 `width × 每像素字节数`；压缩格式不写 |
+//! | `timestamp` | Integer | 采集时刻，Unix 纪元以来的纳秒数 |
+//! | `seq` | Integer | 帧序号，每个发送方从 0 递增 |
+//! | `encoded_bytes` | Integer | 本帧数据的字节数，便于下游统计带宽 |
+//!
+//! 接收方用 [`FrameInfo::from_parameters`] 解析，启用 `opencv` 特性后用 [`to_bgr`] 把任意编码的帧
+//! 转为 BGR `Mat`。为兼容只发送 JPEG 字节的旧节点，缺少 `encoding` 时按 `jpeg` 处理
+
+use dora_node_api::{MetadataParameters, Parameter};
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "opencv")]
+mod mat;
+
+#[cfg(feature = "opencv")]
+pub use mat::{encode, to_bgr};
+
+/// JPEG、WebP 质量和 PNG 压缩级别的取值范围
+pub const JPEG_QUALITY: RangeInclusive<i32> = 0..=100;
+pub const WEBP_QUALITY: RangeInclusive<i32> = 1..=100;
+pub const PNG_COMPRESSION: RangeInclusive<i32> = 0..=9;
+
+pub const WIDTH_PARAM: &str = "width";
+pub const HEIGHT_PARAM: &str = "height";
+pub const ENCODING_PARAM: &str = "encoding";
+pub const STRIDE_PARAM: &str = "stride";
+pub const TIMESTAMP_PARAM: &str = "timestamp";
+pub const SEQ_PARAM: &str = "seq";
+pub const ENCODED_BYTES_PARAM: &str = "encoded_bytes";
+
+/// 帧数据的编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Bgr8,
+    Bgra8,
+    Rgb8,
+    Jpeg,
+    Png,
+    Webp,
+}
+
+impl Encoding {
+    /// 原始像素每像素的字节数，压缩格式为 `None`
+    pub fn bytes_per_pixel(&self) -> Option<usize> {
+        match self {
+            Encoding::Bgr8 | Encoding::Rgb8 => Some(3),
+            Encoding::Bgra8 => Some(4),
+            Encoding::Jpeg | Encoding::Png | Encoding::Webp => None,
+        }
+    }
+
+    /// 压缩格式可调的质量参数范围：JPEG、WebP 为质量，PNG 为压缩级别
+    pub fn quality_range(&self) -> Option<RangeInclusive<i32>> {
+        match self {
+            Encoding::Jpeg => Some(JPEG_QUALITY),
+            Encoding::Webp => Some(WEBP_QUALITY),
+            Encoding::Png => Some(PNG_COMPRESSION),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "bgr8" => Ok(Encoding::Bgr8),
+            "bgra8" => Ok(Encoding::Bgra8),
+            "rgb8" => Ok(Encoding::Rgb8),
+            "jpeg" | "jpg" => Ok(Encoding::Jpeg),
+            "png" => Ok(Encoding::Png),
+            "webp" => Ok(Encoding::Webp),
+            _ => Err(format!(
+                "未知的图像编码 `{s}`，可选 bgr8、bgra8、rgb8、jpeg、png、webp"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Encoding::Bgr8 => "bgr8",
+            Encoding::Bgra8 => "bgra8",
+            Encoding::Rgb8 => "rgb8",
+            Encoding::Jpeg => "jpeg",
+            Encoding::Png => "png",
+            Encoding::Webp => "webp",
+        })
+    }
+}
+
+/// 一帧图像的元数据
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameInfo {
+    pub width: usize,
+    pub height: usize,
+    pub encoding: Encoding,
+    /// 原始像素每行的字节数，压缩格式为 0
+    pub stride: usize,
+    pub timestamp_ns: u64,
+    pub seq: u64,
+    /// 数据字节数：原始像素为 `stride × height`，压缩格式在编码后填写，未知时为 0
+    pub encoded_bytes: usize,
+}
+
+impl FrameInfo {
+    /// 行与行之间没有填充的帧，`timestamp_ns` 为采集时刻
+    pub fn packed(
+        width: usize,
+        height: usize,
+        encoding: Encoding,
+        timestamp_ns: u64,
+        seq: u64,
+    ) -> Self {
+        let stride = width * encoding.bytes_per_pixel().unwrap_or(0);
+        Self {
+            width,
+            height,
+            encoding,
+            stride,
+            timestamp_ns,
+            seq,
+            encoded_bytes: stride * height,
+        }
+    }
+
+    /// 写入输出元数据，保留 `parameters` 中的其他参数
+    pub fn insert_into(&self, parameters: &mut MetadataParameters) {
+        let integer = |value: usize| Parameter::Integer(value as i64);
+        parameters.insert(WIDTH_PARAM.to_owned(), integer(self.width));
+        parameters.insert(HEIGHT_PARAM.to_owned(), integer(self.height));
+        parameters.insert(
+            ENCODING_PARAM.to_owned(),
+            Parameter::String(self.encoding.to_string()),
+        );
+        if self.encoding.bytes_per_pixel().is_some() {
+            parameters.insert(STRIDE_PARAM.to_owned(), integer(self.stride));
+        } else {
+            parameters.remove(STRIDE_PARAM);
+        }
+        parameters.insert(
+            TIMESTAMP_PARAM.to_owned(),
+            Parameter::Integer(self.timestamp_ns as i64),
+        );
+        parameters.insert(SEQ_PARAM.to_owned(), Parameter::Integer(self.seq as i64));
+        parameters.insert(ENCODED_BYTES_PARAM.to_owned(), integer(self.encoded_bytes));
+    }
+
+    /// 从输入元数据解析；原始像素必须带 `width`/`height`，`stride` 缺省为紧密排列
+    pub fn from_parameters(parameters: &MetadataParameters) -> Result<Self, Box<dyn Error>> {
+        let encoding = match parameters.get(ENCODING_PARAM) {
+            Some(Parameter::String(value)) => value.parse()?,
+            Some(other) => {
+                return Err(format!("{ENCODING_PARAM} 应为字符串，收到 {other:?}").into())
+            }
+            None => Encoding::Jpeg,
+        };
+        let raw = encoding.bytes_per_pixel();
+        let width = integer(parameters, WIDTH_PARAM, raw.is_some())?;
+        let height = integer(parameters, HEIGHT_PARAM, raw.is_some())?;
+        let stride = match raw {
+            Some(bpp) => {
+                let stride = integer(parameters, STRIDE_PARAM, false)?;
+                if stride == 0 {
+                    width * bpp
+                } else if stride < width * bpp {
+                    return Err(format!(
+                        "{STRIDE_PARAM}={stride} 小于 {width} 像素的 {encoding} 行宽"
+                    )
+                    .into());
+                } else {
+                    stride
+                }
+            }
+            None => 0,
+        };
+        Ok(Self {
+            width,
+            height,
+            encoding,
+            stride,
+            timestamp_ns: integer(parameters, TIMESTAMP_PARAM, false)? as u64,
+            seq: integer(parameters, SEQ_PARAM, false)? as u64,
+            encoded_bytes: integer(parameters, ENCODED_BYTES_PARAM, false)?,
+        })
+    }
+
+    /// 检查数据长度与尺寸是否一致，压缩格式只要求非空
+    pub fn check_len(&self, len: usize) -> Result<(), Box<dyn Error>> {
+        let expected = match self.encoding.bytes_per_pixel() {
+            None if len > 0 => return Ok(()),
+            None => return Err(format!("{} 帧为空", self.encoding).into()),
+            Some(_) if self.width == 0 || self.height == 0 => {
+                return Err(format!("帧尺寸 {}x{} 无效", self.width, self.height).into())
+            }
+            Some(_) => self.stride * self.height,
+        };
+        if len != expected {
+            return Err(format!(
+                "{}x{} {} 帧 (stride {}) 应为 {expected} 字节，收到 {len} 字节",
+                self.width, self.height, self.encoding, self.stride
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// 发送方的编码设置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EncodeOptions {
+    pub encoding: Encoding,
+    /// JPEG/WebP 质量或 PNG 压缩级别，`None` 使用 OpenCV 的默认值
+    pub quality: Option<i32>,
+    /// 编码前的缩放比例，1.0 为原尺寸
+    pub scale: f64,
+}
+
+impl EncodeOptions {
+    /// 检查质量参数是否适用于该编码并在范围内，缩放比例须在 (0, 1] 内
+    pub fn new(encoding: Encoding, quality: Option<i32>, scale: f64) -> Result<Self, String> {
+        if let Some(quality) = quality {
+            match encoding.quality_range() {
+                Some(range) if range.contains(&quality) => {}
+                Some(range) => {
+                    return Err(format!(
+                        "{encoding} 的质量参数应在 {}..={} 之间，收到 {quality}",
+                        range.start(),
+                        range.end()
+                    ))
+                }
+                None => return Err(format!("原始像素编码 {encoding} 不支持质量参数")),
+            }
+        }
+        if !(scale > 0.0 && scale <= 1.0) {
+            return Err(format!("缩放比例应在 (0, 1] 之间，收到 {scale}"));
+        }
+        Ok(Self {
+            encoding,
+            quality,
+            scale,
+        })
+    }
+}
+
+/// 读取非负整数参数，缺少且非必需时为 0
+fn integer(
+    parameters: &MetadataParameters,
+    key: &str,
+    required: bool,
+) -> Result<usize, Box<dyn Error>> {
+    match parameters.get(key) {
+        Some(Parameter::Integer(value)) if *value >= 0 => Ok(*value as usize),
+        Some(other) => Err(format!("{key} 应为非负整数，收到 {other:?}").into()),
+        None if required => Err(format!("原始像素帧缺少 {key} 参数").into()),
+        None => Ok(0),
+    }
+}
+
+/// 当前时刻，Unix 纪元以来的纳秒数；发送方应在取得帧后立即调用
+pub fn now_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_info_round_trips_through_parameters() {
+        let mut parameters = MetadataParameters::new();
+        parameters.insert("other".to_owned(), Parameter::Bool(true));
+        let mut info = FrameInfo::packed(4, 2, Encoding::Bgra8, 1_000, 7);
+        assert_eq!(info.encoded_bytes, 32);
+        info.stride = 20;
+        info.encoded_bytes = 40;
+        info.insert_into(&mut parameters);
+
+        assert_eq!(FrameInfo::from_parameters(&parameters).unwrap(), info);
+        assert_eq!(parameters.get("other"), Some(&Parameter::Bool(true)));
+        assert!(info.check_len(40).is_ok());
+        assert!(info.check_len(32).is_err());
+
+        // 改为 JPEG 时去掉 stride
+        let mut jpeg = FrameInfo::packed(4, 2, Encoding::Jpeg, 2_000, 8);
+        jpeg.encoded_bytes = 123;
+        jpeg.insert_into(&mut parameters);
+        assert!(!parameters.contains_key(STRIDE_PARAM));
+        assert_eq!(FrameInfo::from_parameters(&parameters).unwrap(), jpeg);
+    }
+
+    #[test]
+    fn missing_fields_fall_back_or_fail_clearly() {
+        // 只有 JPEG 字节的旧格式
+        let info = FrameInfo::from_parameters(&MetadataParameters::new()).unwrap();
+        assert_eq!(info.encoding, Encoding::Jpeg);
+        assert!(info.check_len(0).is_err());
+
+        let mut parameters = MetadataParameters::new();
+        parameters.insert(ENCODING_PARAM.to_owned(), Parameter::String("RGB8".into()));
+        parameters.insert(WIDTH_PARAM.to_owned(), Parameter::Integer(4));
+        let err = FrameInfo::from_parameters(&parameters).unwrap_err();
+        assert!(err.to_string().contains(HEIGHT_PARAM), "{err}");
+
+        parameters.insert(HEIGHT_PARAM.to_owned(), Parameter::Integer(2));
+        let info = FrameInfo::from_parameters(&parameters).unwrap();
+        assert_eq!((info.encoding, info.stride), (Encoding::Rgb8, 12));
+
+        parameters.insert(STRIDE_PARAM.to_owned(), Parameter::Integer(8));
+        assert!(FrameInfo::from_parameters(&parameters).is_err());
+        parameters.insert(ENCODING_PARAM.to_owned(), Parameter::String("yuv".into()));
+        assert!(FrameInfo::from_parameters(&parameters).is_err());
+    }
+
+    #[test]
+    fn encode_options_check_quality_per_encoding() {
+        assert!(EncodeOptions::new(Encoding::Jpeg, Some(80), 0.5).is_ok());
+        assert!(EncodeOptions::new(Encoding::Png, Some(9), 1.0).is_ok());
+        assert!(EncodeOptions::new(Encoding::Webp, None, 1.0).is_ok());
+
+        assert!(EncodeOptions::new(Encoding::Png, Some(10), 1.0).is_err());
+        assert!(EncodeOptions::new(Encoding::Webp, Some(0), 1.0).is_err());
+        assert!(EncodeOptions::new(Encoding::Bgr8, Some(80), 1.0).is_err());
+        assert!(EncodeOptions::new(Encoding::Jpeg, None, 0.0).is_err());
+        assert!(EncodeOptions::new(Encoding::Jpeg, None, 1.5).is_err());
+        assert!(EncodeOptions::new(Encoding::Jpeg, None, f64::NAN).is_err());
+    }
+}
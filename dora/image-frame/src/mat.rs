@@ -0,0 +1,117 @@
+use opencv::{
+    core::{AlgorithmHint, Mat, Rect, Size, Vector},
+    imgcodecs, imgproc,
+    prelude::*,
+};
+use std::error::Error;
+
+use crate::{EncodeOptions, Encoding, FrameInfo};
+
+/// 把 BGR 帧按 `options` 缩放并编码，返回字节和紧密排列的帧信息（尺寸为缩放后，时间戳为传入的采集时刻，带编码后的字节数）
+pub fn encode(
+    frame: &Mat,
+    options: &EncodeOptions,
+    timestamp_ns: u64,
+    seq: u64,
+) -> Result<(Vec<u8>, FrameInfo), Box<dyn Error>> {
+    let scaled;
+    let frame = if options.scale < 1.0 {
+        let mut resized = Mat::default();
+        // 缩小时 INTER_AREA 的混叠最少
+        imgproc::resize(
+            frame,
+            &mut resized,
+            Size::default(),
+            options.scale,
+            options.scale,
+            imgproc::INTER_AREA,
+        )?;
+        scaled = resized;
+        &scaled
+    } else {
+        frame
+    };
+
+    let encoding = options.encoding;
+    let mut info = FrameInfo::packed(
+        frame.cols() as usize,
+        frame.rows() as usize,
+        encoding,
+        timestamp_ns,
+        seq,
+    );
+    let bytes = match encoding {
+        Encoding::Jpeg => compress(frame, ".jpg", imgcodecs::IMWRITE_JPEG_QUALITY, options)?,
+        Encoding::Png => compress(frame, ".png", imgcodecs::IMWRITE_PNG_COMPRESSION, options)?,
+        Encoding::Webp => compress(frame, ".webp", imgcodecs::IMWRITE_WEBP_QUALITY, options)?,
+        Encoding::Bgr8 => packed_bytes(frame)?,
+        Encoding::Rgb8 => packed_bytes(&convert(frame, imgproc::COLOR_BGR2RGB)?)?,
+        Encoding::Bgra8 => packed_bytes(&convert(frame, imgproc::COLOR_BGR2BGRA)?)?,
+    };
+    info.encoded_bytes = bytes.len();
+    Ok((bytes, info))
+}
+
+/// 用 `imencode` 压缩，设置了质量时通过 `flag` 传给编码器
+fn compress(
+    frame: &Mat,
+    ext: &str,
+    flag: i32,
+    options: &EncodeOptions,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut params = Vector::new();
+    if let Some(quality) = options.quality {
+        params.push(flag);
+        params.push(quality);
+    }
+    let mut buffer = Vector::new();
+    if !imgcodecs::imencode(ext, frame, &mut buffer, &params)? {
+        return Err(format!("OpenCV 无法编码为 {}", options.encoding).into());
+    }
+    Ok(buffer.to_vec())
+}
+
+/// 把任意编码的帧转为 BGR `Mat`，数据长度与 `info` 不符时返回错误
+pub fn to_bgr(info: &FrameInfo, data: &[u8]) -> Result<Mat, Box<dyn Error>> {
+    info.check_len(data.len())?;
+    let Some(bpp) = info.encoding.bytes_per_pixel() else {
+        // JPEG、PNG、WebP 都由 imdecode 按文件头识别
+        let frame = imgcodecs::imdecode(&Vector::from_slice(data), imgcodecs::IMREAD_COLOR)?;
+        if frame.empty() {
+            return Err(format!("无法解码 {} 帧", info.encoding).into());
+        }
+        return Ok(frame);
+    };
+
+    // 按字节矩阵包装数据，去掉行尾填充后再按通道数解释为像素
+    let rows = Mat::new_rows_cols_with_data(info.height as i32, info.stride as i32, data)?;
+    let row_bytes = Rect::new(0, 0, (info.width * bpp) as i32, info.height as i32);
+    let packed = Mat::roi(&*rows, row_bytes)?.try_clone()?;
+    let pixels = packed.reshape(bpp as i32, info.height as i32)?;
+    match info.encoding {
+        Encoding::Rgb8 => convert(&pixels, imgproc::COLOR_RGB2BGR),
+        Encoding::Bgra8 => convert(&pixels, imgproc::COLOR_BGRA2BGR),
+        _ => Ok(pixels.try_clone()?),
+    }
+}
+
+fn convert(frame: &Mat, code: i32) -> Result<Mat, Box<dyn Error>> {
+    let mut converted = Mat::default();
+    imgproc::cvt_color(
+        frame,
+        &mut converted,
+        code,
+        0,
+        AlgorithmHint::ALGO_HINT_DEFAULT,
+    )?;
+    Ok(converted)
+}
+
+/// 行与行之间没有填充的像素字节
+fn packed_bytes(frame: &Mat) -> Result<Vec<u8>, Box<dyn Error>> {
+    if frame.is_continuous() {
+        Ok(frame.data_bytes()?.to_vec())
+    } else {
+        Ok(frame.try_clone()?.data_bytes()?.to_vec())
+    }
+}
@@ -0,0 +1,231 @@
+//! 把节点逻辑与 `DoraNode` 解耦：节点实现 [`NodeHandler`]，运行时由 [`run`] 接入 dora 事件流，
+//! 测试时由 [`FakeNode`] 按脚本顺序投递输入并记录发送的输出，无需启动 dora 守护进程
+
+use dora_node_api::arrow::array::{new_empty_array, Array, ArrayRef};
+use dora_node_api::arrow::datatypes::DataType;
+use dora_node_api::{
+    dora_core::config::DataId, ArrowData, DoraNode, Event, MetadataParameters, Parameter,
+};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::sync::Arc;
+
+/// 节点发送输出的通道：运行时是 `DoraNode`，测试时是内存中的记录
+pub trait Outputs {
+    fn send(
+        &mut self,
+        id: &str,
+        parameters: MetadataParameters,
+        data: ArrayRef,
+    ) -> Result<(), Box<dyn Error>>;
+}
+
+impl Outputs for DoraNode {
+    fn send(
+        &mut self,
+        id: &str,
+        parameters: MetadataParameters,
+        data: ArrayRef,
+    ) -> Result<(), Box<dyn Error>> {
+        self.send_output(DataId::from(id.to_owned()), parameters, data)?;
+        Ok(())
+    }
+}
+
+/// 节点逻辑：每收到一个输入调用一次，通过 `outputs` 发送结果
+pub trait NodeHandler {
+    fn on_input(
+        &mut self,
+        id: &str,
+        parameters: MetadataParameters,
+        data: ArrowData,
+        outputs: &mut dyn Outputs,
+    ) -> Result<(), Box<dyn Error>>;
+}
+
+/// 连接 dora，把每个输入交给 `handler`，直到事件流结束
+pub fn run(handler: &mut dyn NodeHandler) -> Result<(), Box<dyn Error>> {
+    let (mut node, mut events) = DoraNode::init_from_env()?;
+    while let Some(event) = events.recv() {
+        if let Event::Input { id, metadata, data } = event {
+            handler.on_input(id.as_str(), metadata.parameters, data, &mut node)?;
+        }
+    }
+    Ok(())
+}
+
+/// 测试脚本中的一个输入
+#[derive(Debug, Clone)]
+pub struct Input {
+    pub id: String,
+    pub parameters: MetadataParameters,
+    pub data: ArrayRef,
+}
+
+impl Input {
+    pub fn new(id: &str, data: impl Array + 'static) -> Self {
+        Self {
+            id: id.to_owned(),
+            parameters: MetadataParameters::new(),
+            data: Arc::new(data),
+        }
+    }
+
+    /// 不带数据的输入，例如 `dora/timer` 产生的 tick
+    pub fn tick(id: &str) -> Self {
+        Self::new(id, new_empty_array(&DataType::Null))
+    }
+
+    pub fn with_parameter(mut self, key: &str, value: Parameter) -> Self {
+        self.parameters.insert(key.to_owned(), value);
+        self
+    }
+}
+
+/// 节点发送的一个输出
+#[derive(Debug, Clone)]
+pub struct Output {
+    pub id: String,
+    pub parameters: MetadataParameters,
+    pub data: ArrayRef,
+}
+
+impl Output {
+    /// 把输出数据转换为具体的 Arrow 数组类型，类型不符时返回 `None`
+    pub fn downcast<T: Array + 'static>(&self) -> Option<&T> {
+        self.data.as_any().downcast_ref::<T>()
+    }
+}
+
+struct Recorder<'a> {
+    declared: &'a Option<Vec<String>>,
+    sent: Vec<Output>,
+}
+
+impl Outputs for Recorder<'_> {
+    fn send(
+        &mut self,
+        id: &str,
+        parameters: MetadataParameters,
+        data: ArrayRef,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(declared) = self.declared {
+            // dora 会静默丢弃未声明的输出，测试中直接报错以暴露拼写错误
+            if !declared.iter().any(|d| d == id) {
+                return Err(format!("输出 `{id}` 未在 outputs 中声明").into());
+            }
+        }
+        self.sent.push(Output {
+            id: id.to_owned(),
+            parameters,
+            data,
+        });
+        Ok(())
+    }
+}
+
+/// 内存中的假节点：代替 dora 事件流按顺序投递排队的输入，并记录处理器发送的输出
+#[derive(Default)]
+pub struct FakeNode {
+    inputs: VecDeque<Input>,
+    /// 与 dataflow.yml 中的 `outputs` 对应，`None` 表示不检查
+    declared: Option<Vec<String>>,
+}
+
+impl FakeNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 只允许发送这些输出，发送其他输出时 `run` 返回错误
+    pub fn with_outputs(outputs: &[&str]) -> Self {
+        Self {
+            inputs: VecDeque::new(),
+            declared: Some(outputs.iter().map(|id| (*id).to_owned()).collect()),
+        }
+    }
+
+    pub fn push(&mut self, input: Input) -> &mut Self {
+        self.inputs.push_back(input);
+        self
+    }
+
+    /// 把排队的输入依次交给 `handler`，返回期间发送的全部输出
+    pub fn run(&mut self, handler: &mut dyn NodeHandler) -> Result<Vec<Output>, Box<dyn Error>> {
+        let mut recorder = Recorder {
+            declared: &self.declared,
+            sent: Vec::new(),
+        };
+        while let Some(input) = self.inputs.pop_front() {
+            handler.on_input(
+                &input.id,
+                input.parameters,
+                ArrowData(input.data),
+                &mut recorder,
+            )?;
+        }
+        Ok(recorder.sent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dora_node_api::arrow::array::Float32Array;
+
+    /// 累加收到的数值，每次 tick 输出一次总和
+    #[derive(Default)]
+    struct Sum(f32);
+
+    impl NodeHandler for Sum {
+        fn on_input(
+            &mut self,
+            id: &str,
+            parameters: MetadataParameters,
+            data: ArrowData,
+            outputs: &mut dyn Outputs,
+        ) -> Result<(), Box<dyn Error>> {
+            match id {
+                "value" => {
+                    let array = data
+                        .as_any()
+                        .downcast_ref::<Float32Array>()
+                        .ok_or("数据不是 Float32Array")?;
+                    self.0 += array.values().iter().sum::<f32>();
+                }
+                "tick" => outputs.send(
+                    "sum",
+                    parameters,
+                    Arc::new(Float32Array::from(vec![self.0])),
+                )?,
+                "typo" => {
+                    outputs.send("summ", parameters, Arc::new(Float32Array::from(vec![0.0])))?
+                }
+                _ => {}
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn scripted_inputs_drive_handler_in_order() {
+        let mut handler = Sum::default();
+        let mut node = FakeNode::with_outputs(&["sum"]);
+        node.push(Input::new("value", Float32Array::from(vec![1.0, 2.0])))
+            .push(Input::tick("tick").with_parameter("seq", Parameter::Integer(1)))
+            .push(Input::new("value", Float32Array::from(vec![3.0])))
+            .push(Input::tick("tick"));
+
+        let sent = node.run(&mut handler).unwrap();
+        let sums: Vec<f32> = sent
+            .iter()
+            .map(|output| output.downcast::<Float32Array>().unwrap().value(0))
+            .collect();
+        assert_eq!(sums, [3.0, 6.0]);
+        assert_eq!(sent[0].parameters.get("seq"), Some(&Parameter::Integer(1)));
+
+        // 未声明的输出在测试中报错，而不是像 dora 那样被静默丢弃
+        node.push(Input::tick("typo"));
+        assert!(node.run(&mut handler).is_err());
+    }
+}
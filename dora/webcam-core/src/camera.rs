@@ -0,0 +1,321 @@
+use anyhow::Context;
+use dora_node_api::{MetadataParameters, Parameter};
+use opencv::{
+    prelude::*,
+    videoio::{self, VideoCapture},
+};
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// 元数据参数：实际协商得到的采集分辨率、帧率和像素格式。帧约定中的 `width`/`height`
+/// 是发送的帧尺寸，`FRAME_SCALE` 小于 1 时小于采集分辨率
+pub const CAPTURE_WIDTH_PARAM: &str = "capture_width";
+pub const CAPTURE_HEIGHT_PARAM: &str = "capture_height";
+pub const FPS_PARAM: &str = "fps";
+pub const FOURCC_PARAM: &str = "fourcc";
+
+/// 四字符像素格式代码，例如 `MJPG`、`YUYV`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FourCC([u8; 4]);
+
+impl FourCC {
+    pub fn code(&self) -> i32 {
+        i32::from_le_bytes(self.0)
+    }
+
+    /// 从 `CAP_PROP_FOURCC` 读回的数值还原，无法识别时返回 `None`
+    pub fn from_code(code: f64) -> Option<Self> {
+        let bytes = (code as i32).to_le_bytes();
+        bytes
+            .iter()
+            .all(|b| b.is_ascii_graphic() || *b == b' ')
+            .then_some(FourCC(bytes))
+    }
+}
+
+impl FromStr for FourCC {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s.trim().to_ascii_uppercase().into_bytes();
+        match <[u8; 4]>::try_from(bytes) {
+            Ok(code) if code.iter().all(u8::is_ascii_alphanumeric) => Ok(FourCC(code)),
+            _ => Err(format!(
+                "`{s}` 不是四个字母或数字组成的像素格式，例如 MJPG、YUYV"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FourCC {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&String::from_utf8_lossy(&self.0))
+    }
+}
+
+/// 曝光设置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Exposure {
+    Auto,
+    /// 手动曝光值，单位和范围由驱动决定（V4L2 通常为 100µs）
+    Manual(f64),
+}
+
+impl FromStr for Exposure {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "auto" => Ok(Exposure::Auto),
+            value => value
+                .parse()
+                .map(Exposure::Manual)
+                .map_err(|_| format!("`{s}` 不是 auto 或曝光数值")),
+        }
+    }
+}
+
+/// 摄像头的采集参数，未设置的项保持驱动默认值
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CameraSettings {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fps: Option<f64>,
+    pub fourcc: Option<FourCC>,
+    pub exposure: Option<Exposure>,
+}
+
+fn optional<T>(key: &str) -> Result<Option<T>, Box<dyn Error>>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    match env::var(key) {
+        Ok(value) if value.trim().is_empty() => Ok(None),
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|e| format!("{key}={value} 无效: {e}").into()),
+        Err(_) => Ok(None),
+    }
+}
+
+impl CameraSettings {
+    /// 从 `CAMERA_WIDTH`、`CAMERA_HEIGHT`、`CAMERA_FPS`、`CAMERA_FOURCC`、`CAMERA_EXPOSURE` 读取
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let settings = Self {
+            width: optional("CAMERA_WIDTH")?,
+            height: optional("CAMERA_HEIGHT")?,
+            fps: optional("CAMERA_FPS")?,
+            fourcc: optional("CAMERA_FOURCC")?,
+            exposure: optional("CAMERA_EXPOSURE")?,
+        };
+        if settings.width == Some(0) || settings.height == Some(0) {
+            return Err("CAMERA_WIDTH/CAMERA_HEIGHT 必须大于 0".into());
+        }
+        if settings
+            .fps
+            .is_some_and(|fps| !fps.is_finite() || fps <= 0.0)
+        {
+            return Err("CAMERA_FPS 必须大于 0".into());
+        }
+        Ok(settings)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 把设置写入摄像头并读回实际生效的格式；设备拒绝某项设置或协商结果与请求不符时返回错误
+    pub fn apply(&self, camera: &mut VideoCapture) -> Result<CaptureFormat, Box<dyn Error>> {
+        // V4L2 需要先设置像素格式，分辨率才能按该格式协商
+        if let Some(fourcc) = self.fourcc {
+            set(
+                camera,
+                videoio::CAP_PROP_FOURCC,
+                fourcc.code() as f64,
+                "CAMERA_FOURCC",
+                &fourcc,
+            )?;
+        }
+        if let Some(width) = self.width {
+            set(
+                camera,
+                videoio::CAP_PROP_FRAME_WIDTH,
+                width as f64,
+                "CAMERA_WIDTH",
+                &width,
+            )?;
+        }
+        if let Some(height) = self.height {
+            set(
+                camera,
+                videoio::CAP_PROP_FRAME_HEIGHT,
+                height as f64,
+                "CAMERA_HEIGHT",
+                &height,
+            )?;
+        }
+        if let Some(fps) = self.fps {
+            set(camera, videoio::CAP_PROP_FPS, fps, "CAMERA_FPS", &fps)?;
+        }
+        // 自动曝光的取值沿用 V4L2 后端的约定：0.75 自动，0.25 手动
+        match self.exposure {
+            Some(Exposure::Auto) => {
+                set(
+                    camera,
+                    videoio::CAP_PROP_AUTO_EXPOSURE,
+                    0.75,
+                    "CAMERA_EXPOSURE",
+                    &"auto",
+                )?;
+            }
+            Some(Exposure::Manual(value)) => {
+                set(
+                    camera,
+                    videoio::CAP_PROP_AUTO_EXPOSURE,
+                    0.25,
+                    "CAMERA_EXPOSURE",
+                    &"manual",
+                )?;
+                set(
+                    camera,
+                    videoio::CAP_PROP_EXPOSURE,
+                    value,
+                    "CAMERA_EXPOSURE",
+                    &value,
+                )?;
+            }
+            None => {}
+        }
+
+        let format = CaptureFormat::read(camera)?;
+        let size_rejected = self.width.is_some_and(|w| w != format.width)
+            || self.height.is_some_and(|h| h != format.height);
+        let fourcc_rejected = self.fourcc.is_some() && self.fourcc != format.fourcc;
+        if size_rejected || fourcc_rejected {
+            return Err(format!(
+                "摄像头不支持请求的格式 {}，实际协商为 {format}",
+                self.requested()
+            )
+            .into());
+        }
+        // 不少驱动把帧率取整到支持的档位，只提示不报错
+        if let Some(fps) = self.fps {
+            if (fps - format.fps).abs() > 0.5 {
+                eprintln!("摄像头帧率协商为 {:.1}，与请求的 {fps} 不同", format.fps);
+            }
+        }
+        Ok(format)
+    }
+
+    /// 请求的格式，未设置的项显示为 `*`
+    fn requested(&self) -> String {
+        let show = |value: Option<String>| value.unwrap_or_else(|| "*".to_owned());
+        format!(
+            "{}x{} @ {} fps {}",
+            show(self.width.map(|w| w.to_string())),
+            show(self.height.map(|h| h.to_string())),
+            show(self.fps.map(|f| f.to_string())),
+            show(self.fourcc.map(|c| c.to_string()))
+        )
+    }
+}
+
+fn set(
+    camera: &mut VideoCapture,
+    property: i32,
+    value: f64,
+    key: &str,
+    shown: &dyn fmt::Display,
+) -> Result<(), Box<dyn Error>> {
+    let accepted = camera
+        .set(property, value)
+        .with_context(|| format!("Failed to set {key}"))?;
+    if !accepted {
+        return Err(format!("摄像头拒绝了 {key}={shown}").into());
+    }
+    Ok(())
+}
+
+/// 采集设备实际使用的格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaptureFormat {
+    pub width: u32,
+    pub height: u32,
+    pub fps: f64,
+    pub fourcc: Option<FourCC>,
+}
+
+impl CaptureFormat {
+    pub fn read(capture: &VideoCapture) -> Result<Self, Box<dyn Error>> {
+        let get = |property: i32| {
+            capture
+                .get(property)
+                .context("Failed to read capture property")
+        };
+        Ok(Self {
+            width: get(videoio::CAP_PROP_FRAME_WIDTH)? as u32,
+            height: get(videoio::CAP_PROP_FRAME_HEIGHT)? as u32,
+            fps: get(videoio::CAP_PROP_FPS)?,
+            fourcc: FourCC::from_code(get(videoio::CAP_PROP_FOURCC)?),
+        })
+    }
+
+    /// 写入输出元数据，下游据此得知实际的采集分辨率、帧率和像素格式
+    pub fn insert_into(&self, parameters: &mut MetadataParameters) {
+        parameters.insert(
+            CAPTURE_WIDTH_PARAM.to_owned(),
+            Parameter::Integer(self.width as i64),
+        );
+        parameters.insert(
+            CAPTURE_HEIGHT_PARAM.to_owned(),
+            Parameter::Integer(self.height as i64),
+        );
+        parameters.insert(FPS_PARAM.to_owned(), Parameter::Float(self.fps));
+        if let Some(fourcc) = self.fourcc {
+            parameters.insert(
+                FOURCC_PARAM.to_owned(),
+                Parameter::String(fourcc.to_string()),
+            );
+        }
+    }
+}
+
+impl fmt::Display for CaptureFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{} @ {:.1} fps", self.width, self.height, self.fps)?;
+        if let Some(fourcc) = self.fourcc {
+            write!(f, " {fourcc}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fourcc_and_exposure() {
+        let mjpg: FourCC = "mjpg".parse().unwrap();
+        assert_eq!(mjpg.to_string(), "MJPG");
+        assert_eq!(FourCC::from_code(mjpg.code() as f64), Some(mjpg));
+        assert_eq!(
+            "YUYV".parse::<FourCC>().unwrap().code(),
+            i32::from_le_bytes(*b"YUYV")
+        );
+        assert!("H26".parse::<FourCC>().is_err());
+        assert_eq!(FourCC::from_code(0.0), None);
+
+        assert_eq!("auto".parse::<Exposure>().unwrap(), Exposure::Auto);
+        assert_eq!(
+            " 150 ".parse::<Exposure>().unwrap(),
+            Exposure::Manual(150.0)
+        );
+        assert!("bright".parse::<Exposure>().is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use image_frame::{EncodeOptions, Encoding};
+use std::env;
+use std::error::Error;
+use std::time::Duration;
+
+/// 根据 dataflow.yml 中的 `FRAME_ENCODING`（默认 jpeg）、`FRAME_QUALITY`（默认 OpenCV 默认值）
+/// 和 `FRAME_SCALE`（默认 1.0）读取编码设置
+pub fn options_from_env() -> Result<EncodeOptions, Box<dyn Error>> {
+    let encoding: Encoding = env::var("FRAME_ENCODING")
+        .unwrap_or_else(|_| "jpeg".to_owned())
+        .parse()?;
+    let quality = match env::var("FRAME_QUALITY") {
+        Ok(value) if !value.trim().is_empty() => Some(
+            value
+                .trim()
+                .parse()
+                .map_err(|e| format!("FRAME_QUALITY={value} 无效: {e}"))?,
+        ),
+        _ => None,
+    };
+    let scale = match env::var("FRAME_SCALE") {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map_err(|e| format!("FRAME_SCALE={value} 无效: {e}"))?,
+        Err(_) => 1.0,
+    };
+    Ok(EncodeOptions::new(encoding, quality, scale)?)
+}
+
+/// 统计编码后的帧大小和编码耗时，每 `interval` 帧汇总一次
+pub struct EncodeStats {
+    interval: usize,
+    frames: usize,
+    total_bytes: usize,
+    min_bytes: usize,
+    max_bytes: usize,
+    encode_time: Duration,
+}
+
+impl EncodeStats {
+    pub fn new(interval: usize) -> Self {
+        Self {
+            interval,
+            frames: 0,
+            total_bytes: 0,
+            min_bytes: usize::MAX,
+            max_bytes: 0,
+            encode_time: Duration::ZERO,
+        }
+    }
+
+    /// `FRAME_STATS_INTERVAL` 帧汇总一次（默认 100），0 关闭统计
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let interval = match env::var("FRAME_STATS_INTERVAL") {
+            Ok(value) => value
+                .trim()
+                .parse()
+                .map_err(|e| format!("FRAME_STATS_INTERVAL={value} 无效: {e}"))?,
+            Err(_) => 100,
+        };
+        Ok(Self::new(interval))
+    }
+
+    /// 记录一帧，凑满一个统计周期时返回汇总并重新计数
+    pub fn record(&mut self, bytes: usize, elapsed: Duration) -> Option<String> {
+        if self.interval == 0 {
+            return None;
+        }
+        self.frames += 1;
+        self.total_bytes += bytes;
+        self.min_bytes = self.min_bytes.min(bytes);
+        self.max_bytes = self.max_bytes.max(bytes);
+        self.encode_time += elapsed;
+        if self.frames < self.interval {
+            return None;
+        }
+        let summary = format!(
+            "最近 {} 帧平均 {:.1} KB (最小 {:.1} KB，最大 {:.1} KB)，平均编码耗时 {:.2} ms",
+            self.frames,
+            self.total_bytes as f64 / self.frames as f64 / 1024.0,
+            self.min_bytes as f64 / 1024.0,
+            self.max_bytes as f64 / 1024.0,
+            self.encode_time.as_secs_f64() * 1000.0 / self.frames as f64
+        );
+        *self = Self::new(self.interval);
+        Some(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_are_summarised_once_per_interval() {
+        let mut stats = EncodeStats::new(2);
+        assert_eq!(stats.record(1024, Duration::from_millis(1)), None);
+        assert_eq!(
+            stats.record(3072, Duration::from_millis(3)).as_deref(),
+            Some("最近 2 帧平均 2.0 KB (最小 1.0 KB，最大 3.0 KB)，平均编码耗时 2.00 ms")
+        );
+        // 汇总后重新计数
+        assert_eq!(stats.record(2048, Duration::from_millis(1)), None);
+
+        let mut off = EncodeStats::new(0);
+        assert_eq!(off.record(1024, Duration::from_millis(1)), None);
+    }
+}
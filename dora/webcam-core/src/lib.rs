@@ -0,0 +1,13 @@
+//! webcam 与 viewer 节点共用的采集和输出代码，dora-webacm-rust、dora-yolo-rust 和 dora-webots-rust
+//! 都依赖本库
+//!
+//! - [`source`]：按 `VIDEO_SOURCE` 打开摄像头、视频文件、图片目录或网络流
+//! - [`camera`]：摄像头分辨率、帧率、像素格式和曝光设置，以及实际协商得到的采集格式
+//! - [`encoder`]：从 `FRAME_ENCODING` 等环境变量读取编码设置，统计编码后的帧大小
+//! - `sink`（`viewer` 特性）：按 `VIEWER_OUTPUT` 显示窗口，或写入视频文件、PNG 序列
+
+pub mod camera;
+pub mod encoder;
+#[cfg(feature = "viewer")]
+pub mod sink;
+pub mod source;
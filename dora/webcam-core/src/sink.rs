@@ -0,0 +1,265 @@
+use anyhow::Context;
+use opencv::{
+    core::{Size, Vector},
+    highgui, imgcodecs, imgproc,
+    prelude::*,
+    videoio::VideoWriter,
+};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 按视频文件写入的扩展名及其默认编码
+const VIDEO_CODECS: [(&str, &str); 4] = [
+    ("mp4", "mp4v"),
+    ("avi", "MJPG"),
+    ("mkv", "MJPG"),
+    ("mov", "mp4v"),
+];
+
+/// `VIEWER_OUTPUT` 指定的输出方式
+#[derive(Debug, Clone, PartialEq)]
+pub enum SinkKind {
+    /// highgui 窗口
+    Window,
+    /// 用 `VideoWriter` 写入视频文件
+    Video(PathBuf),
+    /// 在目录中写入按序号命名的 PNG
+    Images(PathBuf),
+}
+
+impl SinkKind {
+    /// `window` 为窗口，扩展名为 mp4/avi/mkv/mov 的为视频文件，其余为 PNG 目录
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        if value.is_empty() || value == "window" {
+            return SinkKind::Window;
+        }
+        let path = PathBuf::from(value);
+        if default_codec(&path).is_some() {
+            SinkKind::Video(path)
+        } else {
+            SinkKind::Images(path)
+        }
+    }
+}
+
+fn default_codec(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_string_lossy().to_ascii_lowercase();
+    VIDEO_CODECS
+        .iter()
+        .find(|(known, _)| *known == ext)
+        .map(|(_, codec)| *codec)
+}
+
+/// 四字符编码转为 `VideoWriter` 使用的整数
+fn fourcc(codec: &str) -> Result<i32, Box<dyn Error>> {
+    let code: [u8; 4] = codec
+        .as_bytes()
+        .try_into()
+        .map_err(|_| format!("VIEWER_CODEC={codec} 应为四个字符，例如 mp4v、MJPG、avc1"))?;
+    Ok(i32::from_le_bytes(code))
+}
+
+fn open_video(
+    path: &Path,
+    codec: &str,
+    fps: f64,
+    size: Size,
+) -> Result<VideoWriter, Box<dyn Error>> {
+    let video = VideoWriter::new(&path.to_string_lossy(), fourcc(codec)?, fps, size, true)
+        .context("Failed to create video writer")?;
+    if !video.is_opened().context("Failed to check video writer")? {
+        return Err(format!(
+            "无法以 {codec} 编码写入 {}，请换用其他 VIEWER_CODEC",
+            path.display()
+        )
+        .into());
+    }
+    Ok(video)
+}
+
+enum Output {
+    Window,
+    /// 第一帧到达时按其尺寸创建 `VideoWriter`
+    Video {
+        path: PathBuf,
+        codec: String,
+        writer: Option<(VideoWriter, Size)>,
+    },
+    Images {
+        dir: PathBuf,
+    },
+}
+
+/// 标注后的帧的去处：窗口，或无显示器时的视频文件、PNG 序列
+pub struct FrameSink {
+    window_name: String,
+    fps: f64,
+    output: Output,
+    frames: u64,
+}
+
+impl FrameSink {
+    /// 根据 dataflow.yml 中的 `VIEWER_OUTPUT`（默认 window）、`VIEWER_FPS`（默认 10，应与帧率一致）
+    /// 和 `VIEWER_CODEC`（默认按扩展名选择）创建输出
+    pub fn from_env(window_name: &str) -> Result<Self, Box<dyn Error>> {
+        let kind = SinkKind::parse(&env::var("VIEWER_OUTPUT").unwrap_or_default());
+        let fps = match env::var("VIEWER_FPS") {
+            Ok(value) => value
+                .trim()
+                .parse()
+                .map_err(|e| format!("VIEWER_FPS={value} 无效: {e}"))?,
+            Err(_) => 10.0,
+        };
+        if !fps.is_finite() || fps <= 0.0 {
+            return Err(format!("VIEWER_FPS 必须大于 0，收到 {fps}").into());
+        }
+        let codec = env::var("VIEWER_CODEC").ok();
+        Self::open(kind, window_name, fps, codec)
+    }
+
+    pub fn open(
+        kind: SinkKind,
+        window_name: &str,
+        fps: f64,
+        codec: Option<String>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let output = match kind {
+            SinkKind::Window => {
+                // 创建一个用于显示的窗口
+                highgui::named_window(window_name, highgui::WINDOW_NORMAL)
+                    .context("Failed to create highgui window")?;
+                Output::Window
+            }
+            SinkKind::Video(path) => {
+                let codec = match codec {
+                    Some(codec) => codec,
+                    None => default_codec(&path).unwrap_or("mp4v").to_owned(),
+                };
+                // 提前检查编码名，避免第一帧到达时才报错
+                fourcc(&codec)?;
+                if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("无法创建目录 {}: {e}", parent.display()))?;
+                }
+                Output::Video {
+                    path,
+                    codec,
+                    writer: None,
+                }
+            }
+            SinkKind::Images(dir) => {
+                fs::create_dir_all(&dir)
+                    .map_err(|e| format!("无法创建目录 {}: {e}", dir.display()))?;
+                Output::Images { dir }
+            }
+        };
+        Ok(Self {
+            window_name: window_name.to_owned(),
+            fps,
+            output,
+            frames: 0,
+        })
+    }
+
+    /// 启动日志中显示的输出描述
+    pub fn describe(&self) -> String {
+        match &self.output {
+            Output::Window => format!("窗口 {}", self.window_name),
+            Output::Video { path, codec, .. } => {
+                format!("视频文件 {} ({codec}, {} fps)", path.display(), self.fps)
+            }
+            Output::Images { dir } => format!("PNG 目录 {}", dir.display()),
+        }
+    }
+
+    /// 显示或写入一帧，窗口模式下返回 `wait_key` 读到的按键
+    pub fn show(&mut self, frame: &Mat) -> Result<Option<i32>, Box<dyn Error>> {
+        let index = self.frames;
+        self.frames += 1;
+        match &mut self.output {
+            Output::Window => {
+                highgui::imshow(&self.window_name, frame).context("Failed to imshow frame")?;
+                // 必须调用 wait_key 来处理 GUI 事件
+                let key = highgui::wait_key(1).context("Failed to wait_key")?;
+                return Ok((key >= 0).then_some(key));
+            }
+            Output::Video {
+                path,
+                codec,
+                writer,
+            } => {
+                let size = frame.size().context("Failed to get frame size")?;
+                let (video, video_size) = match writer {
+                    Some(opened) => opened,
+                    None => writer.insert((open_video(path, codec, self.fps, size)?, size)),
+                };
+                // 视频帧尺寸固定，尺寸变化的帧缩放到第一帧的尺寸
+                if size == *video_size {
+                    video.write(frame).context("Failed to write video frame")?;
+                } else {
+                    let mut resized = Mat::default();
+                    imgproc::resize(
+                        frame,
+                        &mut resized,
+                        *video_size,
+                        0.0,
+                        0.0,
+                        imgproc::INTER_LINEAR,
+                    )?;
+                    video
+                        .write(&resized)
+                        .context("Failed to write video frame")?;
+                }
+            }
+            Output::Images { dir } => {
+                let path = dir.join(format!("frame_{index:06}.png"));
+                if !imgcodecs::imwrite(&path.to_string_lossy(), frame, &Vector::new())
+                    .context("Failed to write image")?
+                {
+                    return Err(format!("无法写入 {}", path.display()).into());
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// 结束输出，视频文件需要释放 `VideoWriter` 才能写完文件尾
+    pub fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Output::Video {
+            writer: Some((video, _)),
+            ..
+        } = &mut self.output
+        {
+            video.release().context("Failed to finish video file")?;
+        }
+        if !matches!(self.output, Output::Window) {
+            println!("💾 已写入 {} 帧到{}", self.frames, self.describe());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sink_kind_is_inferred_from_value() {
+        assert_eq!(SinkKind::parse(""), SinkKind::Window);
+        assert_eq!(SinkKind::parse("window"), SinkKind::Window);
+        assert_eq!(
+            SinkKind::parse("out/annotated.MP4"),
+            SinkKind::Video(PathBuf::from("out/annotated.MP4"))
+        );
+        assert_eq!(
+            SinkKind::parse("out/frames"),
+            SinkKind::Images(PathBuf::from("out/frames"))
+        );
+        assert_eq!(default_codec(Path::new("a.avi")), Some("MJPG"));
+        assert_eq!(fourcc("MJPG").unwrap(), i32::from_le_bytes(*b"MJPG"));
+        assert!(fourcc("h264x").is_err());
+    }
+}
@@ -0,0 +1,316 @@
+use anyhow::Context;
+use opencv::{
+    imgcodecs,
+    prelude::*,
+    videoio::{self, VideoCapture},
+};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::camera::{CameraSettings, CaptureFormat};
+
+/// 图片序列目录中读取的文件扩展名（不区分大小写）
+const IMAGE_EXTENSIONS: [&str; 7] = ["jpg", "jpeg", "png", "bmp", "tif", "tiff", "webp"];
+
+/// `VIDEO_SOURCE` 指定的帧来源
+#[derive(Debug, Clone, PartialEq)]
+pub enum SourceKind {
+    /// 摄像头编号
+    Camera(i32),
+    /// V4L2 设备路径，例如 `/dev/video2`
+    Device(PathBuf),
+    /// RTSP / HTTP 等网络视频流地址
+    Stream(String),
+    /// 本地视频文件
+    File(PathBuf),
+    /// 图片目录，按文件名排序逐帧发送
+    Images(PathBuf),
+}
+
+impl SourceKind {
+    /// 整数为摄像头编号，`/dev/` 下的路径为摄像头设备，带 `://` 的为网络流，目录为图片序列，
+    /// 其余按视频文件打开
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        if let Ok(index) = value.parse() {
+            return SourceKind::Camera(index);
+        }
+        if value.contains("://") {
+            return SourceKind::Stream(value.to_owned());
+        }
+        let path = PathBuf::from(value);
+        if path.starts_with("/dev") {
+            SourceKind::Device(path)
+        } else if path.is_dir() {
+            SourceKind::Images(path)
+        } else {
+            SourceKind::File(path)
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            SourceKind::Camera(index) => format!("摄像头 {index}"),
+            SourceKind::Device(path) => format!("摄像头 {}", path.display()),
+            SourceKind::Stream(url) => format!("视频流 {url}"),
+            SourceKind::File(path) => format!("视频文件 {}", path.display()),
+            SourceKind::Images(dir) => format!("图片目录 {}", dir.display()),
+        }
+    }
+}
+
+enum Input {
+    Capture(VideoCapture),
+    Images { paths: Vec<PathBuf>, next: usize },
+}
+
+/// 摄像头、视频文件、网络流或图片目录，每次 `read` 取出一帧
+pub struct FrameSource {
+    kind: SourceKind,
+    /// 视频文件和图片目录播放完后是否从头开始
+    looped: bool,
+    input: Input,
+    /// 摄像头、视频文件和网络流实际使用的格式
+    format: Option<CaptureFormat>,
+    finished: bool,
+}
+
+impl FrameSource {
+    /// 根据 dataflow.yml 中的 `VIDEO_SOURCE`（默认摄像头 0）和 `VIDEO_LOOP`（默认 false）打开帧来源，
+    /// 摄像头按 `CAMERA_*` 设置采集参数
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let kind = SourceKind::parse(&env::var("VIDEO_SOURCE").unwrap_or_else(|_| "0".to_owned()));
+        let looped = match env::var("VIDEO_LOOP") {
+            Ok(value) => value
+                .trim()
+                .parse()
+                .map_err(|e| format!("VIDEO_LOOP={value} 无效: {e}"))?,
+            Err(_) => false,
+        };
+        Self::open(kind, looped, CameraSettings::from_env()?)
+    }
+
+    pub fn open(
+        kind: SourceKind,
+        looped: bool,
+        settings: CameraSettings,
+    ) -> Result<Self, Box<dyn Error>> {
+        let is_camera = matches!(kind, SourceKind::Camera(_) | SourceKind::Device(_));
+        if !is_camera && !settings.is_empty() {
+            eprintln!("CAMERA_* 设置只对摄像头生效，已忽略");
+        }
+        let mut input = match &kind {
+            SourceKind::Camera(index) => Input::Capture(open_camera(*index)?),
+            SourceKind::Device(path) => Input::Capture(open_device(path)?),
+            SourceKind::Stream(url) => Input::Capture(open_video(url)?),
+            SourceKind::File(path) => Input::Capture(open_video(&path.to_string_lossy())?),
+            SourceKind::Images(dir) => Input::Images {
+                paths: list_images(dir)?,
+                next: 0,
+            },
+        };
+        let format = match &mut input {
+            Input::Capture(camera) if is_camera => Some(
+                settings
+                    .apply(camera)
+                    .map_err(|e| format!("{}: {e}", kind.describe()))?,
+            ),
+            Input::Capture(capture) => Some(CaptureFormat::read(capture)?),
+            Input::Images { .. } => None,
+        };
+        Ok(Self {
+            kind,
+            looped,
+            input,
+            format,
+            finished: false,
+        })
+    }
+
+    /// 启动日志中显示的来源描述
+    pub fn describe(&self) -> String {
+        let looped = if self.looped { "，循环播放" } else { "" };
+        match (&self.kind, &self.input) {
+            (SourceKind::File(path), _) => format!("视频文件 {}{looped}", path.display()),
+            (SourceKind::Images(dir), Input::Images { paths, .. }) => {
+                format!("图片目录 {} ({} 张{looped})", dir.display(), paths.len())
+            }
+            (kind, _) => kind.describe(),
+        }
+    }
+
+    /// 摄像头协商得到的格式，或视频文件、网络流的格式；图片目录为 `None`
+    pub fn format(&self) -> Option<&CaptureFormat> {
+        self.format.as_ref()
+    }
+
+    /// 视频文件或图片目录已播放完且不循环，之后不再产生帧
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    /// 读取下一帧，本次没有可用的帧时返回 `None`
+    pub fn read(&mut self) -> Result<Option<Mat>, Box<dyn Error>> {
+        if self.finished {
+            return Ok(None);
+        }
+        match &mut self.input {
+            Input::Capture(capture) => {
+                if let Some(frame) = read_capture(capture)? {
+                    return Ok(Some(frame));
+                }
+                match &self.kind {
+                    // 摄像头偶尔读不到帧，下个 tick 再试
+                    SourceKind::Camera(_) | SourceKind::Device(_) => Ok(None),
+                    // 网络流断开后重新连接，连接失败时下个 tick 再试
+                    SourceKind::Stream(url) => {
+                        eprintln!("视频流 {url} 读取失败，重新连接");
+                        match open_video(url) {
+                            Ok(reopened) => *capture = reopened,
+                            Err(e) => eprintln!("重新连接失败: {e}"),
+                        }
+                        Ok(None)
+                    }
+                    SourceKind::File(_) if self.looped => {
+                        capture
+                            .set(videoio::CAP_PROP_POS_FRAMES, 0.0)
+                            .context("Failed to rewind video file")?;
+                        let frame = read_capture(capture)?;
+                        // 空视频无法循环
+                        self.finished = frame.is_none();
+                        Ok(frame)
+                    }
+                    _ => {
+                        self.finished = true;
+                        Ok(None)
+                    }
+                }
+            }
+            Input::Images { paths, next } => {
+                // 最多尝试一轮，跳过无法解码的文件
+                for _ in 0..paths.len() {
+                    if *next == paths.len() {
+                        if !self.looped {
+                            break;
+                        }
+                        *next = 0;
+                    }
+                    let path = &paths[*next];
+                    *next += 1;
+                    let frame = imgcodecs::imread(&path.to_string_lossy(), imgcodecs::IMREAD_COLOR)
+                        .context("Failed to read image")?;
+                    if frame.empty() {
+                        eprintln!("无法解码图片 {}，已跳过", path.display());
+                        continue;
+                    }
+                    return Ok(Some(frame));
+                }
+                self.finished = *next == paths.len() && !self.looped;
+                Ok(None)
+            }
+        }
+    }
+}
+
+fn read_capture(capture: &mut VideoCapture) -> Result<Option<Mat>, Box<dyn Error>> {
+    let mut frame = Mat::default();
+    let ok = capture
+        .read(&mut frame)
+        .context("Failed to read frame from capture")?;
+    Ok((ok && !frame.empty()).then_some(frame))
+}
+
+fn open_camera(index: i32) -> Result<VideoCapture, Box<dyn Error>> {
+    let camera =
+        VideoCapture::new(index, videoio::CAP_ANY).context("Failed to create video capture")?;
+    // 尝试打开摄像头
+    if !VideoCapture::is_opened(&camera).context("Failed to check if camera is open")? {
+        // 在 Mac M1 上，有时需要延迟以确保摄像头初始化完成
+        thread::sleep(Duration::from_millis(500));
+        if !VideoCapture::is_opened(&camera).context("Camera still not open after delay")? {
+            return Err(format!("Could not open camera {index} or check its status.").into());
+        }
+    }
+    Ok(camera)
+}
+
+/// 按路径打开 V4L2 摄像头设备
+fn open_device(path: &Path) -> Result<VideoCapture, Box<dyn Error>> {
+    let camera = VideoCapture::from_file(&path.to_string_lossy(), videoio::CAP_V4L2)
+        .context("Failed to create video capture")?;
+    if !VideoCapture::is_opened(&camera).context("Failed to check if camera is open")? {
+        return Err(format!("无法打开摄像头设备 {}", path.display()).into());
+    }
+    Ok(camera)
+}
+
+/// 视频文件和网络流都由 OpenCV 按名称打开
+fn open_video(name: &str) -> Result<VideoCapture, Box<dyn Error>> {
+    let capture = VideoCapture::from_file(name, videoio::CAP_ANY)
+        .context("Failed to create video capture")?;
+    if !VideoCapture::is_opened(&capture).context("Failed to check if video is open")? {
+        return Err(format!("无法打开视频 {name}").into());
+    }
+    Ok(capture)
+}
+
+/// 目录中扩展名为图片的文件，按文件名排序
+fn list_images(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("无法读取图片目录 {}: {e}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path.extension().is_some_and(|ext| {
+                    IMAGE_EXTENSIONS.contains(&ext.to_string_lossy().to_ascii_lowercase().as_str())
+                })
+        })
+        .collect();
+    if paths.is_empty() {
+        return Err(format!("图片目录 {} 中没有图片", dir.display()).into());
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_kind_is_inferred_from_value() {
+        let dir = env::temp_dir().join(format!("webcam-frames-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["b.PNG", "a.jpg", "notes.txt"] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+
+        assert_eq!(SourceKind::parse("0"), SourceKind::Camera(0));
+        assert_eq!(SourceKind::parse(" 2 "), SourceKind::Camera(2));
+        assert_eq!(
+            SourceKind::parse("/dev/video2"),
+            SourceKind::Device(PathBuf::from("/dev/video2"))
+        );
+        assert_eq!(
+            SourceKind::parse("rtsp://192.168.1.10:554/stream"),
+            SourceKind::Stream("rtsp://192.168.1.10:554/stream".to_owned())
+        );
+        assert_eq!(
+            SourceKind::parse("videos/test.mp4"),
+            SourceKind::File(PathBuf::from("videos/test.mp4"))
+        );
+        assert_eq!(
+            SourceKind::parse(&dir.to_string_lossy()),
+            SourceKind::Images(dir.clone())
+        );
+        assert_eq!(
+            list_images(&dir).unwrap(),
+            [dir.join("a.jpg"), dir.join("b.PNG")]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}